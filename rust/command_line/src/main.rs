@@ -1,21 +1,26 @@
 use calamine::{Reader, Xlsx};
 use clap::Parser;
 use indicatif::{ProgressBar, ProgressStyle};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
-use std::collections::HashSet;
+use std::collections::HashMap;
 use std::fs;
 use std::fs::File;
-use std::io::BufReader;
+use std::io::{BufReader, BufWriter};
 use std::path::PathBuf;
 use std::process;
 use std::rc::Rc;
+use symbolic_regression::expression_tree::random::SeededRandom;
 use symbolic_regression::expression_tree::ExpressionTree;
 use symbolic_regression::model::default::{
-    GenerationSize, Individual, Model, ModelResult, StopCriterion, StopReason,
+    AdaptiveMutation, CallbackObserver, Checkpoint, Diversity, Genealogy, GenerationSize,
+    GenerationStats, HallOfFame, Individual, Model, ModelResult, Replacement, Restart,
+    StopCriterion, StopReason,
 };
-use symbolic_regression::model::settings::Settings;
-use symbolic_regression::model::{FitnessError, InputData};
+use symbolic_regression::model::settings::{
+    BloatLimits, FunctionDeclaration, Initialization, NamedConstant, Settings,
+};
+use symbolic_regression::model::{CrossoverMethod, FitnessError, InputData};
 
 #[derive(Parser)]
 #[clap(author, version, about, long_about = None)]
@@ -32,6 +37,25 @@ struct Cli {
     /// Log every <LOG> generation.
     #[clap(long, short = 'e', value_parser, default_value = "25")]
     log_every: usize,
+    /// Path to json file for saving checkpoints.
+    #[clap(long = "checkpoint", value_parser)]
+    checkpoint_path: Option<PathBuf>,
+    /// Save a checkpoint every <CHECKPOINT_EVERY> generation.
+    #[clap(long, value_parser = clap::value_parser!(u32).range(1..), default_value = "10")]
+    checkpoint_every: u32,
+    /// Path to checkpoint json file to resume the run from.
+    /// The input data and the configuration must be the same as in the checkpointed run.
+    #[clap(long = "resume", value_parser)]
+    resume_path: Option<PathBuf>,
+    /// Path to json file for saving the ancestry of the result individual.
+    #[clap(long = "genealogy", value_parser)]
+    genealogy_path: Option<PathBuf>,
+    /// Print result constants within the relative tolerance of named constants by name.
+    #[clap(long = "named-constants", value_parser)]
+    named_constant_tolerance: Option<f64>,
+    /// Seed of the random generator.
+    #[clap(long, value_parser)]
+    seed: Option<u64>,
 }
 
 #[derive(Deserialize)]
@@ -40,11 +64,48 @@ struct Config {
     generation_size: GenerationSize,
     auxiliary_expressions: Vec<String>,
     complexity_impact: Option<f32>,
+    constants: Option<Vec<NamedConstant>>,
+    variadic_functions: Option<bool>,
+    piecewise_operations: Option<bool>,
+    special_functions: Option<bool>,
+    functions: Option<Vec<FunctionDeclaration>>,
+    replacement: Option<Replacement>,
+    initialization: Option<Initialization>,
+    bloat_limits: Option<BloatLimits>,
+    mutation_probabilities: Option<HashMap<String, f64>>,
+    crossover: Option<CrossoverMethod>,
+    adaptive_mutation: Option<AdaptiveMutation>,
+    diversity: Option<Diversity>,
+    restart: Option<Restart>,
+    hall_of_fame: Option<HallOfFame>,
+}
+
+/// Options of printing result expression trees.
+struct ResultFormat {
+    output_variable: String,
+    constants: Vec<NamedConstant>,
+    named_constant_tolerance: Option<f64>,
+}
+
+/// Model options set after the model creation.
+struct ModelOptions {
+    replacement: Replacement,
+    adaptive_mutation: Option<AdaptiveMutation>,
+    diversity: Option<Diversity>,
+    restart: Option<Restart>,
+    hall_of_fame: HallOfFame,
+}
+
+#[derive(Serialize)]
+struct GenerationLog {
+    stats: GenerationStats,
+    individuals: Vec<Rc<Individual>>,
 }
 
 struct RunResult {
     model_result: Result<ModelResult, FitnessError>,
-    generations: Vec<Vec<Rc<Individual>>>,
+    generations: Vec<GenerationLog>,
+    ancestry: Vec<Rc<Individual>>,
 }
 
 fn main() {
@@ -56,19 +117,65 @@ fn main() {
         generation_size,
         auxiliary_expressions,
         complexity_impact,
+        constants,
+        variadic_functions,
+        piecewise_operations,
+        special_functions,
+        functions,
+        replacement,
+        initialization,
+        bloat_limits,
+        mutation_probabilities,
+        crossover,
+        adaptive_mutation,
+        diversity,
+        restart,
+        hall_of_fame,
     } = read_config(&cli);
     if let Some(complexity_impact) = complexity_impact {
         settings.complexity_impact = complexity_impact;
     }
+    if let Some(constants) = constants {
+        set_constants(&mut settings, constants);
+    }
+    if variadic_functions.unwrap_or_default() {
+        settings.add_variadic_functions();
+    }
+    if piecewise_operations.unwrap_or_default() {
+        settings.add_piecewise_operations();
+    }
+    if special_functions.unwrap_or_default() {
+        add_special_functions(&mut settings);
+    }
+    if let Some(functions) = functions {
+        declare_functions(&mut settings, functions);
+    }
+    if let Some(initialization) = initialization {
+        settings.initialization = initialization;
+    }
+    if let Some(bloat_limits) = bloat_limits {
+        settings.bloat_limits = bloat_limits;
+    }
+    if let Some(crossover) = crossover {
+        settings.crossover = crossover.create_crossover();
+    }
+    if let Some(mutation_probabilities) = mutation_probabilities {
+        set_mutation_probabilities(&mut settings, mutation_probabilities);
+    }
     let auxiliary_expression_trees = parse_expression_trees(
         &settings,
         auxiliary_expressions,
         &input_data.variables[0..input_data.variables.len() - 1],
     );
-    let output_variable = input_data.variables[input_data.variables.len() - 1].to_owned();
+    let result_format = ResultFormat {
+        output_variable: input_data.variables[input_data.variables.len() - 1].to_owned(),
+        constants: settings.constants.clone(),
+        named_constant_tolerance: cli.named_constant_tolerance,
+    };
     let RunResult {
         model_result,
         generations,
+        ancestry,
     } = run_model(
         &cli,
         settings,
@@ -76,11 +183,21 @@ fn main() {
         stop_criterion,
         generation_size,
         auxiliary_expression_trees,
+        ModelOptions {
+            replacement: replacement.unwrap_or_default(),
+            adaptive_mutation,
+            diversity,
+            restart,
+            hall_of_fame: hall_of_fame.unwrap_or_default(),
+        },
     );
-    print_model_result(output_variable, model_result);
+    print_model_result(&result_format, model_result);
     if let Some(path) = &cli.log_path {
         output_log(path, generations);
     }
+    if let Some(path) = &cli.genealogy_path {
+        output_genealogy(path, ancestry);
+    }
 }
 
 fn read_input_data(cli: &Cli) -> InputData {
@@ -134,35 +251,66 @@ fn read_config(cli: &Cli) -> Config {
     }
 }
 
+fn set_mutation_probabilities(settings: &mut Settings, probabilities: HashMap<String, f64>) {
+    for (name, probability) in probabilities {
+        match settings.find_mutation_by_name_mut(&name) {
+            Some(mutation) => mutation.probability = probability,
+            None => exit_with_error(&format!(r#"Unknown mutation "{}"."#, name)),
+        }
+    }
+}
+
+/// Add user-defined constants replacing the ones with the same names.
+fn set_constants(settings: &mut Settings, constants: Vec<NamedConstant>) {
+    for constant in constants {
+        settings
+            .constants
+            .retain(|settings_constant| settings_constant.name != constant.name);
+        settings.constants.push(constant);
+    }
+}
+
+#[cfg(feature = "special-functions")]
+fn add_special_functions(settings: &mut Settings) {
+    settings.add_special_functions();
+}
+
+#[cfg(not(feature = "special-functions"))]
+fn add_special_functions(_: &mut Settings) {
+    exit_with_error(r#"Special functions require building with the "special-functions" feature."#);
+}
+
+/// Add user-defined functions in the order of declaration.
+fn declare_functions(settings: &mut Settings, functions: Vec<FunctionDeclaration>) {
+    for function in functions {
+        if let Err(err) = settings.declare_function(&function) {
+            exit_with_error(&format!(
+                r#"Can't declare function "{}": {}"#,
+                function.expression, err
+            ));
+        }
+    }
+}
+
 fn parse_expression_trees(
     settings: &Settings,
     expressions: Vec<String>,
     variables: &[String],
 ) -> Vec<ExpressionTree> {
-    let variables_set = HashSet::<&str>::from_iter(variables.iter().map(|v| v.as_str()));
     let mut expression_trees = vec![];
+    let mut messages = vec![];
     for expression in expressions {
-        match ExpressionTree::parse(&expression, settings) {
-            Ok(mut expression_tree) => {
-                let expression_tree_variables = HashSet::<&str>::from_iter(
-                    expression_tree.variables.iter().map(|v| v.as_str()),
-                );
-                if expression_tree_variables.is_subset(&variables_set) {
-                    expression_tree.variables = variables.iter().cloned().collect::<Vec<String>>();
-                    expression_trees.push(expression_tree);
-                } else {
-                    exit_with_error(&format!(
-                        r#"The expression tree for "{}" expression contains {:?} variables, but the input data contains "{:?}" ones."#,
-                        expression, expression_tree.variables, variables
-                    ));
-                }
-            }
-            Err(err) => exit_with_error(&format!(
-                r#"Can't parse expression "{}": "{}"."#,
-                expression, err
+        match ExpressionTree::parse_with_diagnostics(&expression, settings, Some(variables)) {
+            Ok(expression_tree) => expression_trees.push(expression_tree),
+            Err(errors) => messages.push(format!(
+                "Can't parse expression \"{}\":\n{}",
+                expression, errors
             )),
         }
     }
+    if !messages.is_empty() {
+        exit_with_error(&messages.join("\n\n"));
+    }
     expression_trees
 }
 
@@ -173,6 +321,7 @@ fn run_model(
     stop_criterion: StopCriterion,
     generation_size: GenerationSize,
     auxiliary_expression_trees: Vec<ExpressionTree>,
+    options: ModelOptions,
 ) -> RunResult {
     let log = !cli.log_path.is_none();
     let log_every = cli.log_every;
@@ -180,17 +329,43 @@ fn run_model(
     let generation_copy = Rc::clone(&generations);
     let progress_bar = Rc::new(RefCell::new(create_progress_bar(&stop_criterion)));
     let progress_bar_copy = Rc::clone(&progress_bar);
-    let mut model = Model::new(
+    let time_budget = stop_criterion.duration;
+    let random = match cli.seed {
+        Some(seed) => SeededRandom::seeded(seed),
+        None => SeededRandom::from_entropy(),
+    };
+    let mut model = Model::with_random(
         settings,
         input_data,
         stop_criterion,
         generation_size,
         auxiliary_expression_trees,
-        Some(Box::new(move |generation| {
-            if log && progress_bar_copy.borrow().position() as usize % log_every == 0 {
-                generation_copy
-                    .borrow_mut()
-                    .push(generation.iter().cloned().collect::<Vec<Rc<Individual>>>())
+        vec![Box::new(CallbackObserver::new(move |generation, stats| {
+            if log && stats.generation_number as usize % log_every == 0 {
+                generation_copy.borrow_mut().push(GenerationLog {
+                    stats: stats.clone(),
+                    individuals: generation.iter().cloned().collect::<Vec<Rc<Individual>>>(),
+                })
+            }
+            progress_bar_copy.borrow().set_message(format!(
+                "generation: {} best: {:.6e} median: {:.6e} complexity: {:.1} unique: {}{} stagnation: {} restarts: {}",
+                stats.generation_number,
+                stats.best_error,
+                stats.median_error,
+                stats.mean_complexity,
+                stats.unique_structure_number,
+                stats
+                    .diversity
+                    .map(|diversity| format!(" diversity: {:.2}", diversity))
+                    .unwrap_or_default(),
+                stats.without_improvement_generation_number,
+                stats.restart_number
+            ));
+            if let Some(time_budget) = time_budget {
+                let progress_bar = progress_bar_copy.borrow();
+                progress_bar
+                    .set_position(progress_bar.elapsed().as_secs().min(time_budget.as_secs()));
+                return;
             }
             if progress_bar_copy.borrow().position() + 1
                 >= progress_bar_copy.borrow().length().unwrap()
@@ -198,18 +373,106 @@ fn run_model(
                 progress_bar_copy.borrow_mut().inc_length(1000);
             }
             progress_bar_copy.borrow_mut().inc(1);
-        })),
+        }))],
+        random,
     );
-    let model_result = model.run();
+    model.replacement = options.replacement;
+    model.adaptive_mutation = options.adaptive_mutation;
+    model.diversity = options.diversity;
+    model.restart = options.restart;
+    model.hall_of_fame = options.hall_of_fame;
+    if cli.genealogy_path.is_some() {
+        model.genealogy = Some(Genealogy::default());
+    }
+    let model_result = run_steps(cli, &mut model, &progress_bar);
     progress_bar.borrow_mut().finish_and_clear();
+    let ancestry = match (&model_result, &model.genealogy) {
+        (Ok(result), Some(genealogy)) => genealogy.get_ancestry(result.individual.id),
+        _ => vec![],
+    };
     drop(model);
     RunResult {
         model_result,
         generations: generations.take(),
+        ancestry,
+    }
+}
+
+fn run_steps(
+    cli: &Cli,
+    model: &mut Model<SeededRandom>,
+    progress_bar: &RefCell<ProgressBar>,
+) -> Result<ModelResult, FitnessError> {
+    let time_budget = model.stop_criterion.duration.is_some();
+    let mut stopped = match &cli.resume_path {
+        Some(path) => {
+            let state = match model.resume(&read_checkpoint(path)) {
+                Ok(state) => state,
+                Err(err) => exit_with_error(&format!(r#"Can't resume the model: "{}"."#, err)),
+            };
+            if !time_budget {
+                progress_bar
+                    .borrow()
+                    .set_position(state.generation_number as u64 + 1);
+            }
+            state.stop_reason.is_some()
+        }
+        None => model.initialize()?.stop_reason.is_some(),
+    };
+    while !stopped {
+        let generation_number = {
+            let state = model.step()?;
+            stopped = state.stop_reason.is_some();
+            state.generation_number
+        };
+        if let Some(path) = &cli.checkpoint_path {
+            if generation_number % cli.checkpoint_every == 0 {
+                output_checkpoint(path, &model.create_checkpoint());
+            }
+        }
+    }
+    Ok(model.finish())
+}
+
+fn read_checkpoint(path: &PathBuf) -> Checkpoint {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(err) => exit_with_error(&format!(r#"Can't read checkpoint file: "{}"."#, err)),
+    };
+    let reader = BufReader::new(file);
+    match serde_json::from_reader(reader) {
+        Ok(checkpoint) => checkpoint,
+        Err(err) => exit_with_error(&format!(r#"Can't parse checkpoint file: "{}"."#, err)),
+    }
+}
+
+/// Write the checkpoint to a temporary file first, so a crash during writing doesn't corrupt the previous checkpoint.
+fn output_checkpoint(path: &PathBuf, checkpoint: &Checkpoint) {
+    let temporary_path = path.with_extension("tmp");
+    let file = match File::create(&temporary_path) {
+        Ok(file) => file,
+        Err(err) => exit_with_error(&format!(r#"Can't create checkpoint file: "{}"."#, err)),
+    };
+    if let Err(err) = serde_json::to_writer(BufWriter::new(file), checkpoint) {
+        exit_with_error(&format!(r#"Can't serialize checkpoint: "{}"."#, err))
+    }
+    if let Err(err) = fs::rename(&temporary_path, path) {
+        exit_with_error(&format!(r#"Can't save checkpoint file: "{}"."#, err))
     }
 }
 
 fn create_progress_bar(stop_criterion: &StopCriterion) -> ProgressBar {
+    if let Some(duration) = stop_criterion.duration {
+        let pb = ProgressBar::new(duration.as_secs());
+        pb.set_style(
+            ProgressStyle::with_template(
+                "{spinner:.green} [{elapsed_precise}] [{wide_bar:.cyan/blue}] {pos:>7}/{len:7}s {msg}",
+            )
+            .unwrap()
+            .progress_chars("#>-"),
+        );
+        return pb;
+    }
     let pb = if let Some(generation_number) = stop_criterion.generation_number {
         ProgressBar::new(generation_number as u64)
     } else {
@@ -217,7 +480,7 @@ fn create_progress_bar(stop_criterion: &StopCriterion) -> ProgressBar {
     };
     pb.set_style(
         ProgressStyle::with_template(
-            "{spinner:.green} [{elapsed_precise}] [{wide_bar:.cyan/blue}] {pos:>7}/{len:7}",
+            "{spinner:.green} [{elapsed_precise}] [{wide_bar:.cyan/blue}] {pos:>7}/{len:7} {msg}",
         )
         .unwrap()
         .progress_chars("#>-"),
@@ -225,13 +488,37 @@ fn create_progress_bar(stop_criterion: &StopCriterion) -> ProgressBar {
     pb
 }
 
-fn print_model_result(output_variable: String, model_result: Result<ModelResult, FitnessError>) {
+impl ResultFormat {
+    fn format(&self, expression_tree: &ExpressionTree) -> String {
+        match self.named_constant_tolerance {
+            Some(tolerance) => {
+                expression_tree.to_string_with_named_constants(&self.constants, tolerance)
+            }
+            None => expression_tree.to_string(),
+        }
+    }
+}
+
+fn print_model_result(format: &ResultFormat, model_result: Result<ModelResult, FitnessError>) {
     match model_result {
         Ok(result) => {
             println!(
                 "Result function: {} = {}",
-                output_variable, result.individual.expression_tree
+                format.output_variable,
+                format.format(&result.individual.expression_tree)
             );
+            if result.hall_of_fame.len() > 1 {
+                println!("Hall of fame:");
+                for individual in &result.hall_of_fame {
+                    println!(
+                        "{} = {} (error: {:.6e}, complexity: {})",
+                        format.output_variable,
+                        format.format(&individual.expression_tree),
+                        individual.fitness.error,
+                        individual.fitness.complexity
+                    );
+                }
+            }
             match &result.stop_reason {
                 StopReason::Error(error) => println!("The reason for the stop is an error equal to {}", error),
                 StopReason::WithoutImprovement(without_improvement) => println!(
@@ -243,6 +530,26 @@ fn print_model_result(output_variable: String, model_result: Result<ModelResult,
                     "The reason for stopping is the maximum number of generations equal to {}, error equal to {}",
                     generation_number.generation_number,
                     generation_number.error
+                ),
+                StopReason::ObserverRequest(observer_request) => println!(
+                    "The reason for stopping is an observer request at generation {}, error equal to {}",
+                    observer_request.generation_number,
+                    observer_request.error
+                ),
+                StopReason::Duration(duration) => println!(
+                    "The reason for stopping is the time budget exceeded after {:.1} seconds, error equal to {}",
+                    duration.duration.as_secs_f64(),
+                    duration.error
+                ),
+                StopReason::EvaluationNumber(evaluation_number) => println!(
+                    "The reason for stopping is the maximum number of fitness evaluations equal to {}, error equal to {}",
+                    evaluation_number.evaluation_number,
+                    evaluation_number.error
+                ),
+                StopReason::ComplexityError(complexity_error) => println!(
+                    "The reason for stopping is an individual with a complexity equal to {} and an error equal to {}",
+                    complexity_error.complexity,
+                    complexity_error.error
                 )
             };
         }
@@ -250,7 +557,7 @@ fn print_model_result(output_variable: String, model_result: Result<ModelResult,
     }
 }
 
-fn output_log(log_path: &PathBuf, generations: Vec<Vec<Rc<Individual>>>) {
+fn output_log(log_path: &PathBuf, generations: Vec<GenerationLog>) {
     let file = match File::create(log_path) {
         Ok(file) => file,
         Err(err) => exit_with_error(&format!(r#"Can't create log file: "{}"."#, err)),
@@ -260,6 +567,16 @@ fn output_log(log_path: &PathBuf, generations: Vec<Vec<Rc<Individual>>>) {
     }
 }
 
+fn output_genealogy(genealogy_path: &PathBuf, ancestry: Vec<Rc<Individual>>) {
+    let file = match File::create(genealogy_path) {
+        Ok(file) => file,
+        Err(err) => exit_with_error(&format!(r#"Can't create genealogy file: "{}"."#, err)),
+    };
+    if let Err(err) = serde_json::to_writer_pretty(file, &ancestry) {
+        exit_with_error(&format!(r#"Can't serialize the ancestry: "{}"."#, err))
+    }
+}
+
 fn exit_with_error(message: &str) -> ! {
     eprintln!("{}", message);
     process::exit(1)
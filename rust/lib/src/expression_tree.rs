@@ -1,6 +1,7 @@
 //! Expression tree core functionality module.
 pub mod compute;
 pub mod display;
+pub mod latex;
 pub mod output;
 pub mod parser;
 pub mod random;
@@ -1,5 +1,9 @@
 //! Module for computing an expression tree.
-use super::types::{ExpressionTree, Node, Operation, OperationNode, ValueNode};
+use super::types::{
+    Compute, ExpressionTree, Function, FunctionDefinition, Node, Operation, OperationNode,
+    ValueNode,
+};
+use crate::model::settings::Settings;
 use std::fmt;
 
 pub trait Computable {
@@ -54,7 +58,12 @@ impl Computable for Node {
     fn simplify(&mut self) -> () {
         match self {
             Node::Operator(operator_node) => operator_node.simplify(),
-            Node::Function(function_node) => function_node.simplify(),
+            Node::Function(function_node) => {
+                function_node.simplify();
+                if let Some(branch) = function_node.take_constant_branch() {
+                    *self = branch;
+                }
+            }
             Node::Value(value) => value.simplify(),
         }
     }
@@ -86,6 +95,57 @@ impl<T: Operation> Computable for OperationNode<T> {
     }
 }
 
+impl OperationNode<Function> {
+    /// Return the branch chosen by the conditional function `if(condition, a, b)`
+    /// if its condition is a constant other than NaN.
+    /// A user function that is only named `if` is not the conditional one.
+    fn take_constant_branch(&mut self) -> Option<Node> {
+        if self.operation != Settings::get_if_function() {
+            return None;
+        }
+        match self.arguments[0] {
+            Node::Value(ValueNode::Constant(condition)) if !condition.is_nan() => {
+                let index = if condition != 0.0 { 1 } else { 2 };
+                Some(self.arguments.swap_remove(index))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The expression is computed with the parameters equal to the arguments.
+/// A variable that is not a parameter is computed as NaN.
+impl Compute for FunctionDefinition {
+    fn compute(&self, arguments: &[f64]) -> f64 {
+        self.compute_node(&self.expression_tree.root, arguments)
+    }
+}
+
+impl FunctionDefinition {
+    fn compute_node(&self, node: &Node, arguments: &[f64]) -> f64 {
+        match node {
+            Node::Operator(operator_node) => operator_node
+                .operation
+                .compute(&self.compute_arguments(&operator_node.arguments, arguments)),
+            Node::Function(function_node) => function_node
+                .operation
+                .compute(&self.compute_arguments(&function_node.arguments, arguments)),
+            Node::Value(ValueNode::Constant(constant)) => *constant,
+            Node::Value(ValueNode::Variable(variable)) => self
+                .parameters
+                .iter()
+                .position(|parameter| parameter == variable)
+                .map_or(f64::NAN, |index| arguments[index]),
+        }
+    }
+    fn compute_arguments(&self, nodes: &[Node], arguments: &[f64]) -> Vec<f64> {
+        nodes
+            .iter()
+            .map(|node| self.compute_node(node, arguments))
+            .collect()
+    }
+}
+
 impl Computable for ValueNode {
     fn compute(&self) -> Result<f64, ComputeError> {
         match self {
@@ -99,7 +159,7 @@ impl Computable for ValueNode {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::model::settings::Settings;
+    use std::rc::Rc;
 
     #[test]
     fn test_value_variable_compute() {
@@ -217,6 +277,37 @@ mod tests {
         assert_eq!(expected_tree, tree);
     }
 
+    #[test]
+    fn test_conditional_tree_simplify() {
+        let mut settings = Settings::default();
+        settings.add_piecewise_operations();
+        for (expression, expected_expression) in [
+            ("2 * if(1 < 2, x + 1, x - 1)", "2 * (x + 1)"),
+            ("if(1 > 2 * 3, x, heaviside(0.5 - 1) * x)", "0 * x"),
+            ("if(x > 1, x, 2 + 3)", "if(x > 1, x, 5)"),
+        ] {
+            let mut tree = ExpressionTree::parse(expression, &settings).unwrap();
+            tree.simplify();
+            assert_eq!(expected_expression, tree.to_string());
+        }
+    }
+
+    #[test]
+    fn test_user_if_function_simplify() {
+        let mut settings = Settings::default();
+        settings
+            .functions
+            .push(Rc::new(Function::with_implementation(
+                "if",
+                3,
+                4,
+                |arguments: &[f64]| arguments.iter().sum(),
+            )));
+        let mut tree = ExpressionTree::parse("if(1, x, 2)", &settings).unwrap();
+        tree.simplify();
+        assert_eq!("if(1, x, 2)", tree.to_string());
+    }
+
     fn create_tree_with_variables(settings: &Settings) -> ExpressionTree {
         ExpressionTree {
             root: Node::Operator(OperationNode {
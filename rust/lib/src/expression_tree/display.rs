@@ -1,7 +1,43 @@
 //! `Display` trait implementation for expression tree types.
-use super::types::{ExpressionTree, Function, Node, Operation, OperationNode, Operator, ValueNode};
+use super::types::{
+    ExpressionTree, Fixity, Function, Node, Operation, OperationNode, Operator, ValueNode,
+};
+use crate::model::settings::NamedConstant;
 use std::fmt;
 
+impl ExpressionTree {
+    /// Return the expression string with constants printed by name
+    /// if they differ from a named constant by no more than the relative tolerance.
+    pub fn to_string_with_named_constants(
+        &self,
+        constants: &[NamedConstant],
+        tolerance: f64,
+    ) -> String {
+        let mut expression_tree = self.clone();
+        for index in expression_tree.get_value_node_indices() {
+            let node = expression_tree.get_node_at_mut(index);
+            if let Node::Value(ValueNode::Constant(constant)) = node {
+                if let Some(name) = find_named_constant(constants, *constant, tolerance) {
+                    *node = Node::Value(ValueNode::Variable(name));
+                }
+            }
+        }
+        expression_tree.to_string()
+    }
+}
+
+fn find_named_constant(constants: &[NamedConstant], value: f64, tolerance: f64) -> Option<String> {
+    constants.iter().find_map(|constant| {
+        if (value - constant.value).abs() <= tolerance * constant.value.abs() {
+            Some(constant.name.to_owned())
+        } else if (value + constant.value).abs() <= tolerance * constant.value.abs() {
+            Some(format!("-{}", constant.name))
+        } else {
+            None
+        }
+    })
+}
+
 impl fmt::Display for ExpressionTree {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", self.root)
@@ -11,52 +47,101 @@ impl fmt::Display for ExpressionTree {
 impl fmt::Display for Node {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Node::Operator(operator_node) => {
-                if operator_node.operation.arguments_number == 2 {
-                    f.write_str(
-                        &operator_node
-                            .arguments
-                            .iter()
-                            .map(|argument| {
-                                if let Node::Operator(argument_node) = argument {
-                                    if !argument_node
-                                        .operation
-                                        .is_computed_before(&operator_node.operation)
-                                    {
-                                        return format!("({})", argument);
-                                    }
-                                }
-                                format!("{}", argument)
-                            })
-                            .collect::<Vec<String>>()
-                            .join(&format!(" {} ", operator_node.operation.name)),
-                    )
-                } else {
-                    write!(f, "{}", operator_node)
-                }
-            }
+            Node::Operator(operator_node) => write!(f, "{}", operator_node),
             Node::Function(function_node) => write!(f, "{}", function_node),
             Node::Value(value_node) => write!(f, "{}", value_node),
         }
     }
 }
 
+/// Operands are enclosed in brackets only if the parser would bind them differently otherwise.
 impl fmt::Display for OperationNode<Operator> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self.operation.arguments_number {
-            1 => write!(f, "{}{}", self.operation.get_name(), self.arguments[0]),
-            2 => write!(
+        let (left_binding_power, right_binding_power) = self.operation.get_binding_powers();
+        let name = self.operation.get_name();
+        match self.operation.fixity {
+            Fixity::Prefix => write!(
+                f,
+                "{}{}",
+                name,
+                format_right_operand(&self.arguments[0], right_binding_power)
+            ),
+            Fixity::Infix => write!(
                 f,
                 "{} {} {}",
-                self.arguments[0],
-                self.operation.get_name(),
-                self.arguments[1]
+                format_left_operand(&self.arguments[0], left_binding_power),
+                name,
+                format_right_operand(&self.arguments[1], right_binding_power)
+            ),
+            Fixity::Postfix => write!(
+                f,
+                "{}{}",
+                format_postfix_operand(&self.arguments[0], left_binding_power),
+                name
             ),
-            _ => unreachable!(),
         }
     }
 }
 
+/// Format the operand on the left of an operator.
+fn format_left_operand(operand: &Node, binding_power: Option<u16>) -> String {
+    format_operand(operand, operand.is_left_operand_enclosed(binding_power))
+}
+
+/// Format the operand of a postfix operator.
+fn format_postfix_operand(operand: &Node, binding_power: Option<u16>) -> String {
+    format_operand(operand, operand.is_postfix_operand_enclosed(binding_power))
+}
+
+/// Format the operand on the right of an operator.
+fn format_right_operand(operand: &Node, binding_power: Option<u16>) -> String {
+    format_operand(operand, operand.is_right_operand_enclosed(binding_power))
+}
+
+fn format_operand(operand: &Node, is_enclosed: bool) -> String {
+    if is_enclosed {
+        format!("({})", operand)
+    } else {
+        operand.to_string()
+    }
+}
+
+impl Node {
+    /// Check if the operand on the left of an operator must be enclosed,
+    /// i.e. the operator binds it tighter than the operand's own operator on the right.
+    pub(super) fn is_left_operand_enclosed(&self, binding_power: Option<u16>) -> bool {
+        if let Node::Operator(operator_node) = self {
+            if let (_, Some(operand_binding_power)) = operator_node.operation.get_binding_powers() {
+                return binding_power >= Some(operand_binding_power);
+            }
+        }
+        false
+    }
+
+    /// Check if the operand of a postfix operator must be enclosed, which is also the case
+    /// if it starts with a sign, e.g. `(-2)!` and `(-x)!`, since the sign is read as a prefix operator.
+    pub(super) fn is_postfix_operand_enclosed(&self, binding_power: Option<u16>) -> bool {
+        match self {
+            Node::Value(ValueNode::Constant(constant)) => constant.is_sign_negative(),
+            Node::Operator(operator_node) if operator_node.operation.fixity == Fixity::Prefix => {
+                true
+            }
+            _ => self.is_left_operand_enclosed(binding_power),
+        }
+    }
+
+    /// Check if the operand on the right of an operator must be enclosed,
+    /// i.e. its own operator on the left binds weaker than the operator.
+    pub(super) fn is_right_operand_enclosed(&self, binding_power: Option<u16>) -> bool {
+        if let Node::Operator(operator_node) = self {
+            if let (Some(operand_binding_power), _) = operator_node.operation.get_binding_powers() {
+                return Some(operand_binding_power) < binding_power;
+            }
+        }
+        false
+    }
+}
+
 impl fmt::Display for OperationNode<Function> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
@@ -95,8 +180,12 @@ impl fmt::Display for Function {
 
 #[cfg(test)]
 mod tests {
+    use super::super::compute::Computable;
+    use super::super::types::Associativity;
     use super::*;
     use crate::model::settings::Settings;
+    use std::f64::consts::{E, PI};
+    use std::rc::Rc;
 
     #[test]
     fn test_display_value_node() {
@@ -147,11 +236,114 @@ mod tests {
         let settings = Settings::default();
         let tree = create_tree_to_display(&settings);
         assert_eq!(
-            "(log(0.5, 1) * x1 / 1.5) ^ (2 ^ 2.5 * (3 + x2))",
+            "(log(0.5, 1) * (x1 / 1.5)) ^ (2 ^ 2.5 * (3 + x2))",
             tree.to_string()
         );
     }
 
+    #[test]
+    fn test_display_operator_brackets() {
+        let settings = Settings::default();
+        let minus = settings.find_binary_operator_by_name("-").unwrap();
+        let x_minus_one = Node::Operator(OperationNode {
+            operation: Rc::clone(&minus),
+            arguments: vec![
+                Node::Value(ValueNode::Variable(String::from("x"))),
+                Node::Value(ValueNode::Constant(1.0)),
+            ],
+        });
+        let negation = Node::Operator(OperationNode {
+            operation: settings.find_unary_operator_by_name("-").unwrap(),
+            arguments: vec![x_minus_one.clone()],
+        });
+        assert_eq!("-(x - 1)", negation.to_string());
+        let difference = Node::Operator(OperationNode {
+            operation: Rc::clone(&minus),
+            arguments: vec![x_minus_one.clone(), x_minus_one],
+        });
+        assert_eq!("x - 1 - (x - 1)", difference.to_string());
+    }
+
+    #[test]
+    fn test_display_postfix_operator_round_trip() {
+        let settings = create_settings_with_factorial();
+        let factorial = settings
+            .find_operator_by_name("!", Fixity::Postfix)
+            .unwrap();
+        let negation = settings.find_unary_operator_by_name("-").unwrap();
+        let power = settings.find_binary_operator_by_name("^").unwrap();
+        let create_factorial = |argument: Node| {
+            Node::Operator(OperationNode {
+                operation: Rc::clone(&factorial),
+                arguments: vec![argument],
+            })
+        };
+        let three = Node::Value(ValueNode::Constant(3.0));
+        let negative_three = Node::Operator(OperationNode {
+            operation: Rc::clone(&negation),
+            arguments: vec![three.clone()],
+        });
+        for (root, expected_expression) in [
+            (
+                create_factorial(Node::Value(ValueNode::Constant(-63.7))),
+                "(-63.7)!",
+            ),
+            (create_factorial(negative_three), "(-3)!"),
+            (
+                Node::Operator(OperationNode {
+                    operation: Rc::clone(&negation),
+                    arguments: vec![create_factorial(three.clone())],
+                }),
+                "-3!",
+            ),
+            (
+                Node::Operator(OperationNode {
+                    operation: Rc::clone(&power),
+                    arguments: vec![create_factorial(three.clone()), create_factorial(three)],
+                }),
+                "3! ^ 3!",
+            ),
+        ] {
+            let tree = ExpressionTree {
+                root,
+                variables: vec![],
+            };
+            assert_eq!(expected_expression, tree.to_string());
+            let parsed_tree = ExpressionTree::parse(&tree.to_string(), &settings).unwrap();
+            assert_eq!(expected_expression, parsed_tree.to_string());
+            assert_eq!(tree.compute(), parsed_tree.compute());
+        }
+    }
+
+    #[test]
+    fn test_to_string_with_named_constants() {
+        let settings = Settings::default();
+        let tree = ExpressionTree {
+            root: Node::Operator(OperationNode {
+                operation: settings.find_binary_operator_by_name("*").unwrap(),
+                arguments: vec![
+                    Node::Value(ValueNode::Constant(-PI * (1.0 + 1e-8))),
+                    Node::Operator(OperationNode {
+                        operation: settings.find_binary_operator_by_name("+").unwrap(),
+                        arguments: vec![
+                            Node::Value(ValueNode::Constant(E)),
+                            Node::Value(ValueNode::Constant(2.7)),
+                        ],
+                    }),
+                ],
+            }),
+            variables: vec![],
+        };
+        assert_eq!(
+            "-pi * (e + 2.7)",
+            tree.to_string_with_named_constants(&settings.constants, 1e-6)
+        );
+        assert_eq!(
+            format!("{} * (e + 2.7)", -PI * (1.0 + 1e-8)),
+            tree.to_string_with_named_constants(&settings.constants, 1e-12)
+        );
+    }
+
     #[test]
     fn test_display_operator() {
         let settings = Settings::default();
@@ -166,6 +358,23 @@ mod tests {
         assert_eq!("sin", format!("{}", sin_function));
     }
 
+    fn create_settings_with_factorial() -> Settings {
+        let mut settings = Settings::default();
+        settings.operators.push(Rc::new(Operator {
+            name: String::from("!"),
+            precedence: 5,
+            associativity: Associativity::Left,
+            fixity: Fixity::Postfix,
+            arguments_number: 1,
+            complexity: 3,
+            commutative: false,
+            computation: Rc::new(|arguments: &[f64]| {
+                (1..=arguments[0] as u64).map(|i| i as f64).product()
+            }),
+        }));
+        settings
+    }
+
     fn create_tree_to_display(settings: &Settings) -> ExpressionTree {
         ExpressionTree {
             root: Node::Operator(OperationNode {
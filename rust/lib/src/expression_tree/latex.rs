@@ -0,0 +1,197 @@
+//! LaTeX output of expression trees.
+use super::types::{
+    ExpressionTree, Fixity, Function, Node, Operation, OperationNode, Operator, ValueNode,
+};
+
+impl ExpressionTree {
+    /// Return the expression as a LaTeX math formula,
+    /// e.g. `\frac{\sin\left(x\right)}{2}` for `sin(x) / 2`.
+    pub fn to_latex(&self) -> String {
+        self.root.to_latex()
+    }
+}
+
+impl Node {
+    /// Return the subtree as a LaTeX math formula.
+    pub fn to_latex(&self) -> String {
+        match self {
+            Node::Operator(operator_node) => format_operator_node(operator_node),
+            Node::Function(function_node) => format_function_node(function_node),
+            Node::Value(ValueNode::Variable(variable)) => variable.to_owned(),
+            Node::Value(ValueNode::Constant(constant)) => format_constant(*constant),
+        }
+    }
+}
+
+/// Operands are enclosed in brackets as in the plain display,
+/// except for fractions and the power arguments, which LaTeX groups by itself.
+fn format_operator_node(node: &OperationNode<Operator>) -> String {
+    let (left_binding_power, right_binding_power) = node.operation.get_binding_powers();
+    let name = node.operation.get_name();
+    match node.operation.fixity {
+        Fixity::Prefix => format!(
+            "{}{}",
+            get_operator_latex_name(name),
+            format_operand(
+                &node.arguments[0],
+                node.arguments[0].is_right_operand_enclosed(right_binding_power)
+            )
+        ),
+        Fixity::Infix if name == "/" => format!(
+            "\\frac{{{}}}{{{}}}",
+            node.arguments[0].to_latex(),
+            node.arguments[1].to_latex()
+        ),
+        Fixity::Infix if name == "^" => format!(
+            "{{{}}}^{{{}}}",
+            format_power_base(&node.arguments[0]),
+            node.arguments[1].to_latex()
+        ),
+        Fixity::Infix => format!(
+            "{} {} {}",
+            format_operand(
+                &node.arguments[0],
+                node.arguments[0].is_left_operand_enclosed(left_binding_power)
+            ),
+            get_operator_latex_name(name),
+            format_operand(
+                &node.arguments[1],
+                node.arguments[1].is_right_operand_enclosed(right_binding_power)
+            )
+        ),
+        Fixity::Postfix => format!(
+            "{}{}",
+            format_operand(
+                &node.arguments[0],
+                node.arguments[0].is_postfix_operand_enclosed(left_binding_power)
+            ),
+            get_operator_latex_name(name)
+        ),
+    }
+}
+
+fn format_operand(operand: &Node, is_enclosed: bool) -> String {
+    if is_enclosed && !is_fraction(operand) {
+        format!("\\left({}\\right)", operand.to_latex())
+    } else {
+        operand.to_latex()
+    }
+}
+
+/// Format the base of a power, which is enclosed if it is an operator or a negative constant,
+/// e.g. `{\left(-2\right)}^{x}`, since the exponent would apply to its last operand otherwise.
+fn format_power_base(base: &Node) -> String {
+    let is_enclosed = match base {
+        Node::Operator(_) => true,
+        Node::Value(ValueNode::Constant(constant)) => constant.is_sign_negative(),
+        _ => false,
+    };
+    if is_enclosed {
+        format!("\\left({}\\right)", base.to_latex())
+    } else {
+        base.to_latex()
+    }
+}
+
+fn is_fraction(node: &Node) -> bool {
+    matches!(node, Node::Operator(operator_node)
+        if operator_node.operation.fixity == Fixity::Infix && operator_node.operation.get_name() == "/")
+}
+
+fn get_operator_latex_name(name: &str) -> &str {
+    match name {
+        "*" => "\\cdot",
+        "<=" => "\\le",
+        ">=" => "\\ge",
+        "!=" => "\\ne",
+        "==" => "=",
+        _ => name,
+    }
+}
+
+fn format_function_node(node: &OperationNode<Function>) -> String {
+    let arguments = node
+        .arguments
+        .iter()
+        .map(Node::to_latex)
+        .collect::<Vec<String>>()
+        .join(", ");
+    match node.operation.get_name() {
+        "abs" => format!("\\left|{}\\right|", arguments),
+        "sqrt" => format!("\\sqrt{{{}}}", arguments),
+        "floor" => format!("\\left\\lfloor {}\\right\\rfloor", arguments),
+        "ceil" => format!("\\left\\lceil {}\\right\\rceil", arguments),
+        name => format!(
+            "{}\\left({}\\right)",
+            get_function_latex_name(name),
+            arguments
+        ),
+    }
+}
+
+/// Return the LaTeX name of a function, which is an upright operator name
+/// unless LaTeX or the common notation has its own symbol for the function.
+fn get_function_latex_name(name: &str) -> String {
+    match name {
+        "sin" | "cos" | "tan" | "cot" | "arcsin" | "arccos" | "arctan" | "sinh" | "cosh"
+        | "tanh" | "coth" | "ln" | "log" | "exp" | "min" | "max" => format!("\\{}", name),
+        "gamma" => String::from("\\Gamma"),
+        "lgamma" => String::from("\\ln\\Gamma"),
+        "sigmoid" => String::from("\\sigma"),
+        "sign" => String::from("\\operatorname{sgn}"),
+        "j0" => String::from("J_0"),
+        "j1" => String::from("J_1"),
+        _ => format!("\\operatorname{{{}}}", name),
+    }
+}
+
+fn format_constant(constant: f64) -> String {
+    if constant.is_nan() {
+        String::from("\\mathrm{NaN}")
+    } else if constant.is_infinite() {
+        format!("{}\\infty", if constant < 0.0 { "-" } else { "" })
+    } else {
+        constant.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::settings::Settings;
+
+    #[test]
+    fn test_to_latex() {
+        let mut settings = Settings::default();
+        settings.add_piecewise_operations();
+        for (expression, expected_latex) in [
+            (
+                "sin(x) / 2 + (x - 1) ^ 2 * -x",
+                r"\frac{\sin\left(x\right)}{2} + {\left(x - 1\right)}^{2} \cdot -x",
+            ),
+            (
+                "abs(x) <= cos(x) * log(x, 2) ^ (1 / 3)",
+                r"\left|x\right| \le \cos\left(x\right) \cdot {\log\left(x, 2\right)}^{\frac{1}{3}}",
+            ),
+            ("(x == 1) != (x >= 2)", r"x = 1 \ne \left(x \ge 2\right)"),
+            (
+                "x - (x / 2) ^ arccot(x) - (x - 1)",
+                r"x - {\left(\frac{x}{2}\right)}^{\operatorname{arccot}\left(x\right)} - \left(x - 1\right)",
+            ),
+        ] {
+            let tree = ExpressionTree::parse(expression, &settings).unwrap();
+            assert_eq!(expected_latex, tree.to_latex());
+        }
+    }
+
+    #[test]
+    fn test_to_latex_constant() {
+        let create_tree = |constant: f64| ExpressionTree {
+            root: Node::Value(ValueNode::Constant(constant)),
+            variables: vec![],
+        };
+        assert_eq!("-2.5", create_tree(-2.5).to_latex());
+        assert_eq!(r"-\infty", create_tree(f64::NEG_INFINITY).to_latex());
+        assert_eq!(r"\mathrm{NaN}", create_tree(f64::NAN).to_latex());
+    }
+}
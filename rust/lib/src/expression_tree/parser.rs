@@ -1,8 +1,11 @@
 //! Expression tree parser module.
-//! The parser uses the shunting yard algorithm.
-//! https://en.wikipedia.org/wiki/Shunting_yard_algorithm
+//! The parser uses the Pratt parsing, so operators are parsed by their binding powers.
+//! https://en.wikipedia.org/wiki/Operator-precedence_parser#Pratt_parsing
+mod declaration;
 mod error;
 mod lexer;
 pub mod parser;
+mod suggestion;
 
+pub use error::{DeclarationError, ParseError, ParseErrors};
 pub use parser::*;
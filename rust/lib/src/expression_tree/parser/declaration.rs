@@ -0,0 +1,139 @@
+//! Module for parsing functions declared by expressions.
+use super::super::types::{ExpressionTree, Function, FunctionDefinition};
+use super::error::DeclarationError;
+use crate::model::settings::Settings;
+
+impl Function {
+    /// Parse the function declared as `name(a, b) = expression`.
+    /// The expression may contain only the parameters, named constants and operations of the settings.
+    pub fn parse_declaration(
+        declaration: &str,
+        complexity: u32,
+        settings: &Settings,
+    ) -> Result<Function, DeclarationError> {
+        let (header, body) = declaration
+            .split_once('=')
+            .ok_or_else(|| DeclarationError::InvalidHeaderError(declaration.trim().to_owned()))?;
+        let (name, parameters) = parse_header(header.trim())
+            .ok_or_else(|| DeclarationError::InvalidHeaderError(header.trim().to_owned()))?;
+        if let Some(name) = std::iter::once(&name)
+            .chain(&parameters)
+            .find(|name| is_name_used(name, settings))
+        {
+            return Err(DeclarationError::NameConflictError(name.to_owned()));
+        }
+        let expression_tree = ExpressionTree::parse_with_variables(body, settings, &parameters)
+            .map_err(DeclarationError::ParseError)?;
+        Ok(Function::with_implementation(
+            &name,
+            parameters.len(),
+            complexity,
+            FunctionDefinition {
+                parameters,
+                expression_tree,
+            },
+        ))
+    }
+}
+
+/// Split `name(a, b)` into the name and the distinct parameters.
+fn parse_header(header: &str) -> Option<(String, Vec<String>)> {
+    let (name, parameters) = header.strip_suffix(')')?.split_once('(')?;
+    let name = name.trim();
+    let parameters = parameters
+        .split(',')
+        .map(|parameter| parameter.trim().to_owned())
+        .collect::<Vec<String>>();
+    let is_valid = is_identifier(name)
+        && parameters
+            .iter()
+            .enumerate()
+            .all(|(i, parameter)| is_identifier(parameter) && !parameters[..i].contains(parameter));
+    if is_valid {
+        Some((name.to_owned(), parameters))
+    } else {
+        None
+    }
+}
+
+fn is_identifier(string: &str) -> bool {
+    string.starts_with(|c: char| c.is_alphabetic() || c == '_')
+        && string.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// Check if the name would be recognized as another function or a named constant.
+fn is_name_used(name: &str, settings: &Settings) -> bool {
+    settings.find_function_by_name(name).is_some()
+        || settings.find_constant_by_name(name).is_some()
+        || settings
+            .operators
+            .iter()
+            .any(|operator| operator.name == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::error::{ErrorTokenData, ParseError, UnknownIdentifierError};
+    use super::*;
+    use crate::expression_tree::Operation;
+
+    #[test]
+    fn test_parse_declaration() -> Result<(), DeclarationError> {
+        let settings = Settings::default();
+        let sigmoid = Function::parse_declaration("sigmoid(a) = 1 / (1 + exp(-a))", 4, &settings)?;
+        assert_eq!("sigmoid", sigmoid.name);
+        assert_eq!(1, sigmoid.arguments_number);
+        assert_eq!(4, sigmoid.complexity);
+        assert_eq!(0.5, sigmoid.compute(&[0.0]));
+        let gauss =
+            Function::parse_declaration(" gauss(a, b_1)=exp(-((a / b_1) ^ 2))", 6, &settings)?;
+        assert_eq!(2, gauss.arguments_number);
+        assert!(((-4.0_f64).exp() - gauss.compute(&[4.0, 2.0])).abs() < 1e-12);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_declaration_invalid_header_error() {
+        let settings = Settings::default();
+        for (declaration, header) in [
+            ("f(a) 2 * a", "f(a) 2 * a"),
+            ("f a = 2 * a", "f a"),
+            ("f(a, a) = 2 * a", "f(a, a)"),
+            ("2f(a) = 2 * a", "2f(a)"),
+            ("f() = 2", "f()"),
+        ] {
+            assert_eq!(
+                Err(DeclarationError::InvalidHeaderError(String::from(header))),
+                Function::parse_declaration(declaration, 1, &settings)
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_declaration_name_conflict_error() {
+        let settings = Settings::default();
+        for (declaration, name) in [("sin(a) = a", "sin"), ("f(a, e) = a * e", "e")] {
+            assert_eq!(
+                Err(DeclarationError::NameConflictError(String::from(name))),
+                Function::parse_declaration(declaration, 1, &settings)
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_declaration_parse_error() {
+        let settings = Settings::default();
+        assert_eq!(
+            Err(DeclarationError::ParseError(
+                ParseError::UnknownIdentifierError(UnknownIdentifierError {
+                    data: ErrorTokenData {
+                        string: String::from("b"),
+                        position: 5
+                    },
+                    suggestion: None
+                })
+            )),
+            Function::parse_declaration("f(a) = a + b", 1, &settings)
+        );
+    }
+}
@@ -1,13 +1,20 @@
 //! Parser error module.
 use std::fmt;
+use std::ops::Range;
 
 #[derive(Debug, PartialEq)]
 pub enum ParseError {
     MissingCommaOrOpeningBracketError(MissingCommaOrOpeningBracketError),
     MissingCommaError(MissingCommaError),
     InvalidArgumentsNumberError(InvalidArgumentsNumberError),
+    AmbiguousImplicitMultiplicationError(AmbiguousImplicitMultiplicationError),
+    UnexpectedCharacterError(UnexpectedCharacterError),
+    UnknownIdentifierError(UnknownIdentifierError),
+    UnknownFunctionError(UnknownFunctionError),
+    MissingOperandError(MissingOperandError),
+    InvalidNumberError(InvalidNumberError),
+    UnexpectedTokenError(UnexpectedTokenError),
     EmptyFormulaError,
-    MultipleFormulaError,
 }
 
 #[derive(Debug, PartialEq)]
@@ -27,12 +34,155 @@ pub struct InvalidArgumentsNumberError {
     pub actual: usize,
 }
 
+/// Operands are adjacent, but a multiplication can't be inserted between them,
+/// e.g. a number follows another operand or a bracket follows a variable.
+#[derive(Debug, PartialEq)]
+pub struct AmbiguousImplicitMultiplicationError {
+    pub data: ErrorTokenData,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct UnexpectedCharacterError {
+    pub data: ErrorTokenData,
+}
+
+/// Identifier that is neither an allowed variable nor a named constant.
+#[derive(Debug, PartialEq)]
+pub struct UnknownIdentifierError {
+    pub data: ErrorTokenData,
+    /// The closest known name.
+    pub suggestion: Option<String>,
+}
+
+/// Identifier followed by a bracket that is not a function.
+#[derive(Debug, PartialEq)]
+pub struct UnknownFunctionError {
+    pub data: ErrorTokenData,
+    /// The closest function name.
+    pub suggestion: Option<String>,
+}
+
+/// Opening bracket or comma followed by no operand, e.g. `()` or `log(2, )`.
+#[derive(Debug, PartialEq)]
+pub struct MissingOperandError {
+    pub data: ErrorTokenData,
+}
+
+/// Token that can't be at its place, e.g. an infix operator in place of an operand
+/// or an operand following another one when there is no multiplication.
+#[derive(Debug, PartialEq)]
+pub struct UnexpectedTokenError {
+    pub data: ErrorTokenData,
+}
+
+/// Malformed number literal, e.g. `2e` or `1..2`.
+#[derive(Debug, PartialEq)]
+pub struct InvalidNumberError {
+    pub data: ErrorTokenData,
+}
+
 #[derive(Debug, PartialEq)]
 pub struct ErrorTokenData {
     pub string: String,
+    /// The position of the first char of the token.
     pub position: usize,
 }
 
+/// All errors found in an expression.
+/// It is displayed as the errors with the source line and carets under each token.
+#[derive(Debug, PartialEq)]
+pub struct ParseErrors {
+    pub expression: String,
+    pub errors: Vec<ParseError>,
+}
+
+/// Error of declaring a function by an expression.
+#[derive(Debug, PartialEq)]
+pub enum DeclarationError {
+    /// The declaration is not in the form `name(a, b) = expression`.
+    InvalidHeaderError(String),
+    /// The function or a parameter has the name of an operation or a named constant.
+    NameConflictError(String),
+    ParseError(ParseError),
+}
+
+impl ParseError {
+    /// Return the token data if the error is caused by a token.
+    pub fn get_data(&self) -> Option<&ErrorTokenData> {
+        match self {
+            ParseError::MissingCommaOrOpeningBracketError(err) => Some(&err.data),
+            ParseError::MissingCommaError(err) => Some(&err.data),
+            ParseError::InvalidArgumentsNumberError(err) => Some(&err.data),
+            ParseError::AmbiguousImplicitMultiplicationError(err) => Some(&err.data),
+            ParseError::UnexpectedCharacterError(err) => Some(&err.data),
+            ParseError::UnknownIdentifierError(err) => Some(&err.data),
+            ParseError::UnknownFunctionError(err) => Some(&err.data),
+            ParseError::MissingOperandError(err) => Some(&err.data),
+            ParseError::InvalidNumberError(err) => Some(&err.data),
+            ParseError::UnexpectedTokenError(err) => Some(&err.data),
+            ParseError::EmptyFormulaError => None,
+        }
+    }
+}
+
+impl ErrorTokenData {
+    /// Return the char positions of the token.
+    pub fn get_span(&self) -> Range<usize> {
+        self.position..self.position + self.string.chars().count()
+    }
+    /// Return the byte positions of the token in the expression.
+    pub fn get_byte_span(&self, expression: &str) -> Range<usize> {
+        let start = expression
+            .char_indices()
+            .nth(self.position)
+            .map_or(expression.len(), |(index, _)| index);
+        start..start + self.string.len()
+    }
+}
+
+impl fmt::Display for ParseErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, err) in self.errors.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "error: {}", err)?;
+            if let Some(data) = err.get_data() {
+                write_snippet(f, &self.expression, data)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Write the source line of the token with carets under it.
+fn write_snippet(
+    f: &mut fmt::Formatter<'_>,
+    expression: &str,
+    data: &ErrorTokenData,
+) -> fmt::Result {
+    let span = data.get_byte_span(expression);
+    let line_start = expression[..span.start]
+        .rfind('\n')
+        .map_or(0, |index| index + 1);
+    let line_end = expression[span.start..]
+        .find('\n')
+        .map_or(expression.len(), |index| span.start + index);
+    let line_number = (expression[..span.start].matches('\n').count() + 1).to_string();
+    let gutter = " ".repeat(line_number.len());
+    let column = expression[line_start..span.start].chars().count();
+    write!(
+        f,
+        "\n{} |\n{} | {}\n{} | {}{}",
+        gutter,
+        line_number,
+        &expression[line_start..line_end],
+        gutter,
+        " ".repeat(column),
+        "^".repeat(data.string.chars().count().max(1))
+    )
+}
+
 impl fmt::Display for ParseError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -53,12 +203,77 @@ impl fmt::Display for ParseError {
                 "Invalid number of arguments at position {}, expected {}, but actually {}. Token string is \"{}\".",
                 err.data.position, err.expected, err.actual, err.data.string
             ),
+            ParseError::AmbiguousImplicitMultiplicationError(err) => write!(
+                f,
+                "Ambiguous implicit multiplication at position {}. Insert an operator before \"{}\".",
+                err.data.position, err.data.string
+            ),
+            ParseError::UnexpectedCharacterError(err) => write!(
+                f,
+                "Unexpected character at position {}. The character is \"{}\".",
+                err.data.position, err.data.string
+            ),
+            ParseError::UnknownIdentifierError(err) => {
+                write!(
+                    f,
+                    "Unknown identifier \"{}\" at position {}.",
+                    err.data.string, err.data.position
+                )?;
+                write_suggestion(f, &err.suggestion)
+            }
+            ParseError::UnknownFunctionError(err) => {
+                write!(
+                    f,
+                    "Unknown function \"{}\" at position {}.",
+                    err.data.string, err.data.position
+                )?;
+                write_suggestion(f, &err.suggestion)
+            }
+            ParseError::MissingOperandError(err) => write!(
+                f,
+                "Missing operand after \"{}\" at position {}.",
+                err.data.string, err.data.position
+            ),
+            ParseError::InvalidNumberError(err) => write!(
+                f,
+                "Invalid number \"{}\" at position {}.",
+                err.data.string, err.data.position
+            ),
+            ParseError::UnexpectedTokenError(err) => write!(
+                f,
+                "Unexpected token \"{}\" at position {}.",
+                err.data.string, err.data.position
+            ),
             ParseError::EmptyFormulaError => write!(f, "The formula is empty."),
-            ParseError::MultipleFormulaError => write!(f, "The formula is multiple."),
         }
     }
 }
 
+impl fmt::Display for DeclarationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeclarationError::InvalidHeaderError(header) => write!(
+                f,
+                "Invalid function header \"{}\". The declaration must be in the form \"name(a, b) = expression\".",
+                header
+            ),
+            DeclarationError::NameConflictError(name) => write!(
+                f,
+                "The name \"{}\" is already used by an operation or a named constant.",
+                name
+            ),
+            DeclarationError::ParseError(err) => write!(f, "Can't parse the function expression. {}", err),
+        }
+    }
+}
+
+fn write_suggestion(f: &mut fmt::Formatter<'_>, suggestion: &Option<String>) -> fmt::Result {
+    match suggestion {
+        Some(suggestion) => write!(f, " Did you mean \"{}\"?", suggestion),
+        None => Ok(()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -100,13 +315,144 @@ mod tests {
                 actual: 1
             }))
         );
+        assert_eq!(
+            "Ambiguous implicit multiplication at position 2. Insert an operator before \"(\".",
+            format!(
+                "{}",
+                ParseError::AmbiguousImplicitMultiplicationError(
+                    AmbiguousImplicitMultiplicationError {
+                        data: ErrorTokenData {
+                            string: String::from("("),
+                            position: 2
+                        }
+                    }
+                )
+            )
+        );
+        assert_eq!(
+            "Unexpected character at position 3. The character is \"$\".",
+            format!(
+                "{}",
+                ParseError::UnexpectedCharacterError(UnexpectedCharacterError {
+                    data: ErrorTokenData {
+                        string: String::from("$"),
+                        position: 3
+                    }
+                })
+            )
+        );
+        assert_eq!(
+            "Unknown function \"sinn\" at position 0. Did you mean \"sin\"?",
+            format!(
+                "{}",
+                ParseError::UnknownFunctionError(UnknownFunctionError {
+                    data: ErrorTokenData {
+                        string: String::from("sinn"),
+                        position: 0
+                    },
+                    suggestion: Some(String::from("sin"))
+                })
+            )
+        );
+        assert_eq!(
+            "Unknown identifier \"y\" at position 4.",
+            format!(
+                "{}",
+                ParseError::UnknownIdentifierError(UnknownIdentifierError {
+                    data: ErrorTokenData {
+                        string: String::from("y"),
+                        position: 4
+                    },
+                    suggestion: None
+                })
+            )
+        );
+        assert_eq!(
+            "Missing operand after \",\" at position 5.",
+            format!(
+                "{}",
+                ParseError::MissingOperandError(MissingOperandError {
+                    data: ErrorTokenData {
+                        string: String::from(","),
+                        position: 5
+                    }
+                })
+            )
+        );
+        assert_eq!(
+            "Invalid number \"1..2\" at position 3.",
+            format!(
+                "{}",
+                ParseError::InvalidNumberError(InvalidNumberError {
+                    data: ErrorTokenData {
+                        string: String::from("1..2"),
+                        position: 3
+                    }
+                })
+            )
+        );
         assert_eq!(
             "The formula is empty.",
             format!("{}", ParseError::EmptyFormulaError)
         );
         assert_eq!(
-            "The formula is multiple.",
-            format!("{}", ParseError::MultipleFormulaError)
+            "Unexpected token \"*\" at position 4.",
+            format!(
+                "{}",
+                ParseError::UnexpectedTokenError(UnexpectedTokenError {
+                    data: ErrorTokenData {
+                        string: String::from("*"),
+                        position: 4
+                    }
+                })
+            )
+        );
+    }
+
+    #[test]
+    fn test_get_byte_span() {
+        let data = ErrorTokenData {
+            string: String::from("zy"),
+            position: 4,
+        };
+        assert_eq!(4..6, data.get_span());
+        assert_eq!(5..7, data.get_byte_span("ψ + zy"));
+    }
+
+    #[test]
+    fn test_parse_errors_display() {
+        let parse_errors = ParseErrors {
+            expression: String::from("x +\nψ # coss(x)"),
+            errors: vec![
+                ParseError::UnexpectedCharacterError(UnexpectedCharacterError {
+                    data: ErrorTokenData {
+                        string: String::from("#"),
+                        position: 6,
+                    },
+                }),
+                ParseError::UnknownFunctionError(UnknownFunctionError {
+                    data: ErrorTokenData {
+                        string: String::from("coss"),
+                        position: 8,
+                    },
+                    suggestion: Some(String::from("cos")),
+                }),
+                ParseError::EmptyFormulaError,
+            ],
+        };
+        assert_eq!(
+            concat!(
+                "error: Unexpected character at position 6. The character is \"#\".\n",
+                "  |\n",
+                "2 | ψ # coss(x)\n",
+                "  |   ^\n",
+                "error: Unknown function \"coss\" at position 8. Did you mean \"cos\"?\n",
+                "  |\n",
+                "2 | ψ # coss(x)\n",
+                "  |     ^^^^\n",
+                "error: The formula is empty."
+            ),
+            parse_errors.to_string()
         );
     }
 }
@@ -1,13 +1,21 @@
 //! Lexer module.
-use super::super::types::{Function, Operator};
-use super::error::ErrorTokenData;
+use super::super::types::{Fixity, Function, Operator};
+use super::error::{
+    AmbiguousImplicitMultiplicationError, ErrorTokenData, InvalidNumberError, ParseError,
+    UnexpectedCharacterError, UnknownFunctionError, UnknownIdentifierError,
+};
+use super::suggestion::find_closest_name;
 use crate::model::settings::Settings;
 use std::rc::Rc;
 
 pub struct Lexer<'a> {
     pub expression: String,
     pub settings: &'a Settings,
+    /// The allowed variables, or None to treat every unknown identifier as a variable.
+    pub variables: Option<&'a [String]>,
     pub tokens: Vec<Rc<Token>>,
+    /// The errors found during the lexical analysis.
+    pub errors: Vec<ParseError>,
 }
 
 #[derive(Debug, PartialEq)]
@@ -56,57 +64,282 @@ impl<'a> Lexer<'a> {
         Lexer {
             expression: expression.to_owned(),
             settings,
+            variables: None,
             tokens: vec![],
+            errors: vec![],
         }
     }
+    /// Split the expression into tokens.
+    /// Numbers may have a fractional part and an exponent, e.g. `1.5e-3`.
+    /// A multiplication is inserted between adjacent operands, e.g. `2x`, `2(x + 1)` and `(a)(b)`.
+    /// The analysis continues after an error, so all of them are collected in `errors`.
     pub fn perform_lexical_analysis(&mut self) {
-        let mut string = String::new();
-        let mut p = 0;
-        for (i, c) in self.expression.chars().enumerate() {
-            if c.is_whitespace() {
-                if string.len() != 0 {
-                    self.tokens.push(Rc::new(Lexer::recognize_value_string(
-                        &string,
-                        self.expression.len() - string.len(),
-                    )));
-                    string = String::new();
-                }
-                p = i + 1;
+        let chars = self.expression.chars().collect::<Vec<char>>();
+        let mut position = 0;
+        while position < chars.len() {
+            if chars[position].is_whitespace() {
+                position += 1;
                 continue;
             }
-            string.push(c);
-            for j in 0..string.len() {
-                if let Some(token) = self.recognize_string(
-                    &string[j..],
-                    p + j,
-                    j == 0 && self.is_next_operator_unary(),
-                ) {
-                    if j != 0 {
-                        self.tokens
-                            .push(Rc::new(Lexer::recognize_value_string(&string[0..j], p)));
+            let (token, length) = if Lexer::is_number_start(&chars, position) {
+                self.read_number(&chars, position)
+            } else if Lexer::is_identifier_char(chars[position]) {
+                self.read_identifier(&chars, position)
+            } else {
+                match self.read_symbol(&chars, position) {
+                    Ok(token_and_length) => token_and_length,
+                    Err(err) => {
+                        self.errors.push(err);
+                        position += 1;
+                        continue;
+                    }
+                }
+            };
+            self.push_token(token);
+            position += length;
+        }
+        self.check_variables();
+    }
+    fn is_number_start(chars: &[char], position: usize) -> bool {
+        chars[position].is_ascii_digit()
+            || chars[position] == '.'
+                && position + 1 < chars.len()
+                && chars[position + 1].is_ascii_digit()
+    }
+    fn is_identifier_char(c: char) -> bool {
+        c.is_alphanumeric() || c == '_'
+    }
+    /// Read a number literal. A malformed one, e.g. `2e` or `1..2`, is reported
+    /// and read as a NaN constant, so the analysis can continue.
+    /// An exponent marker followed by an identifier char starts the identifier, e.g. `2ex`.
+    fn read_number(&mut self, chars: &[char], start: usize) -> (Token, usize) {
+        let skip_digits = |mut position: usize| {
+            while position < chars.len() && chars[position].is_ascii_digit() {
+                position += 1;
+            }
+            position
+        };
+        let mut end = skip_digits(start);
+        if end < chars.len() && chars[end] == '.' {
+            end = skip_digits(end + 1);
+        }
+        let mut is_valid = true;
+        if end < chars.len() && (chars[end] == 'e' || chars[end] == 'E') {
+            let mut exponent_start = end + 1;
+            if exponent_start < chars.len()
+                && (chars[exponent_start] == '+' || chars[exponent_start] == '-')
+            {
+                exponent_start += 1;
+            }
+            if exponent_start < chars.len() && chars[exponent_start].is_ascii_digit() {
+                end = skip_digits(exponent_start);
+            } else if exponent_start > end + 1
+                || exponent_start == chars.len()
+                || !Lexer::is_identifier_char(chars[exponent_start])
+            {
+                end = exponent_start;
+                is_valid = false;
+            }
+        }
+        while end < chars.len() && (chars[end] == '.' || chars[end].is_ascii_digit()) {
+            end += 1;
+            is_valid = false;
+        }
+        let string = chars[start..end].iter().collect::<String>();
+        if is_valid {
+            return (self.recognize_value_string(&string, start), end - start);
+        }
+        let data = ErrorTokenData {
+            string: string.clone(),
+            position: start,
+        };
+        self.errors
+            .push(ParseError::InvalidNumberError(InvalidNumberError { data }));
+        let token = Token::Constant(TokenValue {
+            value: f64::NAN,
+            string,
+            position: start,
+        });
+        (token, end - start)
+    }
+    fn read_identifier(&self, chars: &[char], start: usize) -> (Token, usize) {
+        let mut end = start;
+        while end < chars.len() && Lexer::is_identifier_char(chars[end]) {
+            end += 1;
+        }
+        let string = chars[start..end].iter().collect::<String>();
+        let token = self
+            .recognize_string(&string, start, self.is_next_operator_unary())
+            .unwrap_or_else(|| self.recognize_value_string(&string, start));
+        (token, end - start)
+    }
+    /// Read the longest operator or a service symbol.
+    fn read_symbol(&self, chars: &[char], start: usize) -> Result<(Token, usize), ParseError> {
+        let max_length = self
+            .settings
+            .operators
+            .iter()
+            .map(|operator| operator.name.chars().count())
+            .max()
+            .unwrap_or(1)
+            .min(chars.len() - start);
+        for length in (1..=max_length).rev() {
+            let string = chars[start..start + length].iter().collect::<String>();
+            if let Some(token) =
+                self.recognize_string(&string, start, self.is_next_operator_unary())
+            {
+                return Ok((token, length));
+            }
+        }
+        Err(ParseError::UnexpectedCharacterError(
+            UnexpectedCharacterError {
+                data: ErrorTokenData {
+                    string: chars[start].to_string(),
+                    position: start,
+                },
+            },
+        ))
+    }
+    /// Push the token inserting a multiplication if it follows an operand.
+    /// An ambiguous multiplication is reported and inserted anyway,
+    /// and an unknown function name is reported and dropped.
+    fn push_token(&mut self, token: Token) {
+        if let Some(previous_token) = self.tokens.last() {
+            if Lexer::is_operand_end(&self.tokens) {
+                let is_ambiguous = match &token {
+                    Token::Constant(_) if Lexer::is_number_literal(&token) => true,
+                    Token::OpeningBracket(_) => Lexer::is_identifier(previous_token),
+                    Token::Constant(_) | Token::Variable(_) | Token::Function(_) => false,
+                    _ => {
+                        self.tokens.push(Rc::new(token));
+                        return;
                     }
+                };
+                if matches!(token, Token::OpeningBracket(_))
+                    && !self.is_variable_allowed(previous_token)
+                {
+                    let err = self.create_unknown_function_error(previous_token);
+                    self.errors.push(err);
+                    self.tokens.pop();
                     self.tokens.push(Rc::new(token));
-                    string = String::new();
-                    p = i + 1;
-                    break;
+                    return;
+                }
+                let multiplication = self.settings.find_binary_operator_by_name("*");
+                if is_ambiguous || multiplication.is_none() {
+                    self.errors
+                        .push(ParseError::AmbiguousImplicitMultiplicationError(
+                            AmbiguousImplicitMultiplicationError {
+                                data: token.get_error_token_data(),
+                            },
+                        ));
+                }
+                if let Some(multiplication) = multiplication {
+                    self.tokens.push(Rc::new(Token::Operator(TokenValue {
+                        value: multiplication,
+                        string: String::from("*"),
+                        position: token.get_error_token_data().position,
+                    })));
                 }
             }
         }
-        if string.len() != 0 {
-            self.tokens.push(Rc::new(Lexer::recognize_value_string(
-                &string,
-                self.expression.len() - string.len(),
-            )));
+        self.tokens.push(Rc::new(token));
+    }
+    fn is_variable_allowed(&self, token: &Token) -> bool {
+        match (token, self.variables) {
+            (Token::Variable(token_value), Some(variables)) => {
+                variables.contains(&token_value.value)
+            }
+            _ => true,
+        }
+    }
+    fn create_unknown_function_error(&self, token: &Token) -> ParseError {
+        let data = token.get_error_token_data();
+        let suggestion = find_closest_name(
+            &data.string,
+            self.settings
+                .functions
+                .iter()
+                .map(|function| function.name.as_str()),
+        );
+        ParseError::UnknownFunctionError(UnknownFunctionError { data, suggestion })
+    }
+    /// Report every variable that is not allowed.
+    fn check_variables(&mut self) {
+        let variables = match self.variables {
+            Some(variables) => variables,
+            None => return,
+        };
+        for token in &self.tokens {
+            if self.is_variable_allowed(token) {
+                continue;
+            }
+            let data = token.get_error_token_data();
+            let suggestion = find_closest_name(
+                &data.string,
+                variables.iter().map(|variable| variable.as_str()).chain(
+                    self.settings
+                        .constants
+                        .iter()
+                        .map(|constant| constant.name.as_str()),
+                ),
+            );
+            self.errors
+                .push(ParseError::UnknownIdentifierError(UnknownIdentifierError {
+                    data,
+                    suggestion,
+                }));
+        }
+    }
+    /// Check if the tokens end with an operand, which may be followed by postfix operators.
+    fn is_operand_end(tokens: &[Rc<Token>]) -> bool {
+        match tokens.split_last() {
+            Some((token, previous_tokens)) => match &**token {
+                Token::Constant(_) | Token::Variable(_) | Token::CloseBracket(_) => true,
+                Token::Operator(token_value) => {
+                    token_value.value.fixity == Fixity::Postfix
+                        && Lexer::is_operand_end(previous_tokens)
+                }
+                _ => false,
+            },
+            None => false,
+        }
+    }
+    /// Check if the token is a variable or a named constant, which can't be followed by a bracket,
+    /// since it would be ambiguous with a function call.
+    fn is_identifier(token: &Token) -> bool {
+        match token {
+            Token::Variable(_) => true,
+            Token::Constant(_) => !Lexer::is_number_literal(token),
+            _ => false,
+        }
+    }
+    fn is_number_literal(token: &Token) -> bool {
+        match token {
+            Token::Constant(token_value) => token_value
+                .string
+                .starts_with(|c: char| c.is_ascii_digit() || c == '.'),
+            _ => false,
         }
     }
-    fn recognize_value_string(string: &str, position: usize) -> Token {
-        match string.parse::<f64>() {
-            Ok(constant) => Token::Constant(TokenValue {
+    /// Recognize a number, a named constant or a variable.
+    /// An allowed variable takes precedence over a named constant with the same name.
+    fn recognize_value_string(&self, string: &str, position: usize) -> Token {
+        let is_allowed_variable = self
+            .variables
+            .is_some_and(|variables| variables.iter().any(|variable| variable == string));
+        match string.parse::<f64>().ok().or_else(|| {
+            if is_allowed_variable {
+                None
+            } else {
+                self.settings.find_constant_by_name(string)
+            }
+        }) {
+            Some(constant) => Token::Constant(TokenValue {
                 value: constant,
                 string: string.to_owned(),
                 position,
             }),
-            Err(_) => Token::Variable(TokenValue {
+            None => Token::Variable(TokenValue {
                 value: string.to_owned(),
                 string: string.to_owned(),
                 position,
@@ -128,11 +361,21 @@ impl<'a> Lexer<'a> {
         position: usize,
         is_next_operator_unary: bool,
     ) -> Option<Token> {
+        // An operator of another fixity is still recognized, so the parser reports it as unexpected.
         let operator_option = if is_next_operator_unary {
-            self.settings.find_unary_operator_by_name(string)
+            self.settings.find_operator_by_name(string, Fixity::Prefix)
         } else {
-            self.settings.find_binary_operator_by_name(string)
-        };
+            self.settings
+                .find_operator_by_name(string, Fixity::Infix)
+                .or_else(|| self.settings.find_operator_by_name(string, Fixity::Postfix))
+        }
+        .or_else(|| {
+            self.settings
+                .operators
+                .iter()
+                .find(|operator| operator.name == string)
+                .map(Rc::clone)
+        });
         if let Some(operator) = operator_option {
             return Some(Token::Operator(TokenValue {
                 value: operator,
@@ -149,14 +392,17 @@ impl<'a> Lexer<'a> {
         }
         None
     }
+    /// Check if the next operator precedes an operand, so it must be a prefix one.
+    /// Otherwise it follows an operand and is an infix or a postfix one.
     fn is_next_operator_unary(&self) -> bool {
-        if self.tokens.len() == 0 {
-            return true;
-        }
-        match &*self.tokens[self.tokens.len() - 1] {
-            Token::Operator(_) => true,
-            Token::OpeningBracket(_) => true,
-            _ => false,
+        match self.tokens.last() {
+            Some(token) => match &**token {
+                Token::Operator(_) => !Lexer::is_operand_end(&self.tokens),
+                Token::OpeningBracket(_) => true,
+                Token::Comma(_) => true,
+                _ => false,
+            },
+            None => true,
         }
     }
     fn recognize_service_string(string: &str, position: usize) -> Option<Token> {
@@ -187,22 +433,56 @@ mod tests {
 
     #[test]
     fn test_recognize_value_string_constant() {
+        let settings = Settings::default();
+        let lexer = Lexer::new("", &settings);
         let expected_token = Token::Constant(TokenValue {
             value: 1.0,
             string: String::from("1.0"),
             position: 5,
         });
-        assert_eq!(expected_token, Lexer::recognize_value_string("1.0", 5));
+        assert_eq!(expected_token, lexer.recognize_value_string("1.0", 5));
+    }
+
+    #[test]
+    fn test_recognize_value_string_named_constant() {
+        let settings = Settings::default();
+        let lexer = Lexer::new("", &settings);
+        let expected_token = Token::Constant(TokenValue {
+            value: std::f64::consts::PI,
+            string: String::from("pi"),
+            position: 5,
+        });
+        assert_eq!(expected_token, lexer.recognize_value_string("pi", 5));
     }
 
     #[test]
     fn test_recognize_value_string_variable() {
+        let settings = Settings::default();
+        let lexer = Lexer::new("", &settings);
         let expected_token = Token::Variable(TokenValue {
             value: String::from("x1"),
             string: String::from("x1"),
             position: 5,
         });
-        assert_eq!(expected_token, Lexer::recognize_value_string("x1", 5));
+        assert_eq!(expected_token, lexer.recognize_value_string("x1", 5));
+    }
+
+    #[test]
+    fn test_recognize_value_string_allowed_variable_named_as_constant() {
+        let settings = Settings::default();
+        let variables = vec![String::from("e")];
+        let mut lexer = Lexer::new("", &settings);
+        lexer.variables = Some(&variables);
+        let expected_token = Token::Variable(TokenValue {
+            value: String::from("e"),
+            string: String::from("e"),
+            position: 5,
+        });
+        assert_eq!(expected_token, lexer.recognize_value_string("e", 5));
+        assert!(matches!(
+            lexer.recognize_value_string("pi", 5),
+            Token::Constant(_)
+        ));
     }
 
     #[test]
@@ -292,6 +572,7 @@ mod tests {
         let settings = Settings::default();
         let mut lexer = Lexer::new("log(2.0, x) + cos(-1.0) - x", &settings);
         lexer.perform_lexical_analysis();
+        assert_eq!(Vec::<ParseError>::new(), lexer.errors);
         assert_eq!(
             vec![
                 Rc::new(Token::Function(TokenValue {
@@ -369,6 +650,39 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_perform_lexical_analysis_unexpected_character() {
+        let settings = Settings::default();
+        let mut lexer = Lexer::new("x # 1 $", &settings);
+        lexer.perform_lexical_analysis();
+        assert_eq!(
+            vec![
+                ParseError::UnexpectedCharacterError(UnexpectedCharacterError {
+                    data: ErrorTokenData {
+                        string: String::from("#"),
+                        position: 2
+                    }
+                }),
+                ParseError::AmbiguousImplicitMultiplicationError(
+                    AmbiguousImplicitMultiplicationError {
+                        data: ErrorTokenData {
+                            string: String::from("1"),
+                            position: 4
+                        }
+                    }
+                ),
+                ParseError::UnexpectedCharacterError(UnexpectedCharacterError {
+                    data: ErrorTokenData {
+                        string: String::from("$"),
+                        position: 6
+                    }
+                }),
+            ],
+            lexer.errors
+        );
+        assert_eq!(3, lexer.tokens.len());
+    }
+
     #[test]
     fn test_get_error_token_data() {
         let settings = Settings::default();
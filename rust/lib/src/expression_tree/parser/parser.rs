@@ -1,677 +1,602 @@
 //! Parser module.
-use super::super::types::{ExpressionTree, Node, ValueNode};
+use super::super::types::{ExpressionTree, Fixity, Node, OperationNode, Operator, ValueNode};
 use super::error::{
-    InvalidArgumentsNumberError, MissingCommaError, MissingCommaOrOpeningBracketError, ParseError,
+    InvalidArgumentsNumberError, MissingCommaError, MissingCommaOrOpeningBracketError,
+    MissingOperandError, ParseError, ParseErrors, UnexpectedTokenError,
 };
 use super::lexer::{Lexer, Token};
 use crate::model::settings::{ConverterOperation, Settings};
-use std::collections::VecDeque;
 use std::rc::Rc;
 
 impl<'a> ExpressionTree {
     pub fn parse(expression: &str, settings: &'a Settings) -> Result<ExpressionTree, ParseError> {
-        Parser::parse(expression, settings)
+        Parser::parse(expression, settings, None)
+    }
+    /// Parse the expression reporting identifiers that are neither allowed variables nor named constants.
+    /// The variables of the tree are the allowed ones.
+    pub fn parse_with_variables(
+        expression: &str,
+        settings: &'a Settings,
+        allowed_variables: &'a [String],
+    ) -> Result<ExpressionTree, ParseError> {
+        let mut expression_tree = Parser::parse(expression, settings, Some(allowed_variables))?;
+        expression_tree.variables = allowed_variables.to_vec();
+        Ok(expression_tree)
+    }
+    /// Parse the expression collecting all errors the parser can recover from.
+    /// If the allowed variables are passed, they are the variables of the tree.
+    pub fn parse_with_diagnostics(
+        expression: &str,
+        settings: &'a Settings,
+        allowed_variables: Option<&'a [String]>,
+    ) -> Result<ExpressionTree, ParseErrors> {
+        match Parser::parse_all(expression, settings, allowed_variables) {
+            Ok(mut expression_tree) => {
+                if let Some(allowed_variables) = allowed_variables {
+                    expression_tree.variables = allowed_variables.to_vec();
+                }
+                Ok(expression_tree)
+            }
+            Err(errors) => Err(ParseErrors {
+                expression: expression.to_owned(),
+                errors,
+            }),
+        }
     }
 }
 
 struct Parser<'a> {
     settings: &'a Settings,
     lexer: Lexer<'a>,
-    queue: VecDeque<Node>,
-    stack: Vec<Rc<Token>>,
+    tokens: Vec<Rc<Token>>,
+    /// The index of the next token.
+    position: usize,
     variables: Vec<String>,
+    errors: Vec<ParseError>,
 }
 
 impl<'a> Parser<'a> {
-    fn parse(expression: &str, settings: &'a Settings) -> Result<ExpressionTree, ParseError> {
+    /// Parse the expression returning the first error.
+    fn parse(
+        expression: &str,
+        settings: &'a Settings,
+        variables: Option<&'a [String]>,
+    ) -> Result<ExpressionTree, ParseError> {
+        Parser::parse_all(expression, settings, variables).map_err(|mut errors| errors.remove(0))
+    }
+    /// Parse the expression returning all errors ordered by position.
+    fn parse_all(
+        expression: &str,
+        settings: &'a Settings,
+        variables: Option<&'a [String]>,
+    ) -> Result<ExpressionTree, Vec<ParseError>> {
         if expression.len() == 0 {
-            return Err(ParseError::EmptyFormulaError);
+            return Err(vec![ParseError::EmptyFormulaError]);
         }
         let mut parser = Parser::new(expression, settings);
+        parser.lexer.variables = variables;
         parser.lexer.perform_lexical_analysis();
-        parser.handle_tokens()?;
-        if parser.queue.len() != 1 {
-            return Err(ParseError::MultipleFormulaError);
+        parser.tokens = parser.lexer.tokens.clone();
+        let root = parser.parse_formula();
+        let mut errors = std::mem::take(&mut parser.lexer.errors);
+        errors.append(&mut parser.errors);
+        match root {
+            Ok(root) if errors.is_empty() => {
+                return Ok(ExpressionTree {
+                    root,
+                    variables: parser.variables,
+                })
+            }
+            Ok(_) => {}
+            Err(err) => errors.push(err),
         }
-        Ok(ExpressionTree {
-            root: parser.queue.pop_front().unwrap(),
-            variables: parser.variables.iter().cloned().collect::<Vec<String>>(),
-        })
+        errors.sort_by_key(|err| err.get_data().map_or(usize::MAX, |data| data.position));
+        Err(errors)
     }
     fn new(expression: &str, settings: &'a Settings) -> Parser<'a> {
         Parser {
             settings,
             lexer: Lexer::new(expression, settings),
-            queue: VecDeque::new(),
-            stack: vec![],
+            tokens: vec![],
+            position: 0,
             variables: vec![],
+            errors: vec![],
         }
     }
-    fn handle_tokens(&mut self) -> Result<(), ParseError> {
-        let tokens_rcs = self
-            .lexer
-            .tokens
-            .iter()
-            .cloned()
-            .collect::<Vec<Rc<Token>>>();
-        for token in tokens_rcs {
+    /// Parse all tokens as a single formula.
+    /// A close bracket or a comma without an opening bracket is reported and skipped.
+    fn parse_formula(&mut self) -> Result<Node, ParseError> {
+        let mut node = self.parse_expression(0);
+        while let Some(token) = self.peek_token() {
             match &*token {
-                Token::Constant(_) => {
-                    self.handle_constant(token)?;
-                }
-                Token::Variable(_) => {
-                    self.handle_variable(token)?;
-                }
-                Token::Function(_) => {
-                    self.handle_function(token);
+                Token::CloseBracket(_) => {
+                    self.position += 1;
+                    self.errors
+                        .push(ParseError::MissingCommaError(MissingCommaError {
+                            data: token.get_error_token_data(),
+                        }))
                 }
                 Token::Comma(_) => {
-                    self.handle_comma(token)?;
-                }
-                Token::Operator(_) => {
-                    self.handle_operator(token)?;
-                }
-                Token::OpeningBracket(_) => {
-                    self.handle_opening_bracket(token);
-                }
-                Token::CloseBracket(_) => {
-                    self.handle_close_bracket(token)?;
+                    self.position += 1;
+                    self.errors
+                        .push(ParseError::MissingCommaOrOpeningBracketError(
+                            MissingCommaOrOpeningBracketError {
+                                data: token.get_error_token_data(),
+                            },
+                        ))
                 }
+                _ => self.skip_unexpected_expression(&token),
             }
+            node = match node {
+                Some(node) => Some(self.parse_infix(node, 0)),
+                None => self.parse_expression(0),
+            };
         }
-        self.shift_all()?;
-        Ok(())
-    }
-    fn handle_constant(&mut self, token: Rc<Token>) -> Result<(), ParseError> {
-        match self.push_token(token) {
-            Err(err) => Err(ParseError::InvalidArgumentsNumberError(err)),
-            Ok(_) => Ok(()),
-        }
-    }
-    fn handle_variable(&mut self, token: Rc<Token>) -> Result<(), ParseError> {
-        let value = match &*token {
-            Token::Variable(token_value) => token_value.value.to_owned(),
-            _ => unreachable!(),
-        };
-        if !self.variables.contains(&value) {
-            self.variables.push(value.to_owned());
-        }
-        match self.push_token(token) {
-            Err(err) => Err(ParseError::InvalidArgumentsNumberError(err)),
-            Ok(_) => Ok(()),
-        }
-    }
-    fn handle_function(&mut self, token: Rc<Token>) {
-        self.stack.push(token);
-    }
-    fn handle_comma(&mut self, token: Rc<Token>) -> Result<(), ParseError> {
-        self.shift_until_opening_bracket(token)
-    }
-    fn handle_operator(&mut self, token: Rc<Token>) -> Result<(), ParseError> {
-        let value = match &*token {
-            Token::Operator(token_value) => token_value.value.to_owned(),
-            _ => unreachable!(),
-        };
-        loop {
-            if self.stack.len() == 0 {
-                break;
+        node.ok_or(ParseError::EmptyFormulaError)
+    }
+    /// Parse an operand and the operators following it that bind at least with the binding power.
+    /// Return None if no operand starts at the next token.
+    fn parse_expression(&mut self, binding_power: u16) -> Option<Node> {
+        self.parse_prefix()
+            .map(|node| self.parse_infix(node, binding_power))
+    }
+    /// Parse a value, a bracketed expression, a function or a prefix operator with its operand.
+    /// An infix or a postfix operator in place of an operand is reported and skipped.
+    fn parse_prefix(&mut self) -> Option<Node> {
+        let token = self.peek_token()?;
+        let node = match &*token {
+            Token::Constant(token_value) => {
+                self.position += 1;
+                Node::Value(ValueNode::Constant(token_value.value))
             }
-            if let Token::Operator(token_value_o2) = &*self.stack[self.stack.len() - 1] {
-                if token_value_o2.value.is_computed_before(&value) {
-                    let last_token = self.stack.pop().unwrap();
-                    if let Err(err) = self.push_token(last_token) {
-                        return Err(ParseError::InvalidArgumentsNumberError(err));
-                    }
-                } else {
-                    break;
+            Token::Variable(token_value) => {
+                self.position += 1;
+                if !self.variables.contains(&token_value.value) {
+                    self.variables.push(token_value.value.to_owned());
                 }
-            } else {
-                break;
+                Node::Value(ValueNode::Variable(token_value.value.to_owned()))
             }
-        }
-        self.stack.push(token);
-        Ok(())
-    }
-    fn handle_opening_bracket(&mut self, token: Rc<Token>) {
-        self.stack.push(token);
-    }
-    fn handle_close_bracket(&mut self, token: Rc<Token>) -> Result<(), ParseError> {
-        self.shift_until_opening_bracket(token)?;
-        self.stack.pop();
-        if self.stack.len() > 0 {
-            if let Token::Function(_) = &*self.stack[self.stack.len() - 1] {
-                let last_token = self.stack.pop().unwrap();
-                if let Err(err) = self.push_token(last_token) {
-                    return Err(ParseError::InvalidArgumentsNumberError(err));
-                }
+            Token::OpeningBracket(_) => {
+                self.position += 1;
+                self.parse_bracket(&token)
             }
-        }
-        Ok(())
-    }
-    fn push_token(&mut self, token: Rc<Token>) -> Result<(), InvalidArgumentsNumberError> {
-        match &*token {
-            Token::Constant(token_value) => Ok(self
-                .queue
-                .push_back(Node::Value(ValueNode::Constant(token_value.value)))),
-            Token::Variable(token_value) => Ok(self.queue.push_back(Node::Value(
-                ValueNode::Variable(token_value.value.to_owned()),
-            ))),
             Token::Function(token_value) => {
-                let node = self.create_operation_node(
-                    Rc::clone(&token),
+                self.position += 1;
+                let arguments = self.parse_arguments(&token);
+                let arguments_range = token_value.value.get_arguments_range();
+                if !arguments_range.contains(&arguments.len()) {
+                    self.errors.push(ParseError::InvalidArgumentsNumberError(
+                        InvalidArgumentsNumberError {
+                            data: token.get_error_token_data(),
+                            expected: arguments
+                                .len()
+                                .clamp(*arguments_range.start(), *arguments_range.end()),
+                            actual: arguments.len(),
+                        },
+                    ));
+                    return Some(Node::Function(OperationNode {
+                        operation: Rc::clone(&token_value.value),
+                        arguments,
+                    }));
+                }
+                self.create_node(
                     ConverterOperation::Function(Rc::clone(&token_value.value)),
-                    token_value.value.arguments_number,
-                )?;
-                Ok(self.queue.push_back(node))
+                    arguments,
+                )
+            }
+            Token::Operator(token_value) if token_value.value.fixity == Fixity::Prefix => {
+                self.position += 1;
+                let (_, right_binding_power) = token_value.value.get_binding_powers();
+                match self.parse_expression(right_binding_power.unwrap_or_default()) {
+                    Some(operand) => self.create_operator_node(&token_value.value, vec![operand]),
+                    None => {
+                        self.report_missing_operator_operand(&token, &token_value.value);
+                        Parser::create_placeholder()
+                    }
+                }
             }
-            Token::Operator(token_value) => {
-                let node = self.create_operation_node(
-                    Rc::clone(&token),
-                    ConverterOperation::Operator(Rc::clone(&token_value.value)),
-                    token_value.value.arguments_number,
-                )?;
-                Ok(self.queue.push_back(node))
+            Token::Operator(_) => {
+                self.position += 1;
+                self.report_unexpected_token(&token);
+                return self.parse_prefix();
             }
-            _ => Ok(()),
+            _ => return None,
+        };
+        Some(node)
+    }
+    /// Apply the infix and postfix operators that bind the node at least with the binding power.
+    /// An infix operator without the right operand is reported and skipped.
+    fn parse_infix(&mut self, mut node: Node, binding_power: u16) -> Node {
+        while let Some(token) = self.peek_token() {
+            let operator = match &*token {
+                Token::Operator(token_value) => Rc::clone(&token_value.value),
+                _ => break,
+            };
+            let right_binding_power = match operator.get_binding_powers() {
+                (Some(left_binding_power), right_binding_power)
+                    if left_binding_power >= binding_power =>
+                {
+                    right_binding_power
+                }
+                _ => break,
+            };
+            self.position += 1;
+            let arguments = match right_binding_power {
+                Some(right_binding_power) => match self.parse_expression(right_binding_power) {
+                    Some(operand) => vec![node, operand],
+                    None => {
+                        self.report_missing_operator_operand(&token, &operator);
+                        continue;
+                    }
+                },
+                None => vec![node],
+            };
+            node = self.create_operator_node(&operator, arguments);
         }
+        node
     }
-    fn create_operation_node(
-        &mut self,
-        token: Rc<Token>,
-        operation: ConverterOperation,
-        arguments_number: usize,
-    ) -> Result<Node, InvalidArgumentsNumberError> {
-        let arguments = self.extract_arguments(Rc::clone(&token), arguments_number)?;
-        Ok(self.settings.convert(operation, arguments).to_node())
-    }
-    fn extract_arguments(
-        &mut self,
-        token: Rc<Token>,
-        arguments_number: usize,
-    ) -> Result<Vec<Node>, InvalidArgumentsNumberError> {
-        if self.queue.len() < arguments_number {
-            return Err(InvalidArgumentsNumberError {
-                data: (&*token).get_error_token_data(),
-                expected: arguments_number,
-                actual: self.queue.len(),
-            });
-        }
-        let arguments = self
-            .queue
-            .split_off(self.queue.len() - arguments_number)
-            .into_iter()
-            .collect::<Vec<Node>>();
-        Ok(arguments)
-    }
-    fn shift_until_opening_bracket(&mut self, token: Rc<Token>) -> Result<(), ParseError> {
-        let mut tokens = VecDeque::new();
-        loop {
-            if self.stack.len() == 0 {
-                match *token {
-                    Token::Comma(_) => {
-                        return Err(ParseError::MissingCommaOrOpeningBracketError(
+    /// Parse the expression after the opening bracket up to the close bracket.
+    /// A comma inside is reported and the following expression is skipped.
+    fn parse_bracket(&mut self, opening_bracket: &Token) -> Node {
+        let node = self.parse_operand_after(opening_bracket);
+        while let Some(token) = self.peek_token() {
+            match &*token {
+                Token::CloseBracket(_) => {
+                    self.position += 1;
+                    return node;
+                }
+                Token::Comma(_) => {
+                    self.position += 1;
+                    self.errors
+                        .push(ParseError::MissingCommaOrOpeningBracketError(
                             MissingCommaOrOpeningBracketError {
-                                data: (&*token).get_error_token_data(),
+                                data: token.get_error_token_data(),
                             },
-                        ))
-                    }
-                    Token::CloseBracket(_) => {
-                        return Err(ParseError::MissingCommaError({
-                            MissingCommaError {
-                                data: (&*token).get_error_token_data(),
-                            }
-                        }))
-                    }
-                    _ => unreachable!(),
+                        ));
+                    self.parse_operand_after(&token);
                 }
+                _ => self.skip_unexpected_expression(&token),
             }
-            if let Token::OpeningBracket(_) = *self.stack[self.stack.len() - 1] {
-                break;
-            }
-            tokens.push_back(self.stack.pop().unwrap());
         }
-        for token in tokens {
-            if let Err(err) = self.push_token(token) {
-                return Err(ParseError::InvalidArgumentsNumberError(err));
+        self.report_unclosed_bracket(opening_bracket);
+        node
+    }
+    /// Parse the arguments of the function in brackets separated by commas.
+    /// If the opening bracket is missing, it is reported and the next operand is the only argument.
+    fn parse_arguments(&mut self, function: &Token) -> Vec<Node> {
+        let opening_bracket = match self.peek_token() {
+            Some(token) if matches!(*token, Token::OpeningBracket(_)) => {
+                self.position += 1;
+                token
             }
-        }
-        Ok(())
-    }
-    fn shift_all(&mut self) -> Result<(), ParseError> {
-        loop {
-            if self.stack.len() == 0 {
-                return Ok(());
+            _ => {
+                self.errors
+                    .push(ParseError::MissingCommaOrOpeningBracketError(
+                        MissingCommaOrOpeningBracketError {
+                            data: function.get_error_token_data(),
+                        },
+                    ));
+                return vec![self
+                    .parse_prefix()
+                    .unwrap_or_else(Parser::create_placeholder)];
             }
-            if let Token::OpeningBracket(_) = *self.stack[self.stack.len() - 1] {
-                return Err(ParseError::MissingCommaError(MissingCommaError {
-                    data: (&*self.stack[self.stack.len() - 1]).get_error_token_data(),
-                }));
+        };
+        if let Some(token) = self.peek_token() {
+            if let Token::CloseBracket(_) = *token {
+                self.position += 1;
+                return vec![];
             }
-            let last_token = self.stack.pop().unwrap();
-            if let Err(err) = self.push_token(last_token) {
-                return Err(ParseError::InvalidArgumentsNumberError(err));
+        }
+        let mut arguments = vec![self.parse_operand_after(&opening_bracket)];
+        while let Some(token) = self.peek_token() {
+            match &*token {
+                Token::Comma(_) => {
+                    self.position += 1;
+                    arguments.push(self.parse_operand_after(&token));
+                }
+                Token::CloseBracket(_) => {
+                    self.position += 1;
+                    return arguments;
+                }
+                _ => self.skip_unexpected_expression(&token),
+            }
+        }
+        self.report_unclosed_bracket(&opening_bracket);
+        arguments
+    }
+    fn parse_operand_after(&mut self, token: &Token) -> Node {
+        match self.parse_expression(0) {
+            Some(node) => node,
+            None => {
+                self.errors
+                    .push(ParseError::MissingOperandError(MissingOperandError {
+                        data: token.get_error_token_data(),
+                    }));
+                Parser::create_placeholder()
             }
         }
     }
+    /// Report the token that can't follow an operand and skip the expression starting at it.
+    fn skip_unexpected_expression(&mut self, token: &Token) {
+        self.report_unexpected_token(token);
+        self.parse_expression(0);
+    }
+    fn report_unexpected_token(&mut self, token: &Token) {
+        self.errors
+            .push(ParseError::UnexpectedTokenError(UnexpectedTokenError {
+                data: token.get_error_token_data(),
+            }));
+    }
+    fn report_missing_operator_operand(&mut self, token: &Token, operator: &Operator) {
+        self.errors.push(ParseError::InvalidArgumentsNumberError(
+            InvalidArgumentsNumberError {
+                data: token.get_error_token_data(),
+                expected: operator.arguments_number,
+                actual: operator.arguments_number - 1,
+            },
+        ));
+    }
+    /// Create the node standing for a missing operand, so the parsing can continue after an error.
+    fn create_placeholder() -> Node {
+        Node::Value(ValueNode::Constant(f64::NAN))
+    }
+    fn report_unclosed_bracket(&mut self, opening_bracket: &Token) {
+        self.errors
+            .push(ParseError::MissingCommaError(MissingCommaError {
+                data: opening_bracket.get_error_token_data(),
+            }));
+    }
+    fn create_operator_node(&self, operator: &Rc<Operator>, arguments: Vec<Node>) -> Node {
+        self.create_node(ConverterOperation::Operator(Rc::clone(operator)), arguments)
+    }
+    fn create_node(&self, operation: ConverterOperation, arguments: Vec<Node>) -> Node {
+        self.settings.convert(operation, arguments).to_node()
+    }
+    fn peek_token(&self) -> Option<Rc<Token>> {
+        self.tokens.get(self.position).map(Rc::clone)
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::super::super::compute::Computable;
     use super::super::super::types::OperationNode;
-    use super::super::lexer::TokenValue;
+    use super::super::super::types::{Associativity, Operator};
+    use super::super::error::{
+        AmbiguousImplicitMultiplicationError, ErrorTokenData, InvalidNumberError,
+        UnexpectedCharacterError, UnknownFunctionError, UnknownIdentifierError,
+    };
     use super::*;
-    use std::f64::consts::E;
+    use crate::model::settings::NamedConstant;
+    use std::f64::consts::{E, PI};
 
     #[test]
-    fn test_push_token() -> Result<(), InvalidArgumentsNumberError> {
+    fn test_parse_empty_formula_error() {
         let settings = Settings::default();
-        let mut parser = Parser::new("", &settings);
-        parser.push_token(Rc::new(create_one_token()))?;
-        let expected_error = InvalidArgumentsNumberError {
-            data: create_plus_token(&settings).get_error_token_data(),
-            expected: 2,
-            actual: 1,
-        };
-        match parser.push_token(Rc::new(create_plus_token(&settings))) {
-            Ok(_) => panic!("Expected {:?}, but Ok(()) was received.", expected_error),
+        let expected_error = ParseError::EmptyFormulaError;
+        match Parser::parse("", &settings, None) {
+            Ok(actual_tree) => panic!(
+                "Expected {:?}, but {:?} was received.",
+                expected_error, actual_tree
+            ),
             Err(err) => assert_eq!(expected_error, err),
         }
-        parser.push_token(Rc::new(create_x_token()))?;
-        parser.push_token(Rc::new(create_plus_token(&settings)))?;
-        parser.push_token(Rc::new(create_sin_token(&settings)))?;
-        assert_eq!(
-            VecDeque::from(vec![Node::Function(OperationNode {
-                operation: settings.find_function_by_name("sin").unwrap(),
-                arguments: vec![Node::Operator(OperationNode {
-                    operation: settings.find_binary_operator_by_name("+").unwrap(),
-                    arguments: vec![
-                        Node::Value(ValueNode::Constant(1.0)),
-                        Node::Value(ValueNode::Variable(String::from("x")))
-                    ]
-                })]
-            }),]),
-            parser.queue
-        );
-        Ok(())
     }
 
     #[test]
-    fn test_extract_arguments() -> Result<(), InvalidArgumentsNumberError> {
-        let settings = Settings::default();
-        let mut parser = Parser::new("", &settings);
-        parser.queue = VecDeque::from(vec![
-            Node::Value(ValueNode::Constant(1.0)),
-            Node::Value(ValueNode::Constant(2.0)),
-            Node::Value(ValueNode::Constant(3.0)),
-        ]);
-        parser.extract_arguments(Rc::new(create_log_token(&settings)), 2)?;
-        assert_eq!(
-            VecDeque::from(vec![Node::Value(ValueNode::Constant(1.0))]),
-            parser.queue
-        );
-        let expected_error = InvalidArgumentsNumberError {
-            data: create_log_token(&settings).get_error_token_data(),
-            expected: 2,
-            actual: 1,
-        };
-        match parser.extract_arguments(Rc::new(create_log_token(&settings)), 2) {
-            Ok(_) => panic!("Expected {:?}, but Ok(()) was received.", expected_error),
-            Err(err) => assert_eq!(expected_error, err),
-        };
-        Ok(())
-    }
-
-    #[test]
-    fn test_create_operation_node() -> Result<(), InvalidArgumentsNumberError> {
-        let settings = Settings::default();
-        let mut parser = Parser::new("", &settings);
-        parser.queue = VecDeque::from(vec![Node::Value(ValueNode::Variable(String::from("x")))]);
-        let expected_node = Node::Function(OperationNode {
-            operation: settings.find_function_by_name("log").unwrap(),
-            arguments: vec![
-                Node::Value(ValueNode::Constant(E)),
-                Node::Value(ValueNode::Variable(String::from("x"))),
-            ],
-        });
-        let actual_node = parser.create_operation_node(
-            Rc::new(create_ln_token(&settings)),
-            ConverterOperation::Function(settings.find_function_by_name("ln").unwrap()),
-            1,
-        )?;
-        assert_eq!(expected_node, actual_node);
-        assert_eq!(VecDeque::new(), parser.queue);
-        Ok(())
-    }
-
-    #[test]
-    fn test_shift_until_opening_bracket() -> Result<(), ParseError> {
-        let settings = Settings::default();
-        let mut parser = Parser::new("", &settings);
-        parser.queue = VecDeque::from(vec![
-            Node::Value(ValueNode::Constant(1.0)),
-            Node::Value(ValueNode::Variable(String::from("x"))),
-        ]);
-        parser.stack = vec![
-            Rc::new(create_one_token()),
-            Rc::new(create_opening_bracket_token()),
-            Rc::new(create_log_token(&settings)),
-        ];
-        parser.shift_until_opening_bracket(Rc::new(create_close_bracket_token()))?;
-        assert_eq!(
-            vec![
-                Rc::new(create_one_token()),
-                Rc::new(create_opening_bracket_token()),
-            ],
-            parser.stack
-        );
-        assert_eq!(
-            VecDeque::from(vec![Node::Function(OperationNode {
-                operation: settings.find_function_by_name("log").unwrap(),
-                arguments: vec![
-                    Node::Value(ValueNode::Constant(1.0)),
-                    Node::Value(ValueNode::Variable(String::from("x"))),
-                ]
-            }),]),
-            parser.queue
-        );
-        Ok(())
-    }
-
-    #[test]
-    fn test_shift_until_opening_bracket_comma() {
-        let settings = Settings::default();
-        let mut parser = Parser::new("", &settings);
-        parser.stack = vec![
-            Rc::new(create_one_token()),
-            Rc::new(create_log_token(&settings)),
-        ];
-        let expected_error =
-            ParseError::MissingCommaOrOpeningBracketError(MissingCommaOrOpeningBracketError {
-                data: create_comma_token().get_error_token_data(),
+    fn test_parse_unexpected_token_error() {
+        let settings = create_settings_with_custom_operators();
+        for (expression, string, position) in
+            [("x + * y", "*", 4), ("!x", "!", 0), ("(x // / y)", "/", 6)]
+        {
+            let expected_error = ParseError::UnexpectedTokenError(UnexpectedTokenError {
+                data: ErrorTokenData {
+                    string: String::from(string),
+                    position,
+                },
             });
-        match parser.shift_until_opening_bracket(Rc::new(create_comma_token())) {
-            Ok(_) => panic!("Expected {:?}, but Ok(()) was received.", expected_error),
-            Err(err) => assert_eq!(expected_error, err),
+            assert_eq!(
+                Err(vec![expected_error]),
+                Parser::parse_all(expression, &settings, None)
+            );
         }
-        let expected_stack: Vec<Rc<Token>> = vec![];
-        assert_eq!(expected_stack, parser.stack);
-        assert_eq!(VecDeque::new(), parser.queue);
     }
 
     #[test]
-    fn test_shift_until_opening_bracket_close_bracket() {
+    fn test_parse_all_errors() {
         let settings = Settings::default();
-        let mut parser = Parser::new("", &settings);
-        parser.stack = vec![
-            Rc::new(create_one_token()),
-            Rc::new(create_log_token(&settings)),
+        let comma_data = || ErrorTokenData {
+            string: String::from(","),
+            position: 14,
+        };
+        let expected_errors = vec![
+            ParseError::InvalidArgumentsNumberError(InvalidArgumentsNumberError {
+                data: ErrorTokenData {
+                    string: String::from("sin"),
+                    position: 0,
+                },
+                expected: 1,
+                actual: 2,
+            }),
+            ParseError::MissingCommaOrOpeningBracketError(MissingCommaOrOpeningBracketError {
+                data: comma_data(),
+            }),
+            ParseError::MissingOperandError(MissingOperandError { data: comma_data() }),
         ];
-        let expected_error = ParseError::MissingCommaError(MissingCommaError {
-            data: create_close_bracket_token().get_error_token_data(),
-        });
-        match parser.shift_until_opening_bracket(Rc::new(create_close_bracket_token())) {
-            Ok(_) => panic!("Expected {:?}, but Ok(()) was received.", expected_error),
-            Err(err) => assert_eq!(expected_error, err),
-        }
-        let expected_stack: Vec<Rc<Token>> = vec![];
-        assert_eq!(expected_stack, parser.stack);
-        assert_eq!(VecDeque::new(), parser.queue);
+        assert_eq!(
+            Err(expected_errors),
+            Parser::parse_all("sin(x, y) + (x,)", &settings, None)
+        );
     }
 
     #[test]
-    fn test_shift_until_opening_bracket_invalid_arguments_number() {
+    fn test_parse_invalid_arguments_number_error() {
         let settings = Settings::default();
-        let mut parser = Parser::new("", &settings);
-        parser.queue = VecDeque::from(vec![Node::Value(ValueNode::Constant(1.0))]);
-        parser.stack = vec![
-            Rc::new(create_one_token()),
-            Rc::new(create_opening_bracket_token()),
-            Rc::new(create_log_token(&settings)),
-        ];
-        let expected_error = ParseError::InvalidArgumentsNumberError(InvalidArgumentsNumberError {
-            data: create_log_token(&settings).get_error_token_data(),
-            expected: 2,
-            actual: 1,
-        });
-        match parser.shift_until_opening_bracket(Rc::new(create_close_bracket_token())) {
-            Ok(_) => panic!("Expected {:?}, but Ok(()) was received.", expected_error),
-            Err(err) => assert_eq!(expected_error, err),
+        for (expression, string, position, expected, actual) in
+            [("sin(1, 2)", "sin", 0, 1, 2), ("2 * (x +)", "+", 7, 2, 1)]
+        {
+            let expected_error =
+                ParseError::InvalidArgumentsNumberError(InvalidArgumentsNumberError {
+                    data: ErrorTokenData {
+                        string: String::from(string),
+                        position,
+                    },
+                    expected,
+                    actual,
+                });
+            match Parser::parse(expression, &settings, None) {
+                Ok(actual_tree) => panic!(
+                    "Expected {:?}, but {:?} was received.",
+                    expected_error, actual_tree
+                ),
+                Err(err) => assert_eq!(expected_error, err),
+            }
         }
-        assert_eq!(
-            vec![
-                Rc::new(create_one_token()),
-                Rc::new(create_opening_bracket_token()),
-            ],
-            parser.stack
-        );
-        assert_eq!(
-            VecDeque::from(vec![Node::Value(ValueNode::Constant(1.0))]),
-            parser.queue
-        );
     }
 
     #[test]
-    fn test_shift_all() -> Result<(), ParseError> {
-        let settings = Settings::default();
-        let mut parser = Parser::new("", &settings);
-        parser.stack = vec![
-            Rc::new(create_plus_token(&settings)),
-            Rc::new(create_one_token()),
-            Rc::new(create_x_token()),
-        ];
-        parser.shift_all()?;
-        let expected_stack: Vec<Rc<Token>> = vec![];
-        assert_eq!(expected_stack, parser.stack);
+    fn test_parse_variadic_function() -> Result<(), ParseError> {
+        let mut settings = Settings::default();
+        settings
+            .functions
+            .append(&mut Settings::get_variadic_functions());
+        let expression_tree = Parser::parse(
+            "max(x, 2, sum(1, 2, 3, 4)) - min(x, 1) * mean(x, 3, 5)",
+            &settings,
+            None,
+        )?;
         assert_eq!(
-            VecDeque::from(vec![Node::Operator(OperationNode {
-                operation: settings.find_binary_operator_by_name("+").unwrap(),
-                arguments: vec![
-                    Node::Value(ValueNode::Variable(String::from("x"))),
-                    Node::Value(ValueNode::Constant(1.0)),
-                ]
-            })]),
-            parser.queue
+            "max(x, 2, sum(1, 2, 3, 4)) - min(x, 1) * mean(x, 3, 5)",
+            expression_tree.to_string()
         );
+        for (expression, string, position, expected, actual) in [
+            ("min(x)", "min", 0, 2, 1),
+            ("hypot(1, 2, 3, 4, 5)", "hypot", 0, 4, 5),
+            ("mean(x)", "mean", 0, 2, 1),
+        ] {
+            let expected_error =
+                ParseError::InvalidArgumentsNumberError(InvalidArgumentsNumberError {
+                    data: ErrorTokenData {
+                        string: String::from(string),
+                        position,
+                    },
+                    expected,
+                    actual,
+                });
+            assert_eq!(
+                Err(expected_error),
+                Parser::parse(expression, &settings, Some(&[String::from("x")]))
+            );
+        }
         Ok(())
     }
 
     #[test]
-    fn test_shift_all_opening_bracket() {
+    fn test_parse_missing_operand_error() {
         let settings = Settings::default();
-        let mut parser = Parser::new("", &settings);
-        parser.stack = vec![
-            Rc::new(create_one_token()),
-            Rc::new(create_opening_bracket_token()),
-            Rc::new(create_x_token()),
-        ];
-        let expected_error = ParseError::MissingCommaError(MissingCommaError {
-            data: create_opening_bracket_token().get_error_token_data(),
+        let expected_error = ParseError::MissingOperandError(MissingOperandError {
+            data: ErrorTokenData {
+                string: String::from(","),
+                position: 5,
+            },
         });
-        match parser.shift_all() {
-            Ok(_) => panic!("Expected {:?}, but Ok(()) was received.", expected_error),
+        match Parser::parse("log(2, )", &settings, None) {
+            Ok(actual_tree) => panic!(
+                "Expected {:?}, but {:?} was received.",
+                expected_error, actual_tree
+            ),
             Err(err) => assert_eq!(expected_error, err),
         }
-        assert_eq!(
-            vec![
-                Rc::new(create_one_token()),
-                Rc::new(create_opening_bracket_token()),
-            ],
-            parser.stack
-        );
-        assert_eq!(
-            VecDeque::from(vec![Node::Value(ValueNode::Variable(String::from("x")))]),
-            parser.queue
-        );
     }
 
     #[test]
-    fn test_shift_all_invalid_arguments_number() {
-        let settings = Settings::default();
-        let mut parser = Parser::new("", &settings);
-        parser.queue = VecDeque::from(vec![Node::Value(ValueNode::Constant(1.0))]);
-        parser.stack = vec![Rc::new(create_plus_token(&settings))];
-        let expected_error = ParseError::InvalidArgumentsNumberError(InvalidArgumentsNumberError {
-            data: create_plus_token(&settings).get_error_token_data(),
-            expected: 2,
-            actual: 1,
-        });
-        match parser.shift_all() {
-            Ok(_) => panic!("Expected {:?}, but Ok(()) was received.", expected_error),
-            Err(err) => assert_eq!(expected_error, err),
+    fn test_parse_custom_operators() -> Result<(), ParseError> {
+        let settings = create_settings_with_custom_operators();
+        for (expression, expected_expression) in [
+            ("x!", "x!"),
+            ("-x! + 2x!", "-x! + 2 * x!"),
+            ("(-x)!", "(-x)!"),
+            ("(x + 1)!!", "(x + 1)!!"),
+            ("2 ** 3 ** x", "2 ** 3 ** x"),
+            ("(2 ** 3) ** x", "(2 ** 3) ** x"),
+            ("x // 2 * 3", "x // 2 * 3"),
+            ("x // (2 * 3)", "x // (2 * 3)"),
+        ] {
+            assert_eq!(
+                expected_expression,
+                Parser::parse(expression, &settings, None)?.to_string()
+            );
         }
-        let expected_stack: Vec<Rc<Token>> = vec![];
-        assert_eq!(expected_stack, parser.stack);
-        assert_eq!(
-            VecDeque::from(vec![Node::Value(ValueNode::Constant(1.0))]),
-            parser.queue
-        );
-    }
-
-    #[test]
-    fn test_handle_constant() {
-        let settings = Settings::default();
-        let mut parser = Parser::new("", &settings);
-        match parser.handle_constant(Rc::new(create_one_token())) {
-            Ok(_) => assert_eq!(
-                VecDeque::from(vec![Node::Value(ValueNode::Constant(1.0))]),
-                parser.queue
-            ),
-            Err(_) => unreachable!(),
-        };
+        let expression_tree = Parser::parse("(1 + 2)! ** 2 // 5", &settings, None)?;
+        assert_eq!(Ok(7.0), expression_tree.compute());
+        Ok(())
     }
 
     #[test]
-    fn test_handle_variable() {
+    fn test_parse_ambiguous_implicit_multiplication_error() {
         let settings = Settings::default();
-        let mut parser = Parser::new("", &settings);
-        match parser.handle_variable(Rc::new(create_x_token())) {
-            Ok(_) => {
-                assert_eq!(
-                    VecDeque::from(vec![Node::Value(ValueNode::Variable(String::from("x")))]),
-                    parser.queue
-                );
-                assert_eq!(vec![String::from("x")], parser.variables);
+        for (expression, string, position) in [("x + 1 1 + 2", "1", 6), ("2 x(1)", "(", 3)] {
+            let expected_error = ParseError::AmbiguousImplicitMultiplicationError(
+                AmbiguousImplicitMultiplicationError {
+                    data: ErrorTokenData {
+                        string: String::from(string),
+                        position,
+                    },
+                },
+            );
+            match Parser::parse(expression, &settings, None) {
+                Ok(actual_tree) => panic!(
+                    "Expected {:?}, but {:?} was received.",
+                    expected_error, actual_tree
+                ),
+                Err(err) => assert_eq!(expected_error, err),
             }
-            Err(_) => unreachable!(),
         }
     }
 
     #[test]
-    fn test_handle_operator_without_computed_before() -> Result<(), ParseError> {
-        let settings = Settings::default();
-        let mut parser = Parser::new("", &settings);
-        parser.stack = vec![Rc::new(create_plus_token(&settings))];
-        parser.handle_operator(Rc::new(create_asterisk_token(&settings)))?;
-        assert_eq!(
-            vec![
-                Rc::new(create_plus_token(&settings)),
-                Rc::new(create_asterisk_token(&settings))
-            ],
-            parser.stack
-        );
-        assert_eq!(VecDeque::new(), parser.queue);
-        Ok(())
-    }
-
-    #[test]
-    fn test_handle_operator_with_computed_before() -> Result<(), ParseError> {
+    fn test_parse_implicit_multiplication() -> Result<(), ParseError> {
         let settings = Settings::default();
-        let mut parser = Parser::new("", &settings);
-        parser.queue = VecDeque::from(vec![
-            Node::Value(ValueNode::Constant(1.0)),
-            Node::Value(ValueNode::Variable(String::from("x"))),
-        ]);
-        parser.stack = vec![Rc::new(create_asterisk_token(&settings))];
-        parser.handle_operator(Rc::new(create_plus_token(&settings)))?;
-        assert_eq!(vec![Rc::new(create_plus_token(&settings)),], parser.stack);
-        assert_eq!(
-            VecDeque::from(vec![Node::Operator(OperationNode {
-                operation: settings.find_binary_operator_by_name("*").unwrap(),
-                arguments: vec![
-                    Node::Value(ValueNode::Constant(1.0)),
-                    Node::Value(ValueNode::Variable(String::from("x"))),
-                ]
-            })]),
-            parser.queue
-        );
+        for (expression, expected_expression) in [
+            ("2x", "2 * x"),
+            ("2(x + 1)", "2 * (x + 1)"),
+            ("(a)(b)", "a * b"),
+            ("2 pi x", "2 * 3.141592653589793 * x"),
+            ("3sin(x)^2", "3 * sin(x) ^ 2"),
+            ("1.5e-3x + 2E+2", "0.0015 * x + 200"),
+            ("2 e", "2 * 2.718281828459045"),
+            ("2ex", "2 * ex"),
+        ] {
+            assert_eq!(
+                expected_expression,
+                Parser::parse(expression, &settings, None)?.to_string()
+            );
+        }
         Ok(())
     }
 
     #[test]
-    fn test_handle_operator_with_computed_before_invalid_arguments_number() {
+    fn test_parse_invalid_number() {
         let settings = Settings::default();
-        let mut parser = Parser::new("", &settings);
-        parser.queue = VecDeque::from(vec![Node::Value(ValueNode::Constant(1.0))]);
-        parser.stack = vec![Rc::new(create_asterisk_token(&settings))];
-        let expected_error = ParseError::InvalidArgumentsNumberError(InvalidArgumentsNumberError {
-            data: create_asterisk_token(&settings).get_error_token_data(),
-            expected: 2,
-            actual: 1,
-        });
-        match parser.handle_operator(Rc::new(create_plus_token(&settings))) {
-            Ok(_) => panic!("Expected {:?}, but Ok(()) was received.", expected_error),
-            Err(err) => assert_eq!(expected_error, err),
+        let expression = "2e + 1..2 * x - 3E- + .5.5";
+        let expected_errors = ParseErrors {
+            expression: String::from(expression),
+            errors: [("2e", 0), ("1..2", 5), ("3E-", 16), (".5.5", 22)]
+                .into_iter()
+                .map(|(string, position)| {
+                    ParseError::InvalidNumberError(InvalidNumberError {
+                        data: ErrorTokenData {
+                            string: String::from(string),
+                            position,
+                        },
+                    })
+                })
+                .collect(),
         };
-        let expected_stack: Vec<Rc<Token>> = vec![];
-        assert_eq!(expected_stack, parser.stack);
-        assert_eq!(
-            VecDeque::from(vec![Node::Value(ValueNode::Constant(1.0))]),
-            parser.queue
-        );
-    }
-
-    #[test]
-    fn test_handle_close_bracket() -> Result<(), ParseError> {
-        let settings = Settings::default();
-        let mut parser = Parser::new("", &settings);
-        parser.queue = VecDeque::from(vec![
-            Node::Value(ValueNode::Constant(1.0)),
-            Node::Value(ValueNode::Variable(String::from("x"))),
-        ]);
-        parser.stack = vec![
-            Rc::new(create_one_token()),
-            Rc::new(create_log_token(&settings)),
-            Rc::new(create_opening_bracket_token()),
-        ];
-        parser.handle_close_bracket(Rc::new(create_close_bracket_token()))?;
-        assert_eq!(vec![Rc::new(create_one_token())], parser.stack);
         assert_eq!(
-            VecDeque::from(vec![Node::Function(OperationNode {
-                operation: settings.find_function_by_name("log").unwrap(),
-                arguments: vec![
-                    Node::Value(ValueNode::Constant(1.0)),
-                    Node::Value(ValueNode::Variable(String::from("x"))),
-                ]
-            })]),
-            parser.queue
+            Err(expected_errors),
+            ExpressionTree::parse_with_diagnostics(expression, &settings, None)
         );
-        Ok(())
-    }
-
-    #[test]
-    fn test_parse_empty_formula_error() {
-        let settings = Settings::default();
-        let expected_error = ParseError::EmptyFormulaError;
-        match Parser::parse("", &settings) {
-            Ok(actual_tree) => panic!(
-                "Expected {:?}, but {:?} was received.",
-                expected_error, actual_tree
-            ),
-            Err(err) => assert_eq!(expected_error, err),
-        }
-    }
-
-    #[test]
-    fn test_multiple_formula_error() {
-        let settings = Settings::default();
-        let expected_error = ParseError::MultipleFormulaError;
-        match Parser::parse("x + 1 1 + 2", &settings) {
-            Ok(actual_tree) => panic!(
-                "Expected {:?}, but {:?} was received.",
-                expected_error, actual_tree
-            ),
-            Err(err) => assert_eq!(expected_error, err),
-        }
     }
 
     #[test]
@@ -684,7 +609,7 @@ mod tests {
         let slash = settings.find_binary_operator_by_name("/").unwrap();
         let asterisk = settings.find_binary_operator_by_name("*").unwrap();
         let circumflex = settings.find_binary_operator_by_name("^").unwrap();
-        let actual_tree = Parser::parse(&expression, &settings)?;
+        let actual_tree = Parser::parse(&expression, &settings, None)?;
         assert_eq!(
             ExpressionTree {
                 root: Node::Operator(OperationNode {
@@ -744,7 +669,7 @@ mod tests {
         let asterisk = settings.find_binary_operator_by_name("*").unwrap();
         let slash = settings.find_binary_operator_by_name("/").unwrap();
         let log = settings.find_function_by_name("log").unwrap();
-        let actual_tree = Parser::parse(&expression, &settings)?;
+        let actual_tree = Parser::parse(&expression, &settings, None)?;
         assert_eq!(
             ExpressionTree {
                 root: Node::Operator(OperationNode {
@@ -782,83 +707,191 @@ mod tests {
         Ok(())
     }
 
-    fn create_one_token() -> Token {
-        Token::Constant(TokenValue {
-            value: 1.0,
-            string: String::from("1.0"),
-            position: 0,
-        })
-    }
-
-    fn create_x_token() -> Token {
-        Token::Variable(TokenValue {
-            value: String::from("x"),
-            string: String::from("x"),
-            position: 0,
-        })
-    }
-
-    fn create_plus_token(settings: &Settings) -> Token {
-        Token::Operator(TokenValue {
-            value: settings.find_binary_operator_by_name("+").unwrap(),
-            string: String::from("+"),
-            position: 0,
-        })
-    }
-
-    fn create_asterisk_token(settings: &Settings) -> Token {
-        Token::Operator(TokenValue {
-            value: settings.find_binary_operator_by_name("*").unwrap(),
-            string: String::from("*"),
-            position: 0,
-        })
+    #[test]
+    fn test_parse_named_constants() -> Result<(), ParseError> {
+        let mut settings = Settings::default();
+        settings.constants.push(NamedConstant {
+            name: String::from("c"),
+            value: 299792458.0,
+        });
+        let actual_tree = Parser::parse("sin(pi * x) + c", &settings, None)?;
+        assert_eq!(
+            ExpressionTree {
+                root: Node::Operator(OperationNode {
+                    operation: settings.find_binary_operator_by_name("+").unwrap(),
+                    arguments: vec![
+                        Node::Function(OperationNode {
+                            operation: settings.find_function_by_name("sin").unwrap(),
+                            arguments: vec![Node::Operator(OperationNode {
+                                operation: settings.find_binary_operator_by_name("*").unwrap(),
+                                arguments: vec![
+                                    Node::Value(ValueNode::Constant(PI)),
+                                    Node::Value(ValueNode::Variable(String::from("x"))),
+                                ]
+                            })]
+                        }),
+                        Node::Value(ValueNode::Constant(299792458.0)),
+                    ]
+                }),
+                variables: vec![String::from("x")]
+            },
+            actual_tree
+        );
+        Ok(())
     }
 
-    fn create_sin_token<'a>(settings: &Settings) -> Token {
-        Token::Function(TokenValue {
-            value: settings.find_function_by_name("sin").unwrap(),
-            string: String::from("sin"),
-            position: 0,
-        })
+    #[test]
+    fn test_parse_with_variables() -> Result<(), ParseError> {
+        let settings = Settings::default();
+        let variables = vec![String::from("x"), String::from("y")];
+        let expression_tree =
+            ExpressionTree::parse_with_variables("2x + pi", &settings, &variables)?;
+        assert_eq!("2 * x + 3.141592653589793", expression_tree.to_string());
+        assert_eq!(variables, expression_tree.variables);
+        Ok(())
     }
 
-    fn create_log_token<'a>(settings: &Settings) -> Token {
-        Token::Function(TokenValue {
-            value: settings.find_function_by_name("log").unwrap(),
-            string: String::from("log"),
-            position: 0,
-        })
+    #[test]
+    fn test_parse_with_variables_named_as_constant() -> Result<(), ParseError> {
+        let settings = Settings::default();
+        let variables = vec![String::from("e"), String::from("x")];
+        let expression_tree =
+            ExpressionTree::parse_with_variables("2 * e + x * pi", &settings, &variables)?;
+        assert_eq!("2 * e + x * 3.141592653589793", expression_tree.to_string());
+        Ok(())
     }
 
-    fn create_ln_token<'a>(settings: &Settings) -> Token {
-        Token::Function(TokenValue {
-            value: settings.find_function_by_name("ln").unwrap(),
-            string: String::from("ln"),
-            position: 0,
-        })
+    #[test]
+    fn test_parse_with_variables_unknown_function_error() {
+        let settings = Settings::default();
+        let variables = vec![String::from("x")];
+        assert_eq!(
+            Err(ParseError::UnknownFunctionError(UnknownFunctionError {
+                data: ErrorTokenData {
+                    string: String::from("coss"),
+                    position: 4
+                },
+                suggestion: Some(String::from("cos"))
+            })),
+            ExpressionTree::parse_with_variables("x + coss(x)", &settings, &variables)
+        );
     }
 
-    fn create_opening_bracket_token() -> Token {
-        Token::OpeningBracket(TokenValue {
-            value: (),
-            string: String::from("("),
-            position: 0,
-        })
+    #[test]
+    fn test_parse_with_diagnostics() {
+        let settings = Settings::default();
+        let variables = vec![String::from("x")];
+        let expression = "sinn(x) + y # 2 * (x";
+        let expected_errors = ParseErrors {
+            expression: String::from(expression),
+            errors: vec![
+                ParseError::UnknownFunctionError(UnknownFunctionError {
+                    data: ErrorTokenData {
+                        string: String::from("sinn"),
+                        position: 0,
+                    },
+                    suggestion: Some(String::from("sin")),
+                }),
+                ParseError::UnknownIdentifierError(UnknownIdentifierError {
+                    data: ErrorTokenData {
+                        string: String::from("y"),
+                        position: 10,
+                    },
+                    suggestion: None,
+                }),
+                ParseError::UnexpectedCharacterError(UnexpectedCharacterError {
+                    data: ErrorTokenData {
+                        string: String::from("#"),
+                        position: 12,
+                    },
+                }),
+                ParseError::AmbiguousImplicitMultiplicationError(
+                    AmbiguousImplicitMultiplicationError {
+                        data: ErrorTokenData {
+                            string: String::from("2"),
+                            position: 14,
+                        },
+                    },
+                ),
+                ParseError::MissingCommaError(MissingCommaError {
+                    data: ErrorTokenData {
+                        string: String::from("("),
+                        position: 18,
+                    },
+                }),
+            ],
+        };
+        assert_eq!(
+            Err(expected_errors),
+            ExpressionTree::parse_with_diagnostics(expression, &settings, Some(&variables))
+        );
+        let expression_tree =
+            ExpressionTree::parse_with_diagnostics("sin(x", &settings, Some(&variables));
+        assert!(matches!(expression_tree, Err(ParseErrors { errors, .. }) if errors.len() == 1));
     }
 
-    fn create_close_bracket_token() -> Token {
-        Token::CloseBracket(TokenValue {
-            value: (),
-            string: String::from(")"),
-            position: 0,
-        })
+    #[test]
+    fn test_parse_with_variables_unknown_identifier_error() {
+        let settings = Settings::default();
+        let variables = vec![String::from("ψ"), String::from("xyz")];
+        assert_eq!(
+            Err(ParseError::UnknownIdentifierError(UnknownIdentifierError {
+                data: ErrorTokenData {
+                    string: String::from("xzy"),
+                    position: 8
+                },
+                suggestion: Some(String::from("xyz"))
+            })),
+            ExpressionTree::parse_with_variables("ψ + 2 * xzy", &settings, &variables)
+        );
+        assert_eq!(
+            Err(ParseError::UnknownIdentifierError(UnknownIdentifierError {
+                data: ErrorTokenData {
+                    string: String::from("y"),
+                    position: 4
+                },
+                suggestion: None
+            })),
+            ExpressionTree::parse_with_variables("ψ + y", &settings, &variables)
+        );
     }
 
-    fn create_comma_token() -> Token {
-        Token::Comma(TokenValue {
-            value: (),
-            string: String::from(","),
-            position: 0,
-        })
+    fn create_settings_with_custom_operators() -> Settings {
+        let mut settings = Settings::default();
+        settings.operators.extend([
+            Rc::new(Operator {
+                name: String::from("!"),
+                precedence: 5,
+                associativity: Associativity::Left,
+                fixity: Fixity::Postfix,
+                arguments_number: 1,
+                complexity: 3,
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| {
+                    (1..=arguments[0] as u64).map(|i| i as f64).product()
+                }),
+            }),
+            Rc::new(Operator {
+                name: String::from("**"),
+                precedence: 3,
+                associativity: Associativity::Right,
+                fixity: Fixity::Infix,
+                arguments_number: 2,
+                complexity: 3,
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| arguments[0].powf(arguments[1])),
+            }),
+            Rc::new(Operator {
+                name: String::from("//"),
+                precedence: 2,
+                associativity: Associativity::Left,
+                fixity: Fixity::Infix,
+                arguments_number: 2,
+                complexity: 2,
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| (arguments[0] / arguments[1]).floor()),
+            }),
+        ]);
+        settings
     }
 }
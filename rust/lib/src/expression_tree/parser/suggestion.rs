@@ -0,0 +1,75 @@
+//! Module for suggesting known names instead of misspelled ones.
+
+/// Return the candidate closest to the name if it is close enough to be a misspelling.
+/// Names shorter than 3 characters get no suggestion, since any other short name would be as close,
+/// and no candidate is suggested if every character of the name would have to change.
+pub fn find_closest_name<'a, I>(name: &str, candidates: I) -> Option<String>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let name_len = name.chars().count();
+    let max_distance = if name_len < 3 { 0 } else { name_len / 3 };
+    candidates
+        .into_iter()
+        .map(|candidate| (get_edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance && *distance < name_len)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate.to_owned())
+}
+
+/// Return the number of insertions, deletions, substitutions and transpositions of adjacent characters
+/// needed to turn one string into another.
+pub fn get_edit_distance(string1: &str, string2: &str) -> usize {
+    let chars1 = string1.chars().collect::<Vec<char>>();
+    let chars2 = string2.chars().collect::<Vec<char>>();
+    let mut distances = vec![vec![0; chars2.len() + 1]; chars1.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+    for i in 1..=chars1.len() {
+        for j in 1..=chars2.len() {
+            let cost = if chars1[i - 1] == chars2[j - 1] { 0 } else { 1 };
+            distances[i][j] = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && chars1[i - 1] == chars2[j - 2] && chars1[i - 2] == chars2[j - 1] {
+                distances[i][j] = distances[i][j].min(distances[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    distances[chars1.len()][chars2.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_edit_distance() {
+        assert_eq!(0, get_edit_distance("sin", "sin"));
+        assert_eq!(1, get_edit_distance("sinn", "sin"));
+        assert_eq!(1, get_edit_distance("tna", "tan"));
+        assert_eq!(2, get_edit_distance("αβγ", "αγ1"));
+        assert_eq!(3, get_edit_distance("", "cos"));
+    }
+
+    #[test]
+    fn test_find_closest_name() {
+        let candidates = ["sin", "sinh", "cos", "cosh"];
+        assert_eq!(
+            Some(String::from("sinh")),
+            find_closest_name("sinhh", candidates)
+        );
+        assert_eq!(
+            Some(String::from("cos")),
+            find_closest_name("cso", candidates)
+        );
+        assert_eq!(None, find_closest_name("tangent", candidates));
+        assert_eq!(None, find_closest_name("y", ["x", "ä"]));
+        assert_eq!(None, find_closest_name("é", ["x", "ä"]));
+        assert_eq!(None, find_closest_name("xy", ["x", "xz"]));
+    }
+}
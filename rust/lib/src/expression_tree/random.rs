@@ -1,9 +1,11 @@
 //! Module with random operations on the expression tree.
-use super::types::{ExpressionTree, Node, Operation, OperationNode, ValueNode};
-use crate::model::settings::Settings;
+use super::types::{ExpressionTree, Function, Node, Operation, OperationNode, ValueNode};
+use crate::model::settings::{Initialization, Settings};
 use rand::rngs::ThreadRng;
-use rand::Rng;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 use rand_distr::{Distribution, Normal};
+use serde::{Deserialize, Serialize};
 use std::ops::Range;
 use std::rc::Rc;
 
@@ -27,6 +29,45 @@ impl Default for DefaultRandom<ThreadRng, Normal<f64>> {
     }
 }
 
+/// Reproducible random whose state can be saved and restored.
+pub type SeededRandom = DefaultRandom<ChaCha8Rng, Normal<f64>>;
+
+/// The state of the seeded random generator.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RandomState {
+    pub seed: [u8; 32],
+    pub stream: u64,
+    pub word_pos: u128,
+}
+
+impl SeededRandom {
+    pub fn seeded(seed: u64) -> SeededRandom {
+        SeededRandom::from_rng(ChaCha8Rng::seed_from_u64(seed))
+    }
+    pub fn from_entropy() -> SeededRandom {
+        SeededRandom::from_rng(ChaCha8Rng::from_entropy())
+    }
+    pub fn from_state(state: &RandomState) -> SeededRandom {
+        let mut rng = ChaCha8Rng::from_seed(state.seed);
+        rng.set_stream(state.stream);
+        rng.set_word_pos(state.word_pos);
+        SeededRandom::from_rng(rng)
+    }
+    pub fn get_state(&self) -> RandomState {
+        RandomState {
+            seed: self.rng.get_seed(),
+            stream: self.rng.get_stream(),
+            word_pos: self.rng.get_word_pos(),
+        }
+    }
+    fn from_rng(rng: ChaCha8Rng) -> SeededRandom {
+        DefaultRandom {
+            rng,
+            float_distribution: Normal::new(0.0, 100.0).unwrap(),
+        }
+    }
+}
+
 impl<G: Rng, D: Distribution<f64>> Random for DefaultRandom<G, D> {
     fn gen_float(&mut self) -> f64 {
         self.float_distribution.sample(&mut self.rng)
@@ -51,10 +92,8 @@ impl ExpressionTree {
     where
         R: Random + ?Sized,
     {
-        let CreateRandomNodeResult { node, .. } =
-            Node::create_random(random, settings, variables, 0);
         ExpressionTree {
-            root: node,
+            root: Node::create_random_initial(random, settings, variables, 0),
             variables: variables.iter().cloned().collect::<Vec<String>>(),
         }
     }
@@ -136,6 +175,17 @@ impl ExpressionTree {
             function_node_indices[random.gen_range(0..function_node_indices.len())],
         ))
     }
+    /// Return a random function node that accepts different numbers of arguments.
+    pub fn find_random_variadic_function_node_mut<R>(&mut self, random: &mut R) -> Option<&mut Node>
+    where
+        R: Random + ?Sized,
+    {
+        let indices = self.get_variadic_function_node_indices();
+        if indices.is_empty() {
+            return None;
+        }
+        Some(self.get_node_at_mut(indices[random.gen_range(0..indices.len())]))
+    }
     pub fn find_random_operation_node<R>(&self, random: &mut R) -> Option<&Node>
     where
         R: Random + ?Sized,
@@ -167,6 +217,88 @@ pub struct CreateRandomNodeResult {
 }
 
 impl Node {
+    /// Create a random node using the initialization method from the settings.
+    pub fn create_random_initial<R>(
+        random: &mut R,
+        settings: &Settings,
+        variables: &[String],
+        tree_complexity: u32,
+    ) -> Node
+    where
+        R: Random + ?Sized,
+    {
+        match settings.initialization {
+            Initialization::Complexity => {
+                Node::create_random(random, settings, variables, tree_complexity).node
+            }
+            Initialization::Grow {
+                min_depth,
+                max_depth,
+            } => Node::create_random_by_depth(random, settings, variables, min_depth, max_depth),
+            Initialization::Full { max_depth } => {
+                Node::create_random_by_depth(random, settings, variables, max_depth, max_depth)
+            }
+            Initialization::RampedHalfAndHalf {
+                min_depth,
+                max_depth,
+            } => {
+                let depth = random.gen_range(min_depth..max_depth + 1);
+                if random.gen_float_standard() < 0.5 {
+                    Node::create_random_by_depth(random, settings, variables, min_depth, depth)
+                } else {
+                    Node::create_random_by_depth(random, settings, variables, depth, depth)
+                }
+            }
+        }
+    }
+    /// Create a random node whose depth is between the bounds.
+    /// Operations and leaves are chosen equally likely while the depth is within the bounds.
+    pub fn create_random_by_depth<R>(
+        random: &mut R,
+        settings: &Settings,
+        variables: &[String],
+        min_depth: usize,
+        max_depth: usize,
+    ) -> Node
+    where
+        R: Random + ?Sized,
+    {
+        let operation_number = settings.operators.len() + settings.functions.len();
+        if max_depth == 0 || operation_number == 0 {
+            return Node::create_random_value(random, settings, variables).node;
+        }
+        if min_depth == 0 && random.gen_float_standard() < 0.5 {
+            return Node::create_random_value(random, settings, variables).node;
+        }
+        let index = random.gen_range(0..operation_number);
+        let create_arguments = |random: &mut R, arguments_number: usize| {
+            (0..arguments_number)
+                .map(|_| {
+                    Node::create_random_by_depth(
+                        random,
+                        settings,
+                        variables,
+                        min_depth.saturating_sub(1),
+                        max_depth - 1,
+                    )
+                })
+                .collect::<Vec<Node>>()
+        };
+        if index < settings.operators.len() {
+            let operator = Rc::clone(&settings.operators[index]);
+            Node::Operator(OperationNode {
+                arguments: create_arguments(random, operator.arguments_number),
+                operation: operator,
+            })
+        } else {
+            let function = Rc::clone(&settings.functions[index - settings.operators.len()]);
+            let arguments_number = function.choose_arguments_number(random);
+            Node::Function(OperationNode {
+                arguments: create_arguments(random, arguments_number),
+                operation: function,
+            })
+        }
+    }
     pub fn create_random<R>(
         random: &mut R,
         settings: &Settings,
@@ -231,7 +363,7 @@ impl Node {
             Rc::clone(&settings.functions[random.gen_range(0..settings.functions.len())]);
         let mut node_complexity = function.get_complexity();
         let mut tree_complexity = tree_complexity + node_complexity;
-        let arguments = (0..function.arguments_number)
+        let arguments = (0..function.choose_arguments_number(random))
             .map(|_| {
                 let CreateRandomNodeResult { node, complexity } =
                     Node::create_random(random, settings, variables, tree_complexity);
@@ -284,6 +416,21 @@ impl Node {
     }
 }
 
+impl Function {
+    /// Return the number of arguments, which is chosen randomly if the function is variadic.
+    pub fn choose_arguments_number<R>(&self, random: &mut R) -> usize
+    where
+        R: Random + ?Sized,
+    {
+        if self.is_variadic() {
+            let range = self.get_arguments_range();
+            random.gen_range(*range.start()..*range.end() + 1)
+        } else {
+            self.arguments_number
+        }
+    }
+}
+
 pub struct MockRandom {
     pub int: Option<Box<dyn Iterator<Item = usize>>>,
     pub float: Option<Box<dyn Iterator<Item = f64>>>,
@@ -338,7 +485,73 @@ impl Random for MockRandom {
 mod tests {
     use super::super::types::{OperationNode, ValueNode};
     use super::*;
-    use crate::model::settings::Settings;
+    use crate::model::settings::{Initialization, Settings};
+
+    #[test]
+    fn test_create_random_by_depth() {
+        let settings = Settings::default();
+        let variables = vec![String::from("x")];
+        let mut random = SeededRandom::seeded(1);
+        for _ in 0..100 {
+            let depth =
+                Node::create_random_by_depth(&mut random, &settings, &variables, 2, 4).get_depth();
+            assert!((2..=4).contains(&depth));
+        }
+    }
+
+    #[test]
+    fn test_create_random_initial_full() {
+        let settings = Settings {
+            initialization: Initialization::Full { max_depth: 3 },
+            ..Settings::default()
+        };
+        let variables = vec![String::from("x")];
+        let mut random = SeededRandom::seeded(1);
+        for _ in 0..100 {
+            let expression_tree = ExpressionTree::create_random(&mut random, &settings, &variables);
+            let mut leaf_depths = vec![];
+            collect_leaf_depths(&expression_tree.root, 0, &mut leaf_depths);
+            assert!(leaf_depths.iter().all(|&depth| depth == 3));
+        }
+    }
+
+    #[test]
+    fn test_create_random_initial_ramped_half_and_half() {
+        let settings = Settings {
+            initialization: Initialization::RampedHalfAndHalf {
+                min_depth: 1,
+                max_depth: 5,
+            },
+            ..Settings::default()
+        };
+        let variables = vec![String::from("x")];
+        let mut random = SeededRandom::seeded(1);
+        let mut depths = [0; 6];
+        for _ in 0..500 {
+            depths
+                [ExpressionTree::create_random(&mut random, &settings, &variables).get_depth()] +=
+                1;
+        }
+        assert_eq!(0, depths[0]);
+        assert!(depths[1..].iter().all(|&number| number > 0));
+    }
+
+    #[test]
+    fn test_seeded_random_state() {
+        let mut random = SeededRandom::seeded(42);
+        random.gen_float();
+        random.gen_range(0..10);
+        let state = random.get_state();
+        let mut restored_random = SeededRandom::from_state(&state);
+        for _ in 0..10 {
+            assert_eq!(random.gen_float(), restored_random.gen_float());
+            assert_eq!(
+                random.gen_float_standard(),
+                restored_random.gen_float_standard()
+            );
+            assert_eq!(random.gen_range(0..100), restored_random.gen_range(0..100));
+        }
+    }
 
     #[test]
     fn test_get_random_node() {
@@ -576,6 +789,22 @@ mod tests {
         assert_eq!(expected_expression_tree, actual_expression_tree);
     }
 
+    fn collect_leaf_depths(node: &Node, depth: usize, leaf_depths: &mut Vec<usize>) {
+        match node {
+            Node::Operator(OperationNode { arguments, .. }) => {
+                for argument in arguments {
+                    collect_leaf_depths(argument, depth + 1, leaf_depths);
+                }
+            }
+            Node::Function(OperationNode { arguments, .. }) => {
+                for argument in arguments {
+                    collect_leaf_depths(argument, depth + 1, leaf_depths);
+                }
+            }
+            Node::Value(_) => leaf_depths.push(depth),
+        }
+    }
+
     fn create_expression_tree(settings: &Settings) -> ExpressionTree {
         ExpressionTree {
             root: Node::Function(OperationNode {
@@ -69,4 +69,16 @@ mod tests {
         assert_eq!(expected_json, actual_json);
         Ok(())
     }
+
+    #[test]
+    fn test_serialize_conditional_expression_tree_to_json() -> Result<(), Error> {
+        let mut settings = Settings::default();
+        settings.add_piecewise_operations();
+        let tree = ExpressionTree::parse("if(x <= 2, heaviside(x), 1)", &settings).unwrap();
+        let expected_json =
+            r#"{"root":{"if":[{"<=":["x",2.0]},{"heaviside":["x"]},1.0]},"variables":["x"]}"#;
+        let actual_json = serde_json::to_string(&tree)?;
+        assert_eq!(expected_json, actual_json);
+        Ok(())
+    }
 }
@@ -31,6 +31,10 @@ impl ExpressionTree {
         });
         counter
     }
+    /// Return the length of the longest path from the root to a leaf.
+    pub fn get_depth(&self) -> usize {
+        self.root.get_depth()
+    }
     /// Return operator node indices according to pre-order traversal algorithm.
     pub fn get_operator_node_indices(&self) -> Vec<usize> {
         let mut indices = vec![];
@@ -55,6 +59,20 @@ impl ExpressionTree {
         });
         indices
     }
+    /// Return variadic function node indices according to pre-order traversal algorithm.
+    pub fn get_variadic_function_node_indices(&self) -> Vec<usize> {
+        let mut indices = vec![];
+        let mut counter = 0;
+        self.walk_pre_order(&mut |node| {
+            if let Node::Function(function_node) = node {
+                if function_node.operation.is_variadic() {
+                    indices.push(counter);
+                }
+            }
+            counter += 1;
+        });
+        indices
+    }
     /// Return value node indices according to pre-order traversal algorithm.
     pub fn get_value_node_indices(&self) -> Vec<usize> {
         let mut indices = vec![];
@@ -111,6 +129,27 @@ impl Node {
             }
         }
     }
+    /// Return number of nodes in the subtree.
+    pub fn count_nodes(&self) -> usize {
+        let mut counter = 0;
+        self.walk_pre_order(&mut |_| {
+            counter += 1;
+        });
+        counter
+    }
+    /// Return the length of the longest path from the node to a leaf.
+    pub fn get_depth(&self) -> usize {
+        let arguments = match self {
+            Node::Operator(operator_node) => &operator_node.arguments,
+            Node::Function(function_node) => &function_node.arguments,
+            Node::Value(_) => return 0,
+        };
+        1 + arguments
+            .iter()
+            .map(|argument| argument.get_depth())
+            .max()
+            .unwrap_or(0)
+    }
     /// Return a reference to the node that satisfies the predicate, or None if no such node exists.
     pub fn get_node<C>(&self, predicate: &mut C) -> Option<&Node>
     where
@@ -225,6 +264,14 @@ mod tests {
         assert_eq!(8, count);
     }
 
+    #[test]
+    fn test_get_depth() {
+        let settings = Settings::default();
+        let expression_tree = create_test_expression_tree(&settings);
+        assert_eq!(4, expression_tree.get_depth());
+        assert_eq!(0, Node::Value(ValueNode::Constant(1.0)).get_depth());
+    }
+
     #[test]
     fn test_get_operator_node_indices() {
         let settings = Settings::default();
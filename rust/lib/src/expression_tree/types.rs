@@ -1,7 +1,7 @@
 //! Expression tree types module.
-use serde::Serialize;
-use std::cmp::Ordering;
+use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::ops::RangeInclusive;
 use std::rc::Rc;
 
 #[derive(Debug, Clone, PartialEq, Serialize)]
@@ -53,14 +53,34 @@ pub trait Operation {
     fn get_complexity(&self) -> u32;
 }
 
+/// Computation of an operation that may have state, e.g. a clipping threshold or a lookup table.
+/// It is implemented for closures.
+pub trait Compute {
+    fn compute(&self, arguments: &[f64]) -> f64;
+}
+
+impl<F> Compute for F
+where
+    F: Fn(&[f64]) -> f64,
+{
+    fn compute(&self, arguments: &[f64]) -> f64 {
+        self(arguments)
+    }
+}
+
 #[derive(Clone)]
 pub struct Operator {
     pub name: String,
     pub precedence: u8,
     pub associativity: Associativity,
+    /// The position of the operator relative to its operands.
+    pub fixity: Fixity,
     pub arguments_number: usize,
     pub complexity: u32,
-    pub compute_fn: fn(arguments: &[f64]) -> f64,
+    /// True if the order of arguments doesn't affect the result.
+    pub commutative: bool,
+    /// The computation shared by the clones of the operator.
+    pub computation: Rc<dyn Compute>,
 }
 
 impl Operation for Operator {
@@ -73,7 +93,7 @@ impl Operation for Operator {
                 arguments.len()
             );
         }
-        (self.compute_fn)(arguments)
+        self.computation.compute(arguments)
     }
     fn get_name(&self) -> &str {
         self.name.as_str()
@@ -90,26 +110,58 @@ impl fmt::Debug for Operator {
             .field("arguments_number", &self.arguments_number)
             .field("precedence", &self.precedence)
             .field("associativity", &self.associativity)
+            .field("fixity", &self.fixity)
             .field("complexity", &self.complexity)
             .finish()
     }
 }
 
+/// Operators are equal if they have the same name, position and computation.
+/// Computations are compared by identity, since closures can't be compared by value.
 impl PartialEq for Operator {
     fn eq(&self, other: &Operator) -> bool {
-        format!("{:?}", self) == format!("{:?}", other)
+        self.name == other.name
+            && self.arguments_number == other.arguments_number
+            && self.fixity == other.fixity
+            && Rc::ptr_eq(&self.computation, &other.computation)
     }
 }
 
 impl Operator {
-    pub fn is_computed_before(&self, other: &Operator) -> bool {
-        match self.precedence.cmp(&other.precedence) {
-            Ordering::Equal => match other.associativity {
-                Associativity::Left => true,
-                Associativity::Right => false,
-            },
-            Ordering::Greater => true,
-            Ordering::Less => false,
+    /// Create an operator computed by a closure or another implementation that may have state.
+    pub fn with_implementation<C>(
+        name: &str,
+        precedence: u8,
+        associativity: Associativity,
+        fixity: Fixity,
+        arguments_number: usize,
+        complexity: u32,
+        implementation: C,
+    ) -> Operator
+    where
+        C: Compute + 'static,
+    {
+        Operator {
+            name: name.to_owned(),
+            precedence,
+            associativity,
+            fixity,
+            arguments_number,
+            complexity,
+            commutative: false,
+            computation: Rc::new(implementation),
+        }
+    }
+    /// Return the binding powers of the operator to the operands on the left and on the right,
+    /// or None if there is no operand on that side.
+    /// A higher precedence binds tighter, and the associativity breaks ties between equal precedences.
+    pub fn get_binding_powers(&self) -> (Option<u16>, Option<u16>) {
+        let power = self.precedence as u16 * 2;
+        match (&self.fixity, &self.associativity) {
+            (Fixity::Prefix, _) => (None, Some(power)),
+            (Fixity::Postfix, _) => (Some(power), None),
+            (Fixity::Infix, Associativity::Left) => (Some(power), Some(power + 1)),
+            (Fixity::Infix, Associativity::Right) => (Some(power + 1), Some(power)),
         }
     }
 }
@@ -117,22 +169,44 @@ impl Operator {
 #[derive(Clone)]
 pub struct Function {
     pub name: String,
+    /// The number of arguments, or the minimum number if the function is variadic.
     pub arguments_number: usize,
+    /// The maximum number of arguments of a variadic function, or None if the number is fixed.
+    pub max_arguments_number: Option<usize>,
     pub complexity: u32,
-    pub compute_fn: fn(arguments: &[f64]) -> f64,
+    /// True if the order of arguments doesn't affect the result.
+    pub commutative: bool,
+    /// The computation shared by the clones of the function, e.g. a closure or a declared expression.
+    pub computation: Rc<dyn Compute>,
+}
+
+/// Function declared by an expression of its parameters, e.g. `sigmoid(a) = 1 / (1 + exp(-a))`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionDefinition {
+    pub parameters: Vec<String>,
+    pub expression_tree: ExpressionTree,
 }
 
 impl Operation for Function {
     fn compute(&self, arguments: &[f64]) -> f64 {
-        if arguments.len() != self.arguments_number {
-            panic!(
-                "The function `{}` expected {} arguments, but received {}.",
-                self,
-                self.arguments_number,
-                arguments.len()
-            );
+        if !self.get_arguments_range().contains(&arguments.len()) {
+            match self.max_arguments_number {
+                Some(max_arguments_number) => panic!(
+                    "The function `{}` expected from {} to {} arguments, but received {}.",
+                    self,
+                    self.arguments_number,
+                    max_arguments_number,
+                    arguments.len()
+                ),
+                None => panic!(
+                    "The function `{}` expected {} arguments, but received {}.",
+                    self,
+                    self.arguments_number,
+                    arguments.len()
+                ),
+            }
         }
-        (self.compute_fn)(arguments)
+        self.computation.compute(arguments)
     }
     fn get_name(&self) -> &str {
         self.name.as_str()
@@ -144,17 +218,54 @@ impl Operation for Function {
 
 impl fmt::Debug for Function {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("Function")
+        let mut debug_struct = f.debug_struct("Function");
+        debug_struct
             .field("name", &self.name)
-            .field("arguments_number", &self.arguments_number)
-            .field("complexity", &self.complexity)
-            .finish()
+            .field("arguments_number", &self.arguments_number);
+        if let Some(max_arguments_number) = &self.max_arguments_number {
+            debug_struct.field("max_arguments_number", max_arguments_number);
+        }
+        debug_struct.field("complexity", &self.complexity).finish()
     }
 }
 
+/// Functions are equal if they have the same name and computation compared by identity.
 impl PartialEq for Function {
     fn eq(&self, other: &Function) -> bool {
-        format!("{:?}", self) == format!("{:?}", other)
+        self.name == other.name
+            && self.arguments_number == other.arguments_number
+            && self.max_arguments_number == other.max_arguments_number
+            && Rc::ptr_eq(&self.computation, &other.computation)
+    }
+}
+
+impl Function {
+    /// Create a function computed by a closure or another implementation that may have state.
+    pub fn with_implementation<C>(
+        name: &str,
+        arguments_number: usize,
+        complexity: u32,
+        implementation: C,
+    ) -> Function
+    where
+        C: Compute + 'static,
+    {
+        Function {
+            name: name.to_owned(),
+            arguments_number,
+            max_arguments_number: None,
+            complexity,
+            commutative: false,
+            computation: Rc::new(implementation),
+        }
+    }
+    /// Return the range of the allowed numbers of arguments.
+    pub fn get_arguments_range(&self) -> RangeInclusive<usize> {
+        self.arguments_number..=self.max_arguments_number.unwrap_or(self.arguments_number)
+    }
+    /// Check if the function accepts different numbers of arguments.
+    pub fn is_variadic(&self) -> bool {
+        self.get_arguments_range().count() > 1
     }
 }
 
@@ -164,6 +275,16 @@ pub enum Associativity {
     Right,
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Fixity {
+    /// The operator precedes its only operand, e.g. `-x`.
+    Prefix,
+    /// The operator is between two operands, e.g. `x + y`.
+    Infix,
+    /// The operator follows its only operand, e.g. `x!`.
+    Postfix,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -204,7 +325,7 @@ mod tests {
             let settings = Settings::default();
             let plus_operator = settings.find_binary_operator_by_name("+").unwrap();
             assert_eq!(
-                "Operator { name: \"+\", arguments_number: 2, precedence: 1, associativity: Left, complexity: 1 }",
+                "Operator { name: \"+\", arguments_number: 2, precedence: 1, associativity: Left, fixity: Infix, complexity: 1 }",
                 format!("{:?}", plus_operator)
             );
         }
@@ -219,6 +340,51 @@ mod tests {
             assert!(plus_operator1 != minus_operator);
         }
 
+        #[test]
+        fn test_eq_computation_identity() {
+            let other_settings = Settings::default();
+            let settings = Settings::default();
+            assert!(
+                settings.find_binary_operator_by_name("+")
+                    == other_settings.find_binary_operator_by_name("+")
+            );
+            let create_clip_operator = |computation: &Rc<dyn Compute>| Operator {
+                computation: Rc::clone(computation),
+                ..Operator::with_implementation(
+                    "clip",
+                    5,
+                    Associativity::Left,
+                    Fixity::Postfix,
+                    1,
+                    2,
+                    |_: &[f64]| f64::NAN,
+                )
+            };
+            let computation: Rc<dyn Compute> = Rc::new(|arguments: &[f64]| arguments[0].min(1.0));
+            let clip_operator = create_clip_operator(&computation);
+            assert!(clip_operator == create_clip_operator(&computation));
+            assert!(clip_operator == clip_operator.clone());
+            let other_computation: Rc<dyn Compute> =
+                Rc::new(|arguments: &[f64]| arguments[0].min(1.0));
+            assert!(clip_operator != create_clip_operator(&other_computation));
+        }
+
+        #[test]
+        fn test_compute_with_implementation() {
+            let threshold = 2.5;
+            let clip_operator = Operator::with_implementation(
+                "clip",
+                5,
+                Associativity::Left,
+                Fixity::Postfix,
+                1,
+                2,
+                move |arguments: &[f64]| arguments[0].min(threshold),
+            );
+            assert_eq!(2.5, clip_operator.compute(&[3.0]));
+            assert_eq!((Some(10), None), clip_operator.get_binding_powers());
+        }
+
         #[test]
         fn test_compute() {
             let settings = Settings::default();
@@ -235,17 +401,19 @@ mod tests {
         }
 
         #[test]
-        fn test_is_computed_before() {
+        fn test_get_binding_powers() {
             let settings = Settings::default();
             let plus_operator = settings.find_binary_operator_by_name("+").unwrap();
-            let minus_operator = settings.find_binary_operator_by_name("-").unwrap();
-            let asterisk_operator = settings.find_binary_operator_by_name("*").unwrap();
-            let slash_operator = settings.find_binary_operator_by_name("/").unwrap();
             let circumflex_operator = settings.find_binary_operator_by_name("^").unwrap();
-            assert!(plus_operator.is_computed_before(&plus_operator));
-            assert!(plus_operator.is_computed_before(&minus_operator));
-            assert!(!plus_operator.is_computed_before(&asterisk_operator));
-            assert!(circumflex_operator.is_computed_before(&slash_operator));
+            let minus_operator = settings.find_unary_operator_by_name("-").unwrap();
+            assert_eq!((Some(2), Some(3)), plus_operator.get_binding_powers());
+            assert_eq!((Some(7), Some(6)), circumflex_operator.get_binding_powers());
+            assert_eq!((None, Some(8)), minus_operator.get_binding_powers());
+            let factorial_operator = Operator {
+                fixity: Fixity::Postfix,
+                ..(*minus_operator).clone()
+            };
+            assert_eq!((Some(8), None), factorial_operator.get_binding_powers());
         }
     }
 
@@ -270,6 +438,78 @@ mod tests {
             let cos_function = settings.find_function_by_name("cos").unwrap();
             assert!(sin_function1 == sin_function2);
             assert!(sin_function1 != cos_function);
+            let clip_function =
+                Function::with_implementation("clip", 1, 2, |arguments: &[f64]| {
+                    arguments[0].min(1.0)
+                });
+            assert!(clip_function == clip_function.clone());
+            let other_clip_function =
+                Function::with_implementation("clip", 1, 2, |arguments: &[f64]| {
+                    arguments[0].min(1.0)
+                });
+            assert!(clip_function != other_clip_function);
+        }
+
+        #[test]
+        fn test_eq_computation_identity() {
+            let settings = Settings::default();
+            let other_settings = Settings::default();
+            assert!(
+                settings.find_function_by_name("sin")
+                    == other_settings.find_function_by_name("sin")
+            );
+            let computation: Rc<dyn Compute> = Rc::new(|arguments: &[f64]| arguments[0].min(1.0));
+            let clip_function = Function {
+                computation: Rc::clone(&computation),
+                ..Function::with_implementation("clip", 1, 2, |_: &[f64]| f64::NAN)
+            };
+            let same_clip_function = Function {
+                computation,
+                ..Function::with_implementation("clip", 1, 2, |_: &[f64]| f64::NAN)
+            };
+            assert!(clip_function == same_clip_function);
+            let renamed_clip_function = Function {
+                name: String::from("clip1"),
+                ..clip_function.clone()
+            };
+            assert!(clip_function != renamed_clip_function);
+        }
+
+        #[test]
+        fn test_compute_with_implementation() {
+            let threshold = 2.5;
+            let clip_function =
+                Function::with_implementation("clip", 1, 2, move |arguments: &[f64]| {
+                    arguments[0].min(threshold)
+                });
+            assert_eq!(2.5, clip_function.compute(&[3.0]));
+            let calibration_function = Function::with_implementation(
+                "calibration",
+                1,
+                3,
+                LookupTable {
+                    points: vec![(0.0, 1.0), (2.0, 5.0), (4.0, 6.0)],
+                },
+            );
+            assert_eq!(3.0, calibration_function.compute(&[1.0]));
+            assert_eq!(5.5, calibration_function.compute(&[3.0]));
+        }
+
+        /// Piecewise linear interpolation between sorted points.
+        struct LookupTable {
+            points: Vec<(f64, f64)>,
+        }
+
+        impl Compute for LookupTable {
+            fn compute(&self, arguments: &[f64]) -> f64 {
+                self.points
+                    .windows(2)
+                    .find(|window| arguments[0] <= window[1].0)
+                    .map_or(f64::NAN, |window| {
+                        let ((x1, y1), (x2, y2)) = (window[0], window[1]);
+                        y1 + (y2 - y1) * (arguments[0] - x1) / (x2 - x1)
+                    })
+            }
         }
 
         #[test]
@@ -279,6 +519,45 @@ mod tests {
             assert_eq!(2.0_f64.sin(), sin_function.compute(&[2.0]));
         }
 
+        #[test]
+        fn test_get_arguments_range() {
+            let settings = Settings::default();
+            let sin_function = settings.find_function_by_name("sin").unwrap();
+            assert_eq!(1..=1, sin_function.get_arguments_range());
+            assert!(!sin_function.is_variadic());
+            let max_function = create_max_function();
+            assert_eq!(2..=4, max_function.get_arguments_range());
+            assert!(max_function.is_variadic());
+        }
+
+        #[test]
+        fn test_compute_variadic() {
+            let max_function = create_max_function();
+            assert_eq!(2.0, max_function.compute(&[1.0, 2.0]));
+            assert_eq!(5.0, max_function.compute(&[1.0, 5.0, -3.0, 2.0]));
+        }
+
+        #[test]
+        #[should_panic(
+            expected = "The function `max` expected from 2 to 4 arguments, but received 5."
+        )]
+        fn test_compute_variadic_panic() {
+            create_max_function().compute(&[1.0, 2.0, 3.0, 4.0, 5.0]);
+        }
+
+        fn create_max_function() -> Function {
+            Function {
+                name: String::from("max"),
+                arguments_number: 2,
+                max_arguments_number: Some(4),
+                complexity: 2,
+                commutative: true,
+                computation: Rc::new(|arguments: &[f64]| {
+                    arguments.iter().copied().fold(f64::NAN, f64::max)
+                }),
+            }
+        }
+
         #[test]
         #[should_panic(expected = "The function `sin` expected 1 arguments, but received 2.")]
         fn test_compute_panic() {
@@ -1,6 +1,198 @@
 //! Module with crossing two expression trees.
+use super::settings::Settings;
 use crate::expression_tree::random::Random;
-use crate::expression_tree::ExpressionTree;
+use crate::expression_tree::{ExpressionTree, Node};
+use serde::Deserialize;
+
+/// Crossover operator producing offspring from two parents.
+pub trait Crossover {
+    /// Return one or two children. The child at index i is derived from the parent i + 1.
+    fn cross(
+        &self,
+        expression_tree1: &ExpressionTree,
+        expression_tree2: &ExpressionTree,
+        random: &mut dyn Random,
+        settings: &Settings,
+    ) -> Vec<CrossoverChild>;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CrossoverChild {
+    pub expression_tree: ExpressionTree,
+    /// The pre-order index of the replaced node in the parent, or None if the parent is unchanged.
+    pub crossover_point: Option<usize>,
+}
+
+/// Crossover method selectable by configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CrossoverMethod {
+    Subtree,
+    TwoChildSubtree,
+    SizeFair,
+    OnePoint,
+    DepthAware,
+}
+
+impl CrossoverMethod {
+    pub fn create_crossover(&self) -> Box<dyn Crossover> {
+        match self {
+            CrossoverMethod::Subtree => Box::new(SubtreeCrossover),
+            CrossoverMethod::TwoChildSubtree => Box::new(TwoChildSubtreeCrossover),
+            CrossoverMethod::SizeFair => Box::new(SizeFairCrossover),
+            CrossoverMethod::OnePoint => Box::new(OnePointCrossover),
+            CrossoverMethod::DepthAware => Box::new(DepthAwareCrossover),
+        }
+    }
+}
+
+/// Replace a random subtree of the first parent with a random subtree of the second one.
+pub struct SubtreeCrossover;
+
+impl Crossover for SubtreeCrossover {
+    fn cross(
+        &self,
+        expression_tree1: &ExpressionTree,
+        expression_tree2: &ExpressionTree,
+        random: &mut dyn Random,
+        _: &Settings,
+    ) -> Vec<CrossoverChild> {
+        check_variables(expression_tree1, expression_tree2);
+        let index2 = random.gen_range(0..expression_tree2.count_nodes());
+        let index1 = random.gen_range(0..expression_tree1.count_nodes());
+        let mut result_tree = expression_tree1.clone();
+        *result_tree.get_node_at_mut(index1) = expression_tree2.get_node_at(index2).clone();
+        vec![CrossoverChild {
+            expression_tree: result_tree,
+            crossover_point: Some(index1),
+        }]
+    }
+}
+
+/// Swap random subtrees of the parents and return both children.
+pub struct TwoChildSubtreeCrossover;
+
+impl Crossover for TwoChildSubtreeCrossover {
+    fn cross(
+        &self,
+        expression_tree1: &ExpressionTree,
+        expression_tree2: &ExpressionTree,
+        random: &mut dyn Random,
+        _: &Settings,
+    ) -> Vec<CrossoverChild> {
+        check_variables(expression_tree1, expression_tree2);
+        let index1 = random.gen_range(0..expression_tree1.count_nodes());
+        let index2 = random.gen_range(0..expression_tree2.count_nodes());
+        swap_subtrees(expression_tree1, expression_tree2, index1, index2)
+    }
+}
+
+/// Replace a random subtree of the first parent with a subtree of the second one
+/// containing at most 1 + 2 * n nodes, where n is the number of nodes of the removed subtree.
+pub struct SizeFairCrossover;
+
+impl Crossover for SizeFairCrossover {
+    fn cross(
+        &self,
+        expression_tree1: &ExpressionTree,
+        expression_tree2: &ExpressionTree,
+        random: &mut dyn Random,
+        _: &Settings,
+    ) -> Vec<CrossoverChild> {
+        check_variables(expression_tree1, expression_tree2);
+        let mut result_tree = expression_tree1.clone();
+        let index = random.gen_range(0..result_tree.count_nodes());
+        let node = result_tree.get_node_at_mut(index);
+        let max_node_number = 1 + 2 * node.count_nodes();
+        let mut donors = vec![];
+        expression_tree2.walk_pre_order(&mut |donor| {
+            if donor.count_nodes() <= max_node_number {
+                donors.push(donor);
+            }
+        });
+        *node = donors[random.gen_range(0..donors.len())].clone();
+        vec![CrossoverChild {
+            expression_tree: result_tree,
+            crossover_point: Some(index),
+        }]
+    }
+}
+
+/// Swap subtrees at a random point of the region where both parents have the same shape
+/// and return both children.
+pub struct OnePointCrossover;
+
+impl Crossover for OnePointCrossover {
+    fn cross(
+        &self,
+        expression_tree1: &ExpressionTree,
+        expression_tree2: &ExpressionTree,
+        random: &mut dyn Random,
+        _: &Settings,
+    ) -> Vec<CrossoverChild> {
+        check_variables(expression_tree1, expression_tree2);
+        let mut common_region = vec![];
+        collect_common_region(
+            &expression_tree1.root,
+            &expression_tree2.root,
+            0,
+            0,
+            &mut common_region,
+        );
+        let (index1, index2) = common_region[random.gen_range(0..common_region.len())];
+        swap_subtrees(expression_tree1, expression_tree2, index1, index2)
+    }
+}
+
+/// Replace a random subtree of the first parent with a random subtree of the second one
+/// chosen among those keeping the child within `Settings::bloat_limits`.
+/// The first parent is returned unchanged if there is no such subtree.
+pub struct DepthAwareCrossover;
+
+impl Crossover for DepthAwareCrossover {
+    fn cross(
+        &self,
+        expression_tree1: &ExpressionTree,
+        expression_tree2: &ExpressionTree,
+        random: &mut dyn Random,
+        settings: &Settings,
+    ) -> Vec<CrossoverChild> {
+        check_variables(expression_tree1, expression_tree2);
+        let mut depths = vec![];
+        collect_node_depths(&expression_tree1.root, 0, &mut depths);
+        let mut result_tree = expression_tree1.clone();
+        let node_number = result_tree.count_nodes();
+        let index = random.gen_range(0..node_number);
+        let node = result_tree.get_node_at_mut(index);
+        let rest_node_number = node_number - node.count_nodes();
+        let limits = &settings.bloat_limits;
+        let mut donors = vec![];
+        expression_tree2.walk_pre_order(&mut |donor| {
+            let depth_satisfied = match limits.max_depth {
+                Some(max_depth) => depths[index] + donor.get_depth() <= max_depth,
+                None => true,
+            };
+            let node_number_satisfied = match limits.max_node_number {
+                Some(max_node_number) => rest_node_number + donor.count_nodes() <= max_node_number,
+                None => true,
+            };
+            if depth_satisfied && node_number_satisfied {
+                donors.push(donor);
+            }
+        });
+        if donors.is_empty() {
+            return vec![CrossoverChild {
+                expression_tree: expression_tree1.clone(),
+                crossover_point: None,
+            }];
+        }
+        *node = donors[random.gen_range(0..donors.len())].clone();
+        vec![CrossoverChild {
+            expression_tree: result_tree,
+            crossover_point: Some(index),
+        }]
+    }
+}
 
 pub fn cross<R>(
     expression_tree1: &ExpressionTree,
@@ -8,24 +200,96 @@ pub fn cross<R>(
     random: &mut R,
 ) -> ExpressionTree
 where
-    R: Random,
+    R: Random + ?Sized,
 {
+    check_variables(expression_tree1, expression_tree2);
+    let mut result_tree = expression_tree1.clone();
+    *result_tree.get_random_node_mut(random) = expression_tree2.get_random_node(random).clone();
+    result_tree
+}
+
+fn check_variables(expression_tree1: &ExpressionTree, expression_tree2: &ExpressionTree) {
     assert_eq!(
         expression_tree1.variables, expression_tree2.variables,
         "Expression trees must contain the same variables, but the first expression tree contains {:?} and the second contains {:?}.",
         expression_tree1.variables, expression_tree2.variables
     );
-    let mut result_tree = expression_tree1.clone();
-    *result_tree.get_random_node_mut(random) = expression_tree2.get_random_node(random).clone();
-    result_tree
+}
+
+fn swap_subtrees(
+    expression_tree1: &ExpressionTree,
+    expression_tree2: &ExpressionTree,
+    index1: usize,
+    index2: usize,
+) -> Vec<CrossoverChild> {
+    let mut result_tree1 = expression_tree1.clone();
+    let mut result_tree2 = expression_tree2.clone();
+    *result_tree1.get_node_at_mut(index1) = expression_tree2.get_node_at(index2).clone();
+    *result_tree2.get_node_at_mut(index2) = expression_tree1.get_node_at(index1).clone();
+    vec![
+        CrossoverChild {
+            expression_tree: result_tree1,
+            crossover_point: Some(index1),
+        },
+        CrossoverChild {
+            expression_tree: result_tree2,
+            crossover_point: Some(index2),
+        },
+    ]
+}
+
+/// Collect pre-order index pairs of nodes located at the same position in both trees,
+/// descending only while the nodes have the same number of arguments.
+fn collect_common_region(
+    node1: &Node,
+    node2: &Node,
+    index1: usize,
+    index2: usize,
+    common_region: &mut Vec<(usize, usize)>,
+) {
+    common_region.push((index1, index2));
+    let arguments1 = get_arguments(node1);
+    let arguments2 = get_arguments(node2);
+    if arguments1.len() != arguments2.len() {
+        return;
+    }
+    let mut argument_index1 = index1 + 1;
+    let mut argument_index2 = index2 + 1;
+    for (argument1, argument2) in arguments1.iter().zip(arguments2) {
+        collect_common_region(
+            argument1,
+            argument2,
+            argument_index1,
+            argument_index2,
+            common_region,
+        );
+        argument_index1 += argument1.count_nodes();
+        argument_index2 += argument2.count_nodes();
+    }
+}
+
+/// Collect depths of nodes in pre-order.
+fn collect_node_depths(node: &Node, depth: usize, depths: &mut Vec<usize>) {
+    depths.push(depth);
+    for argument in get_arguments(node) {
+        collect_node_depths(argument, depth + 1, depths);
+    }
+}
+
+fn get_arguments(node: &Node) -> &[Node] {
+    match node {
+        Node::Operator(operator_node) => &operator_node.arguments,
+        Node::Function(function_node) => &function_node.arguments,
+        Node::Value(_) => &[],
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::super::settings::Settings;
+    use super::super::settings::BloatLimits;
     use super::*;
     use crate::expression_tree::random::MockRandom;
-    use crate::expression_tree::{Node, OperationNode, ValueNode};
+    use crate::expression_tree::{OperationNode, ValueNode};
 
     #[test]
     fn test_cross() {
@@ -75,6 +339,171 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_two_child_subtree_crossover() {
+        let settings = Settings::default();
+        let expression_tree1 = create_expression_tree1(&settings);
+        let expression_tree2 = create_expression_tree2(&settings);
+        let children = TwoChildSubtreeCrossover.cross(
+            &expression_tree1,
+            &expression_tree2,
+            &mut MockRandom::new_int(vec![3, 3]),
+            &settings,
+        );
+        let expected_children = vec![
+            CrossoverChild {
+                expression_tree: create_binary_tree(
+                    &settings,
+                    "sin",
+                    "+",
+                    "x1",
+                    ValueNode::Constant(5.0),
+                ),
+                crossover_point: Some(3),
+            },
+            CrossoverChild {
+                expression_tree: create_binary_tree(
+                    &settings,
+                    "cos",
+                    "-",
+                    "x2",
+                    ValueNode::Variable(String::from("x2")),
+                ),
+                crossover_point: Some(3),
+            },
+        ];
+        assert_eq!(expected_children, children);
+    }
+
+    #[test]
+    fn test_size_fair_crossover() {
+        let settings = Settings::default();
+        let expression_tree1 = create_expression_tree1(&settings);
+        let expression_tree2 = create_expression_tree2(&settings);
+        let children = SizeFairCrossover.cross(
+            &expression_tree1,
+            &expression_tree2,
+            &mut MockRandom::new_int(vec![3, 2]),
+            &settings,
+        );
+        assert_eq!(
+            vec![CrossoverChild {
+                expression_tree: create_binary_tree(
+                    &settings,
+                    "sin",
+                    "+",
+                    "x1",
+                    ValueNode::Constant(5.0)
+                ),
+                crossover_point: Some(3),
+            }],
+            children
+        );
+    }
+
+    #[test]
+    fn test_one_point_crossover() {
+        let settings = Settings::default();
+        let expression_tree1 = create_expression_tree1(&settings);
+        let expression_tree2 = ExpressionTree {
+            root: Node::Function(OperationNode {
+                operation: settings.find_function_by_name("cos").unwrap(),
+                arguments: vec![Node::Value(ValueNode::Constant(5.0))],
+            }),
+            variables: vec![String::from("x1"), String::from("x2")],
+        };
+        let mut common_region = vec![];
+        collect_common_region(
+            &expression_tree1.root,
+            &expression_tree2.root,
+            0,
+            0,
+            &mut common_region,
+        );
+        assert_eq!(vec![(0, 0), (1, 1)], common_region);
+        let children = OnePointCrossover.cross(
+            &expression_tree1,
+            &expression_tree2,
+            &mut MockRandom::new_int(vec![1]),
+            &settings,
+        );
+        assert_eq!(
+            ExpressionTree {
+                root: Node::Function(OperationNode {
+                    operation: settings.find_function_by_name("sin").unwrap(),
+                    arguments: vec![Node::Value(ValueNode::Constant(5.0))],
+                }),
+                variables: vec![String::from("x1"), String::from("x2")],
+            },
+            children[0].expression_tree
+        );
+        assert_eq!(Some(1), children[0].crossover_point);
+        assert_eq!(4, children[1].expression_tree.count_nodes());
+    }
+
+    #[test]
+    fn test_depth_aware_crossover() {
+        let settings = Settings {
+            bloat_limits: BloatLimits {
+                max_depth: Some(2),
+                ..BloatLimits::default()
+            },
+            ..Settings::default()
+        };
+        let expression_tree1 = create_expression_tree1(&settings);
+        let expression_tree2 = create_expression_tree2(&settings);
+        let children = DepthAwareCrossover.cross(
+            &expression_tree1,
+            &expression_tree2,
+            &mut MockRandom::new_int(vec![3, 1]),
+            &settings,
+        );
+        assert_eq!(
+            vec![CrossoverChild {
+                expression_tree: create_binary_tree(
+                    &settings,
+                    "sin",
+                    "+",
+                    "x1",
+                    ValueNode::Constant(5.0)
+                ),
+                crossover_point: Some(3),
+            }],
+            children
+        );
+    }
+
+    #[test]
+    fn test_deserialize_crossover_method() -> Result<(), serde_json::Error> {
+        assert_eq!(
+            CrossoverMethod::TwoChildSubtree,
+            serde_json::from_str::<CrossoverMethod>(r#""two_child_subtree""#)?
+        );
+        Ok(())
+    }
+
+    fn create_binary_tree(
+        settings: &Settings,
+        function: &str,
+        operator: &str,
+        variable: &str,
+        value: ValueNode,
+    ) -> ExpressionTree {
+        ExpressionTree {
+            root: Node::Function(OperationNode {
+                operation: settings.find_function_by_name(function).unwrap(),
+                arguments: vec![Node::Operator(OperationNode {
+                    operation: settings.find_binary_operator_by_name(operator).unwrap(),
+                    arguments: vec![
+                        Node::Value(ValueNode::Variable(String::from(variable))),
+                        Node::Value(value),
+                    ],
+                })],
+            }),
+            variables: vec![String::from("x1"), String::from("x2")],
+        }
+    }
+
     fn create_expression_tree1(settings: &Settings) -> ExpressionTree {
         ExpressionTree {
             root: Node::Function(OperationNode {
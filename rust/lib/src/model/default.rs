@@ -1,9 +1,28 @@
 //! Module with default symbolic regression model.
+pub mod adaptive_mutation;
+pub mod checkpoint;
 pub mod core;
+pub mod diversity;
+pub mod genealogy;
 pub mod generation_size;
+pub mod generation_stats;
+pub mod hall_of_fame;
+pub mod observer;
+pub mod replacement;
+pub mod restart;
 pub mod stop_criterion;
 mod utils;
 
 pub use self::core::*;
+pub use adaptive_mutation::*;
+pub use checkpoint::*;
+pub use diversity::*;
+pub use genealogy::*;
 pub use generation_size::*;
+pub use generation_stats::*;
+pub use hall_of_fame::*;
+pub use observer::*;
+pub use replacement::*;
+pub use restart::*;
 pub use stop_criterion::*;
+pub use utils::IdGenerator;
@@ -0,0 +1,267 @@
+//! Module with self-adaptive mutation probabilities.
+use super::super::settings::Settings;
+use serde::{Deserialize, Serialize};
+
+/// Probability matching scheme that re-weights mutations by the share of offspring better than their parent.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct AdaptiveMutation {
+    /// The lower bound of each mutation probability.
+    #[serde(default = "get_default_min_probability")]
+    pub min_probability: f64,
+    /// The upper bound of each mutation probability.
+    #[serde(default = "get_default_max_probability")]
+    pub max_probability: f64,
+    /// The weight of the last generation success rate in the mutation quality between 0.0 and 1.0.
+    #[serde(default = "get_default_adaptation_rate")]
+    pub adaptation_rate: f64,
+}
+
+/// The state of the adaptation stored between generations.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AdaptiveMutationState {
+    /// The sum of mutation probabilities kept during the adaptation.
+    pub total_probability: f64,
+    /// The smoothed success rate of each mutation.
+    pub qualities: Vec<f64>,
+    /// False for each mutation whose configured probability is 0, which is never adapted.
+    /// An empty list enables all mutations.
+    #[serde(default)]
+    pub enabled: Vec<bool>,
+}
+
+/// The number of offspring produced by each mutation in a generation and how many of them improved on their parent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MutationOutcomes {
+    pub applied_numbers: Vec<u32>,
+    pub improved_numbers: Vec<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MutationProbability {
+    pub name: String,
+    pub probability: f64,
+}
+
+impl Default for AdaptiveMutation {
+    fn default() -> AdaptiveMutation {
+        AdaptiveMutation {
+            min_probability: get_default_min_probability(),
+            max_probability: get_default_max_probability(),
+            adaptation_rate: get_default_adaptation_rate(),
+        }
+    }
+}
+
+impl AdaptiveMutation {
+    /// Return mutation probabilities proportional to the qualities within the bounds.
+    /// The probabilities of mutations clamped to the bounds are fixed and the rest of the total probability
+    /// is distributed among the other enabled mutations, so the sum stays equal to the total if the bounds allow it.
+    /// Disabled mutations keep zero probability.
+    pub fn get_probabilities(&self, state: &AdaptiveMutationState) -> Vec<f64> {
+        let mut probabilities = vec![0.0; state.qualities.len()];
+        let mut fixed = (0..state.qualities.len())
+            .map(|index| !state.is_enabled(index))
+            .collect::<Vec<bool>>();
+        loop {
+            let free_indices = (0..probabilities.len())
+                .filter(|index| !fixed[*index])
+                .collect::<Vec<usize>>();
+            if free_indices.is_empty() {
+                break;
+            }
+            let fixed_probability = probabilities
+                .iter()
+                .zip(&fixed)
+                .filter(|(_, fixed)| **fixed)
+                .map(|(probability, _)| probability)
+                .sum::<f64>();
+            let free_probability = (state.total_probability - fixed_probability).max(0.0);
+            let quality_sum = free_indices
+                .iter()
+                .map(|index| state.qualities[*index])
+                .sum::<f64>();
+            let mut clamped = false;
+            for index in &free_indices {
+                let share = if quality_sum > 0.0 {
+                    state.qualities[*index] / quality_sum
+                } else {
+                    1.0 / free_indices.len() as f64
+                };
+                let probability = free_probability * share;
+                probabilities[*index] =
+                    probability.clamp(self.min_probability, self.max_probability);
+                if probabilities[*index] != probability {
+                    fixed[*index] = true;
+                    clamped = true;
+                }
+            }
+            if !clamped {
+                break;
+            }
+        }
+        probabilities
+    }
+    /// Update the qualities of applied mutations with their success rates.
+    pub fn update(&self, state: &mut AdaptiveMutationState, outcomes: &MutationOutcomes) {
+        for (index, quality) in state.qualities.iter_mut().enumerate() {
+            let applied_number = outcomes.applied_numbers[index];
+            if applied_number > 0 {
+                let success_rate = outcomes.improved_numbers[index] as f64 / applied_number as f64;
+                *quality =
+                    (1.0 - self.adaptation_rate) * *quality + self.adaptation_rate * success_rate;
+            }
+        }
+    }
+    /// Set the mutation probabilities of the settings according to the state.
+    pub fn apply(&self, state: &AdaptiveMutationState, settings: &mut Settings) {
+        for (mutation, probability) in settings
+            .mutations
+            .iter_mut()
+            .zip(self.get_probabilities(state))
+        {
+            mutation.probability = probability;
+        }
+    }
+}
+
+impl AdaptiveMutationState {
+    fn is_enabled(&self, index: usize) -> bool {
+        self.enabled.get(index).copied().unwrap_or(true)
+    }
+    /// Create the state with qualities proportional to the current mutation probabilities.
+    /// Mutations with zero probability stay disabled.
+    pub fn new(settings: &Settings) -> AdaptiveMutationState {
+        let total_probability = settings
+            .mutations
+            .iter()
+            .map(|mutation| mutation.probability)
+            .sum::<f64>();
+        AdaptiveMutationState {
+            total_probability,
+            qualities: settings
+                .mutations
+                .iter()
+                .map(|mutation| {
+                    if total_probability > 0.0 {
+                        mutation.probability / total_probability
+                    } else {
+                        0.0
+                    }
+                })
+                .collect(),
+            enabled: settings
+                .mutations
+                .iter()
+                .map(|mutation| mutation.probability > 0.0)
+                .collect(),
+        }
+    }
+}
+
+impl MutationOutcomes {
+    pub fn new(mutation_number: usize) -> MutationOutcomes {
+        MutationOutcomes {
+            applied_numbers: vec![0; mutation_number],
+            improved_numbers: vec![0; mutation_number],
+        }
+    }
+}
+
+impl MutationProbability {
+    pub fn from_settings(settings: &Settings) -> Vec<MutationProbability> {
+        settings
+            .mutations
+            .iter()
+            .map(|mutation| MutationProbability {
+                name: mutation.name.to_owned(),
+                probability: mutation.probability,
+            })
+            .collect()
+    }
+}
+
+fn get_default_min_probability() -> f64 {
+    0.01
+}
+
+fn get_default_max_probability() -> f64 {
+    0.5
+}
+
+fn get_default_adaptation_rate() -> f64 {
+    0.3
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_and_get_probabilities() {
+        let adaptive_mutation = AdaptiveMutation {
+            min_probability: 0.05,
+            max_probability: 0.5,
+            adaptation_rate: 0.5,
+        };
+        let mut state = AdaptiveMutationState {
+            total_probability: 0.8,
+            qualities: vec![0.5, 0.5, 0.0],
+            enabled: vec![true; 3],
+        };
+        adaptive_mutation.update(
+            &mut state,
+            &MutationOutcomes {
+                applied_numbers: vec![4, 2, 0],
+                improved_numbers: vec![0, 1, 0],
+            },
+        );
+        assert_eq!(vec![0.25, 0.5, 0.0], state.qualities);
+        let probabilities = adaptive_mutation.get_probabilities(&state);
+        assert_eq!(3, probabilities.len());
+        assert!((0.25 - probabilities[0]).abs() < 1e-9);
+        assert!((0.5 - probabilities[1]).abs() < 1e-9);
+        assert!((0.05 - probabilities[2]).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_get_probabilities_disabled() {
+        let adaptive_mutation = AdaptiveMutation {
+            min_probability: 0.05,
+            max_probability: 0.5,
+            adaptation_rate: 0.5,
+        };
+        let state = AdaptiveMutationState {
+            total_probability: 0.8,
+            qualities: vec![0.25, 0.5, 0.0],
+            enabled: vec![true, true, false],
+        };
+        let probabilities = adaptive_mutation.get_probabilities(&state);
+        assert!((0.3 - probabilities[0]).abs() < 1e-9);
+        assert!((0.5 - probabilities[1]).abs() < 1e-9);
+        assert_eq!(0.0, probabilities[2]);
+    }
+
+    #[test]
+    fn test_new_state() {
+        let settings = Settings::default();
+        let state = AdaptiveMutationState::new(&settings);
+        assert!((0.9 - state.total_probability).abs() < 1e-9);
+        let probabilities = AdaptiveMutation::default().get_probabilities(&state);
+        for (mutation, probability) in settings.mutations.iter().zip(&probabilities) {
+            assert!((mutation.probability - probability).abs() < 1e-9);
+        }
+        assert!((0.9 - probabilities.iter().sum::<f64>()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_deserialize() -> Result<(), serde_json::Error> {
+        let expected_adaptive_mutation = AdaptiveMutation {
+            min_probability: 0.02,
+            ..AdaptiveMutation::default()
+        };
+        let actual_adaptive_mutation =
+            serde_json::from_str::<AdaptiveMutation>(r#"{"min_probability": 0.02}"#)?;
+        assert_eq!(expected_adaptive_mutation, actual_adaptive_mutation);
+        Ok(())
+    }
+}
@@ -0,0 +1,389 @@
+//! Module with model checkpoints.
+use super::super::fitness::Fitness;
+use super::super::settings::Settings;
+use super::adaptive_mutation::AdaptiveMutationState;
+use super::core::Individual;
+use super::genealogy::Provenance;
+use crate::expression_tree::random::RandomState;
+use crate::expression_tree::{ExpressionTree, Fixity, Node, Operation, OperationNode, ValueNode};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::rc::Rc;
+use std::time::Duration;
+
+/// The model state required to resume a run with identical subsequent behavior.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub generation_number: u32,
+    pub without_improvement_generation_number: u32,
+    #[serde(with = "float")]
+    pub best_error: f64,
+    pub stop_requested: bool,
+    /// The time elapsed since the model initialization.
+    pub elapsed: Duration,
+    pub evaluation_number: u64,
+    /// The id of the next created individual.
+    pub next_id: u32,
+    pub random_state: RandomState,
+    pub current_generation: Vec<CheckpointIndividual>,
+    pub auxiliary_expression_trees: Vec<CheckpointExpressionTree>,
+    #[serde(default)]
+    pub adaptive_mutation_state: Option<AdaptiveMutationState>,
+    #[serde(default)]
+    pub restart_number: u32,
+    #[serde(default)]
+    pub hall_of_fame: Vec<CheckpointIndividual>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CheckpointIndividual {
+    pub id: u32,
+    pub generation_number: u32,
+    pub expression_tree: CheckpointExpressionTree,
+    #[serde(with = "float")]
+    pub error: f64,
+    pub complexity: u32,
+    #[serde(default)]
+    pub provenance: Provenance,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CheckpointExpressionTree {
+    pub root: CheckpointNode,
+    pub variables: Vec<String>,
+}
+
+/// Expression tree node with operations stored by name.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum CheckpointNode {
+    Operator {
+        name: String,
+        /// None for checkpoints saved before operators of any fixity were supported,
+        /// whose operators are prefix if unary and infix if binary.
+        #[serde(default)]
+        fixity: Option<Fixity>,
+        arguments: Vec<CheckpointNode>,
+    },
+    Function {
+        name: String,
+        arguments: Vec<CheckpointNode>,
+    },
+    Variable(String),
+    Constant(#[serde(with = "float")] f64),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CheckpointError {
+    UnknownOperationError(UnknownOperationError),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnknownOperationError {
+    pub name: String,
+    pub arguments_number: usize,
+}
+
+impl fmt::Display for CheckpointError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckpointError::UnknownOperationError(err) => write!(
+                f,
+                r#"The checkpoint contains an unknown operation "{}" with {} arguments."#,
+                err.name, err.arguments_number
+            ),
+        }
+    }
+}
+
+impl CheckpointIndividual {
+    pub fn to_individual(&self, settings: &Settings) -> Result<Individual, CheckpointError> {
+        Ok(Individual {
+            id: self.id,
+            generation_number: self.generation_number,
+            expression_tree: self.expression_tree.to_expression_tree(settings)?,
+            fitness: Fitness {
+                error: self.error,
+                complexity: self.complexity,
+            },
+            defective: self.error.is_nan(),
+            provenance: self.provenance.clone(),
+        })
+    }
+}
+
+impl From<&Rc<Individual>> for CheckpointIndividual {
+    fn from(individual: &Rc<Individual>) -> CheckpointIndividual {
+        CheckpointIndividual {
+            id: individual.id,
+            generation_number: individual.generation_number,
+            expression_tree: CheckpointExpressionTree::from(&individual.expression_tree),
+            error: individual.fitness.error,
+            complexity: individual.fitness.complexity,
+            provenance: individual.provenance.clone(),
+        }
+    }
+}
+
+impl CheckpointExpressionTree {
+    pub fn to_expression_tree(
+        &self,
+        settings: &Settings,
+    ) -> Result<ExpressionTree, CheckpointError> {
+        Ok(ExpressionTree {
+            root: self.root.to_node(settings)?,
+            variables: self.variables.clone(),
+        })
+    }
+}
+
+impl From<&ExpressionTree> for CheckpointExpressionTree {
+    fn from(expression_tree: &ExpressionTree) -> CheckpointExpressionTree {
+        CheckpointExpressionTree {
+            root: CheckpointNode::from(&expression_tree.root),
+            variables: expression_tree.variables.clone(),
+        }
+    }
+}
+
+impl CheckpointNode {
+    pub fn to_node(&self, settings: &Settings) -> Result<Node, CheckpointError> {
+        match self {
+            CheckpointNode::Operator {
+                name,
+                fixity,
+                arguments,
+            } => {
+                let fixity = match (fixity, arguments.len()) {
+                    (Some(fixity), _) => Some(fixity.clone()),
+                    (None, 1) => Some(Fixity::Prefix),
+                    (None, 2) => Some(Fixity::Infix),
+                    _ => None,
+                };
+                let operation = fixity
+                    .and_then(|fixity| settings.find_operator_by_name(name, fixity))
+                    .filter(|operation| operation.arguments_number == arguments.len());
+                match operation {
+                    Some(operation) => Ok(Node::Operator(OperationNode {
+                        operation,
+                        arguments: CheckpointNode::to_nodes(arguments, settings)?,
+                    })),
+                    None => Err(CheckpointNode::create_unknown_operation_error(
+                        name, arguments,
+                    )),
+                }
+            }
+            CheckpointNode::Function { name, arguments } => {
+                match settings.find_function_by_name(name) {
+                    Some(operation)
+                        if operation.get_arguments_range().contains(&arguments.len()) =>
+                    {
+                        Ok(Node::Function(OperationNode {
+                            operation,
+                            arguments: CheckpointNode::to_nodes(arguments, settings)?,
+                        }))
+                    }
+                    _ => Err(CheckpointNode::create_unknown_operation_error(
+                        name, arguments,
+                    )),
+                }
+            }
+            CheckpointNode::Variable(variable) => {
+                Ok(Node::Value(ValueNode::Variable(variable.to_owned())))
+            }
+            CheckpointNode::Constant(constant) => Ok(Node::Value(ValueNode::Constant(*constant))),
+        }
+    }
+    fn to_nodes(
+        nodes: &[CheckpointNode],
+        settings: &Settings,
+    ) -> Result<Vec<Node>, CheckpointError> {
+        nodes.iter().map(|node| node.to_node(settings)).collect()
+    }
+    fn create_unknown_operation_error(name: &str, arguments: &[CheckpointNode]) -> CheckpointError {
+        CheckpointError::UnknownOperationError(UnknownOperationError {
+            name: name.to_owned(),
+            arguments_number: arguments.len(),
+        })
+    }
+}
+
+impl From<&Node> for CheckpointNode {
+    fn from(node: &Node) -> CheckpointNode {
+        match node {
+            Node::Operator(operator_node) => CheckpointNode::Operator {
+                name: operator_node.operation.get_name().to_owned(),
+                fixity: Some(operator_node.operation.fixity.clone()),
+                arguments: operator_node
+                    .arguments
+                    .iter()
+                    .map(CheckpointNode::from)
+                    .collect(),
+            },
+            Node::Function(function_node) => CheckpointNode::Function {
+                name: function_node.operation.get_name().to_owned(),
+                arguments: function_node
+                    .arguments
+                    .iter()
+                    .map(CheckpointNode::from)
+                    .collect(),
+            },
+            Node::Value(ValueNode::Variable(variable)) => {
+                CheckpointNode::Variable(variable.to_owned())
+            }
+            Node::Value(ValueNode::Constant(constant)) => CheckpointNode::Constant(*constant),
+        }
+    }
+}
+
+/// Floats are stored as strings, since JSON numbers can't represent NaN and infinity
+/// and are not guaranteed to be parsed back to the same value.
+mod float {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(value: &f64, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&format!("{:?}", value))
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<f64, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        value
+            .parse::<f64>()
+            .map_err(|err| D::Error::custom(format!(r#"Invalid float {}: "{}"."#, value, err)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expression_tree::{Associativity, Operator};
+
+    #[test]
+    fn test_node_conversion() -> Result<(), CheckpointError> {
+        let settings = Settings::default();
+        let expression_tree = create_expression_tree(&settings);
+        let checkpoint_expression_tree = CheckpointExpressionTree::from(&expression_tree);
+        assert_eq!(
+            expression_tree,
+            checkpoint_expression_tree.to_expression_tree(&settings)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_node_conversion_operator_fixity() -> Result<(), CheckpointError> {
+        let mut settings = Settings::default();
+        for fixity in [Fixity::Prefix, Fixity::Postfix] {
+            settings
+                .operators
+                .push(Rc::new(Operator::with_implementation(
+                    "!",
+                    5,
+                    Associativity::Left,
+                    fixity,
+                    1,
+                    3,
+                    |arguments: &[f64]| arguments[0],
+                )));
+        }
+        let expression_tree = ExpressionTree {
+            root: Node::Operator(OperationNode {
+                operation: settings
+                    .find_operator_by_name("!", Fixity::Postfix)
+                    .unwrap(),
+                arguments: vec![Node::Value(ValueNode::Variable(String::from("x")))],
+            }),
+            variables: vec![String::from("x")],
+        };
+        let json =
+            serde_json::to_string(&CheckpointExpressionTree::from(&expression_tree)).unwrap();
+        let checkpoint_expression_tree =
+            serde_json::from_str::<CheckpointExpressionTree>(&json).unwrap();
+        assert_eq!(
+            expression_tree,
+            checkpoint_expression_tree.to_expression_tree(&settings)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_node_conversion_without_fixity() -> Result<(), CheckpointError> {
+        let settings = Settings::default();
+        let json = r#"{"root":{"Operator":{"name":"-","arguments":[{"Operator":{"name":"-","arguments":[{"Variable":"x"}]}},{"Function":{"name":"sin","arguments":[{"Constant":"0.1"}]}}]}},"variables":["x"]}"#;
+        let checkpoint_expression_tree =
+            serde_json::from_str::<CheckpointExpressionTree>(json).unwrap();
+        assert_eq!(
+            create_expression_tree(&settings),
+            checkpoint_expression_tree.to_expression_tree(&settings)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_node_conversion_unknown_operation() {
+        let settings = Settings::default();
+        let node = CheckpointNode::Function {
+            name: String::from("sqrt"),
+            arguments: vec![CheckpointNode::Constant(1.0), CheckpointNode::Constant(2.0)],
+        };
+        assert_eq!(
+            Err(CheckpointError::UnknownOperationError(
+                UnknownOperationError {
+                    name: String::from("sqrt"),
+                    arguments_number: 2
+                }
+            )),
+            node.to_node(&settings)
+        );
+    }
+
+    #[test]
+    fn test_serialize_individual() -> Result<(), serde_json::Error> {
+        let settings = Settings::default();
+        let individual = CheckpointIndividual {
+            id: 1,
+            generation_number: 2,
+            expression_tree: CheckpointExpressionTree::from(&create_expression_tree(&settings)),
+            error: f64::NAN,
+            complexity: 5,
+            provenance: Provenance::default(),
+        };
+        let json = serde_json::to_string(&individual)?;
+        assert_eq!(
+            r#"{"id":1,"generation_number":2,"expression_tree":{"root":{"Operator":{"name":"-","fixity":"Infix","arguments":[{"Operator":{"name":"-","fixity":"Prefix","arguments":[{"Variable":"x"}]}},{"Function":{"name":"sin","arguments":[{"Constant":"0.1"}]}}]}},"variables":["x"]},"error":"NaN","complexity":5,"provenance":{"parent_ids":[],"crossover_point":null,"mutations":[]}}"#,
+            json
+        );
+        let deserialized_individual = serde_json::from_str::<CheckpointIndividual>(&json)?;
+        assert!(deserialized_individual.error.is_nan());
+        assert_eq!(
+            individual.expression_tree,
+            deserialized_individual.expression_tree
+        );
+        Ok(())
+    }
+
+    fn create_expression_tree(settings: &Settings) -> ExpressionTree {
+        ExpressionTree {
+            root: Node::Operator(OperationNode {
+                operation: settings.find_binary_operator_by_name("-").unwrap(),
+                arguments: vec![
+                    Node::Operator(OperationNode {
+                        operation: settings.find_unary_operator_by_name("-").unwrap(),
+                        arguments: vec![Node::Value(ValueNode::Variable(String::from("x")))],
+                    }),
+                    Node::Function(OperationNode {
+                        operation: settings.find_function_by_name("sin").unwrap(),
+                        arguments: vec![Node::Value(ValueNode::Constant(0.1))],
+                    }),
+                ],
+            }),
+            variables: vec![String::from("x")],
+        }
+    }
+}
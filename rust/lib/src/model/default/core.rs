@@ -1,17 +1,30 @@
 //! Module with symbolic regression default model core functionality.
-use super::super::crossing;
 use super::super::fitness::{Fitness, FitnessError};
 use super::super::input_data::InputData;
-use super::super::settings::Settings;
+use super::super::settings::{BloatPolicy, Settings};
+use super::adaptive_mutation::{
+    AdaptiveMutation, AdaptiveMutationState, MutationOutcomes, MutationProbability,
+};
+use super::checkpoint::{
+    Checkpoint, CheckpointError, CheckpointExpressionTree, CheckpointIndividual,
+};
+use super::diversity::{get_mean_tree_distance, Diversity};
+use super::genealogy::{Genealogy, Provenance};
 use super::generation_size::GenerationSize;
-use super::stop_criterion::{StopCriterion, StopReason};
+use super::generation_stats::GenerationStats;
+use super::hall_of_fame::HallOfFame;
+use super::observer::{Observer, ObserverAction};
+use super::replacement::{Replacement, ReplacementStrategy};
+use super::restart::Restart;
+use super::stop_criterion::{StopCriterion, StopData, StopReason};
 use super::utils::{get_individuals_fitness, sort_individuals, IdGenerator};
-use crate::expression_tree::random::{DefaultRandom, Random};
+use crate::expression_tree::random::{DefaultRandom, Random, SeededRandom};
 use crate::expression_tree::{Computable, ExpressionTree};
 use rand::rngs::ThreadRng;
 use rand_distr::Normal;
 use serde::Serialize;
 use std::rc::Rc;
+use std::time::Instant;
 
 pub struct Model<R: Random> {
     pub settings: Settings,
@@ -19,9 +32,26 @@ pub struct Model<R: Random> {
     pub stop_criterion: StopCriterion,
     pub generation_size: GenerationSize,
     pub auxiliary_expression_trees: Vec<ExpressionTree>,
-    pub callback: Option<Box<dyn FnMut(&[Rc<Individual>])>>,
+    pub replacement: Replacement,
+    /// The scheme adapting mutation probabilities, or None to keep them fixed.
+    pub adaptive_mutation: Option<AdaptiveMutation>,
+    pub adaptive_mutation_state: Option<AdaptiveMutationState>,
+    /// The diversity preserving selection of the next generation, or None to select the best individuals.
+    pub diversity: Option<Diversity>,
+    /// The archive of ancestors of the current generation, or None to not track the genealogy.
+    pub genealogy: Option<Genealogy>,
+    /// The archive of the best individuals seen during the run.
+    pub hall_of_fame: HallOfFame,
+    /// The policy of reinitializing the population on stagnation, or None to never restart.
+    pub restart: Option<Restart>,
+    pub observers: Vec<Box<dyn Observer>>,
     pub random: R,
-    pub id_generator: Box<dyn Iterator<Item = u32>>,
+    pub id_generator: IdGenerator,
+    /// The number of fitness evaluations performed since the model initialization.
+    pub evaluation_number: u64,
+    /// The number of offspring exceeding the bloat limits in the last created generation.
+    pub rejected_offspring_number: usize,
+    pub state: Option<ModelState>,
 }
 
 impl Model<DefaultRandom<ThreadRng, Normal<f64>>> {
@@ -31,58 +61,365 @@ impl Model<DefaultRandom<ThreadRng, Normal<f64>>> {
         stop_criterion: StopCriterion,
         generation_size: GenerationSize,
         auxiliary_expression_trees: Vec<ExpressionTree>,
-        callback: Option<Box<dyn FnMut(&[Rc<Individual>])>>,
+        observers: Vec<Box<dyn Observer>>,
     ) -> Model<DefaultRandom<ThreadRng, Normal<f64>>> {
-        Model {
+        Model::with_random(
             settings,
             input_data,
             stop_criterion,
             generation_size,
             auxiliary_expression_trees,
-            callback,
-            random: DefaultRandom::default(),
-            id_generator: Box::new(IdGenerator { id: 0 }),
+            observers,
+            DefaultRandom::default(),
+        )
+    }
+}
+
+impl Model<SeededRandom> {
+    /// Return the checkpoint of the current model state.
+    /// Panic if the model has not been initialized.
+    pub fn create_checkpoint(&self) -> Checkpoint {
+        let state = match &self.state {
+            Some(state) => state,
+            None => panic!("The model must be initialized before creating a checkpoint."),
+        };
+        Checkpoint {
+            generation_number: state.generation_number,
+            without_improvement_generation_number: state.without_improvement_generation_number,
+            best_error: state.best_error,
+            stop_requested: state.stop_requested,
+            elapsed: state.started_at.elapsed(),
+            evaluation_number: self.evaluation_number,
+            next_id: self.id_generator.id,
+            random_state: self.random.get_state(),
+            current_generation: state
+                .current_generation
+                .iter()
+                .map(CheckpointIndividual::from)
+                .collect(),
+            auxiliary_expression_trees: self
+                .auxiliary_expression_trees
+                .iter()
+                .map(CheckpointExpressionTree::from)
+                .collect(),
+            adaptive_mutation_state: self.adaptive_mutation_state.clone(),
+            restart_number: state.restart_number,
+            hall_of_fame: state
+                .hall_of_fame
+                .iter()
+                .map(CheckpointIndividual::from)
+                .collect(),
+        }
+    }
+    /// Restore the model state from the checkpoint instead of the initialization.
+    /// Observers are not notified about the run start.
+    pub fn resume(&mut self, checkpoint: &Checkpoint) -> Result<&ModelState, CheckpointError> {
+        let mut current_generation = vec![];
+        for individual in &checkpoint.current_generation {
+            current_generation.push(Rc::new(individual.to_individual(&self.settings)?));
+        }
+        let mut hall_of_fame = vec![];
+        for individual in &checkpoint.hall_of_fame {
+            hall_of_fame.push(Rc::new(individual.to_individual(&self.settings)?));
+        }
+        let mut auxiliary_expression_trees = vec![];
+        for expression_tree in &checkpoint.auxiliary_expression_trees {
+            auxiliary_expression_trees.push(expression_tree.to_expression_tree(&self.settings)?);
         }
+        self.auxiliary_expression_trees = auxiliary_expression_trees;
+        self.evaluation_number = checkpoint.evaluation_number;
+        self.id_generator = IdGenerator {
+            id: checkpoint.next_id,
+        };
+        self.random = SeededRandom::from_state(&checkpoint.random_state);
+        self.adaptive_mutation_state = checkpoint.adaptive_mutation_state.clone();
+        if let (Some(adaptive_mutation), Some(adaptive_mutation_state)) =
+            (&self.adaptive_mutation, &self.adaptive_mutation_state)
+        {
+            adaptive_mutation.apply(adaptive_mutation_state, &mut self.settings);
+        }
+        let now = Instant::now();
+        let mut state = ModelState {
+            generation_number: checkpoint.generation_number,
+            without_improvement_generation_number: checkpoint.without_improvement_generation_number,
+            best_error: checkpoint.best_error,
+            stop_requested: checkpoint.stop_requested,
+            started_at: now.checked_sub(checkpoint.elapsed).unwrap_or(now),
+            stats: self.create_stats(
+                checkpoint.generation_number,
+                &current_generation,
+                checkpoint.without_improvement_generation_number,
+            ),
+            current_generation,
+            restart_number: checkpoint.restart_number,
+            hall_of_fame,
+            stop_reason: None,
+        };
+        state.stats.restart_number = checkpoint.restart_number;
+        state.stats.mutation_probabilities = MutationProbability::from_settings(&self.settings);
+        if let Some(genealogy) = &mut self.genealogy {
+            *genealogy = Genealogy::default();
+            for individual in state.current_generation.iter().chain(&state.hall_of_fame) {
+                genealogy.add(individual);
+            }
+        }
+        state.stop_reason = self.must_stop(&state);
+        Ok(self.state.insert(state))
     }
 }
 
 impl<R: Random> Model<R> {
+    pub fn with_random(
+        settings: Settings,
+        input_data: InputData,
+        stop_criterion: StopCriterion,
+        generation_size: GenerationSize,
+        auxiliary_expression_trees: Vec<ExpressionTree>,
+        observers: Vec<Box<dyn Observer>>,
+        random: R,
+    ) -> Model<R> {
+        Model {
+            settings,
+            input_data,
+            stop_criterion,
+            generation_size,
+            auxiliary_expression_trees,
+            replacement: Replacement::default(),
+            adaptive_mutation: None,
+            adaptive_mutation_state: None,
+            diversity: None,
+            genealogy: None,
+            hall_of_fame: HallOfFame::default(),
+            restart: None,
+            observers,
+            random,
+            id_generator: IdGenerator { id: 0 },
+            evaluation_number: 0,
+            rejected_offspring_number: 0,
+            state: None,
+        }
+    }
+    /// Run the model until one of the stop criteria is met.
     pub fn run(&mut self) -> Result<ModelResult, FitnessError> {
-        let mut generation_number = 0;
-        let mut without_improvement_generation_number = 0;
-        let mut current_generation = self.create_first_generation()?;
-        self.execute_callback(&current_generation);
-        let mut error = current_generation[0].fitness.error;
-        let mut stop_reason = self.stop_criterion.must_stop(
-            error,
-            without_improvement_generation_number,
+        let mut stopped = self.initialize()?.stop_reason.is_some();
+        while !stopped {
+            stopped = self.step()?.stop_reason.is_some();
+        }
+        Ok(self.finish())
+    }
+    /// Create the first generation and return the model state.
+    pub fn initialize(&mut self) -> Result<&ModelState, FitnessError> {
+        let started_at = Instant::now();
+        self.evaluation_number = 0;
+        self.adaptive_mutation_state = self
+            .adaptive_mutation
+            .as_ref()
+            .map(|_| AdaptiveMutationState::new(&self.settings));
+        if let Some(genealogy) = &mut self.genealogy {
+            *genealogy = Genealogy::default();
+        }
+        let stop_requested = self.notify_run_start();
+        let current_generation = self.create_first_generation()?;
+        let mut state = ModelState {
+            generation_number: 0,
+            without_improvement_generation_number: 0,
+            best_error: f64::INFINITY,
+            stop_requested,
+            started_at,
+            stats: self.create_stats(0, &current_generation, 0),
+            current_generation,
+            restart_number: 0,
+            hall_of_fame: vec![],
+            stop_reason: None,
+        };
+        self.complete_generation(&mut state);
+        Ok(self.state.insert(state))
+    }
+    /// Create the next generation and return the model state.
+    /// The step can be performed even if the model has stopped, in which case the stop reason is checked again.
+    /// Panic if the model has not been initialized.
+    pub fn step(&mut self) -> Result<&ModelState, FitnessError> {
+        let current_generation = match &self.state {
+            Some(state) => state.current_generation.clone(),
+            None => panic!("The model must be initialized before performing a step."),
+        };
+        let generation_number = self.state.as_ref().unwrap().generation_number + 1;
+        let mut next_generation =
+            self.create_next_generation(&current_generation, generation_number)?;
+        let is_next_generation_better =
+            self.is_next_generation_better(&current_generation, &next_generation);
+        let previous_state = self.state.as_ref().unwrap();
+        let mut without_improvement_generation_number = if is_next_generation_better {
+            0
+        } else {
+            previous_state.without_improvement_generation_number + 1
+        };
+        let mut restart_number = previous_state.restart_number;
+        let mut hall_of_fame = previous_state.hall_of_fame.clone();
+        if let Some(restart) = &self.restart {
+            if restart.must_restart(without_improvement_generation_number, restart_number) {
+                hall_of_fame = self.hall_of_fame.update(
+                    &hall_of_fame,
+                    &next_generation,
+                    self.settings.complexity_impact,
+                );
+                next_generation =
+                    self.create_restart_generation(&hall_of_fame, generation_number)?;
+                without_improvement_generation_number = 0;
+                restart_number += 1;
+                self.notify_restart(restart_number, &hall_of_fame);
+            }
+        }
+        if let Some(genealogy) = &mut self.genealogy {
+            genealogy.retain_ancestors(&[next_generation.as_slice(), &hall_of_fame].concat());
+        }
+        let mut state = self.state.take().unwrap();
+        state.generation_number = generation_number;
+        state.without_improvement_generation_number = without_improvement_generation_number;
+        state.restart_number = restart_number;
+        state.hall_of_fame = hall_of_fame;
+        state.stats = self.create_stats(
             generation_number,
+            &next_generation,
+            state.without_improvement_generation_number,
         );
-        while let None = stop_reason {
-            generation_number += 1;
-            let next_generation =
-                self.create_next_generation(&current_generation, generation_number)?;
-            if self.is_next_generation_better(&current_generation, &next_generation) {
-                without_improvement_generation_number = 0;
-            } else {
-                without_improvement_generation_number += 1;
-            };
-            current_generation = next_generation;
-            self.execute_callback(&current_generation);
-            error = current_generation[0].fitness.error;
-            stop_reason = self.stop_criterion.must_stop(
-                error,
-                without_improvement_generation_number,
-                generation_number,
-            );
+        state.stats.rejected_offspring_number = self.rejected_offspring_number;
+        state.stats.restart_number = restart_number;
+        state.current_generation = next_generation;
+        self.complete_generation(&mut state);
+        Ok(self.state.insert(state))
+    }
+    /// Return an iterator that initializes the model if necessary and yields the state after each generation.
+    /// The iterator ends after the generation for which a stop reason has been found.
+    pub fn generations(&mut self) -> Generations<'_, R> {
+        let finished = match &self.state {
+            Some(state) => state.stop_reason.is_some(),
+            None => false,
+        };
+        Generations {
+            model: self,
+            finished,
         }
-        Ok(ModelResult {
-            individual: (*current_generation[0]).clone(),
-            stop_reason: match stop_reason {
+    }
+    /// Create individuals from expression trees and add them to the current generation keeping its size.
+    /// Panic if the model has not been initialized.
+    pub fn inject(&mut self, expression_trees: Vec<ExpressionTree>) -> Result<(), FitnessError> {
+        let generation_number = match &self.state {
+            Some(state) => state.generation_number,
+            None => panic!("The model must be initialized before injecting individuals."),
+        };
+        let mut individuals = self.create_individuals(expression_trees, generation_number)?;
+        let state = self.state.as_mut().unwrap();
+        state.current_generation.append(&mut individuals);
+        sort_individuals(
+            &mut state.current_generation,
+            self.settings.complexity_impact,
+        );
+        state
+            .current_generation
+            .truncate(self.generation_size.generation_len as usize);
+        state.hall_of_fame = self.hall_of_fame.update(
+            &state.hall_of_fame,
+            &state.current_generation,
+            self.settings.complexity_impact,
+        );
+        let state = self.state.as_ref().unwrap();
+        let mut stats = self.create_stats(
+            generation_number,
+            &state.current_generation,
+            state.without_improvement_generation_number,
+        );
+        stats.mutation_probabilities = MutationProbability::from_settings(&self.settings);
+        self.state.as_mut().unwrap().stats = stats;
+        Ok(())
+    }
+    /// Return the model result and notify observers about the run finish.
+    /// Panic if the model has not been stopped.
+    pub fn finish(&mut self) -> ModelResult {
+        let state = match self.state.take() {
+            Some(state) => state,
+            None => panic!("The model must be initialized before finishing."),
+        };
+        let best_individual = state
+            .hall_of_fame
+            .first()
+            .unwrap_or(&state.current_generation[0]);
+        let result = ModelResult {
+            individual: (**best_individual).clone(),
+            hall_of_fame: state
+                .hall_of_fame
+                .iter()
+                .map(|individual| (**individual).clone())
+                .collect(),
+            stop_reason: match state.stop_reason {
                 Some(stop_reason) => stop_reason,
-                None => unreachable!(),
+                None => panic!("The model must be stopped before finishing."),
             },
-        })
+        };
+        for observer in self.observers.iter_mut() {
+            observer.on_run_finished(&result);
+        }
+        result
+    }
+    /// Compute the generation statistics, including the tree distance only if the diversity is maintained.
+    fn create_stats(
+        &self,
+        generation_number: u32,
+        individuals: &[Rc<Individual>],
+        without_improvement_generation_number: u32,
+    ) -> GenerationStats {
+        let mut stats = GenerationStats::new(
+            generation_number,
+            individuals,
+            without_improvement_generation_number,
+        );
+        if self.diversity.is_some() {
+            stats.diversity = Some(get_mean_tree_distance(individuals));
+        }
+        stats
+    }
+    /// Notify observers about the created generation and check the stop criterion.
+    fn complete_generation(&mut self, state: &mut ModelState) {
+        state.hall_of_fame = self.hall_of_fame.update(
+            &state.hall_of_fame,
+            &state.current_generation,
+            self.settings.complexity_impact,
+        );
+        state.stats.mutation_probabilities = MutationProbability::from_settings(&self.settings);
+        state.stop_requested |= self.notify_generation(
+            &state.current_generation,
+            &state.stats,
+            &mut state.best_error,
+        );
+        state.stop_reason = self.must_stop(state);
+    }
+    fn must_stop(&self, state: &ModelState) -> Option<StopReason> {
+        let error = state.current_generation[0].fitness.error;
+        if let Some(stop_reason) = self.stop_criterion.must_stop(
+            error,
+            state.without_improvement_generation_number,
+            state.generation_number,
+        ) {
+            return Some(stop_reason);
+        }
+        if let Some(stop_reason) = self
+            .stop_criterion
+            .must_stop_by_complexity(&state.current_generation)
+        {
+            return Some(stop_reason);
+        }
+        match self.stop_criterion.must_stop_by_budget(
+            error,
+            state.started_at.elapsed(),
+            self.evaluation_number,
+        ) {
+            Some(stop_reason) => Some(stop_reason),
+            None if state.stop_requested => Some(StopReason::ObserverRequest(StopData {
+                error,
+                generation_number: state.generation_number,
+            })),
+            None => None,
+        }
     }
     fn create_first_generation(&mut self) -> Result<Vec<Rc<Individual>>, FitnessError> {
         let initial_expression_trees = self.create_initial_expression_trees();
@@ -90,6 +427,35 @@ impl<R: Random> Model<R> {
         sort_individuals(&mut first_generation, self.settings.complexity_impact);
         Ok(first_generation)
     }
+    /// Create the generation of the hall of fame and random individuals after a restart.
+    /// The hall of fame takes at most half of the generation, so the restart always reinitializes the rest.
+    fn create_restart_generation(
+        &mut self,
+        hall_of_fame: &[Rc<Individual>],
+        generation_number: u32,
+    ) -> Result<Vec<Rc<Individual>>, FitnessError> {
+        let initialization = self.settings.initialization;
+        if let Some(restart_initialization) = self.restart.as_ref().unwrap().initialization {
+            self.settings.initialization = restart_initialization;
+        }
+        let generation_len = self.generation_size.generation_len as usize;
+        let carried_number = hall_of_fame.len().min(generation_len / 2);
+        let random_number = generation_len - carried_number;
+        let expression_trees = (0..random_number)
+            .map(|_| {
+                ExpressionTree::create_random(
+                    &mut self.random,
+                    &self.settings,
+                    &self.input_data.variables[0..self.input_data.variables.len() - 1],
+                )
+            })
+            .collect();
+        self.settings.initialization = initialization;
+        let mut generation = hall_of_fame[0..carried_number].to_vec();
+        generation.append(&mut self.create_individuals(expression_trees, generation_number)?);
+        sort_individuals(&mut generation, self.settings.complexity_impact);
+        Ok(generation)
+    }
     fn create_initial_expression_trees(&mut self) -> Vec<ExpressionTree> {
         if self.auxiliary_expression_trees.len() >= self.generation_size.generation_len as usize {
             self.auxiliary_expression_trees
@@ -115,23 +481,67 @@ impl<R: Random> Model<R> {
         current_generation: &[Rc<Individual>],
         generation_number: u32,
     ) -> Result<Vec<Rc<Individual>>, FitnessError> {
-        let mut individuals = current_generation
-            .iter()
-            .cloned()
-            .collect::<Vec<Rc<Individual>>>();
+        let generation_len = self.generation_size.generation_len as usize;
+        let elite_number = self.replacement.elite_number.min(current_generation.len());
         let individuals_to_cross = self.select_individuals_to_cross(current_generation);
-        let mut expression_trees = self.cross(&individuals_to_cross);
+        let offspring_number = match self.replacement.strategy {
+            ReplacementStrategy::MuCommaLambda => generation_len.saturating_sub(
+                elite_number
+                    + self.replacement.immigrant_number
+                    + self.auxiliary_expression_trees.len().min(1),
+            ),
+            _ => individuals_to_cross.len(),
+        };
+        let mut expression_trees = vec![];
+        let mut provenances = vec![];
+        let mut origins = vec![];
+        for offspring in self.cross(&individuals_to_cross, offspring_number) {
+            expression_trees.push(offspring.expression_tree);
+            provenances.push(offspring.provenance);
+            origins.push(offspring.origin);
+        }
         if self.auxiliary_expression_trees.len() > 0 {
             expression_trees.push(self.auxiliary_expression_trees.remove(0));
         }
-        expression_trees.push(ExpressionTree::create_random(
-            &mut self.random,
-            &self.settings,
-            &self.input_data.variables[0..self.input_data.variables.len() - 1],
-        ));
-        individuals.append(&mut self.create_individuals(expression_trees, generation_number)?);
+        for _ in 0..self.replacement.immigrant_number {
+            expression_trees.push(ExpressionTree::create_random(
+                &mut self.random,
+                &self.settings,
+                &self.input_data.variables[0..self.input_data.variables.len() - 1],
+            ));
+        }
+        let mut offspring = self.create_individuals_with_provenances(
+            expression_trees,
+            provenances,
+            generation_number,
+        )?;
+        self.adapt_mutation_probabilities(&offspring[0..origins.len()], &origins);
+        let survivor_number = match self.replacement.strategy {
+            ReplacementStrategy::MuPlusLambda => current_generation.len(),
+            // Offspring rejected by the bloat limits are replaced with the best non-elite individuals.
+            ReplacementStrategy::MuCommaLambda => generation_len
+                .saturating_sub(offspring.len())
+                .max(elite_number)
+                .min(current_generation.len()),
+            ReplacementStrategy::SteadyState => current_generation
+                .len()
+                .saturating_sub(offspring.len())
+                .max(elite_number),
+        };
+        let survivors = current_generation[0..survivor_number].to_vec();
+        if let Some(diversity) = &self.diversity {
+            return Ok(diversity.select(
+                survivors,
+                offspring,
+                generation_len,
+                self.settings.complexity_impact,
+                &self.input_data,
+            ));
+        }
+        let mut individuals = survivors;
+        individuals.append(&mut offspring);
         sort_individuals(&mut individuals, self.settings.complexity_impact);
-        individuals.drain(self.generation_size.generation_len as usize..);
+        individuals.truncate(generation_len);
         Ok(individuals)
     }
     fn select_individuals_to_cross<'a>(
@@ -154,41 +564,159 @@ impl<R: Random> Model<R> {
         }
         individuals_to_cross
     }
-    fn cross(&mut self, individuals: &[Rc<Individual>]) -> Vec<ExpressionTree> {
+    /// Create offspring applying the bloat limits policy to offspring exceeding the limits.
+    fn cross(&mut self, individuals: &[Rc<Individual>], number: usize) -> Vec<Offspring> {
+        self.rejected_offspring_number = 0;
         let mut expression_trees = vec![];
-        while expression_trees.len() != individuals.len() {
-            let parent1 = &individuals[self.random.gen_range(0..individuals.len())];
-            let mut parent2 = &individuals[self.random.gen_range(0..individuals.len())];
-            while parent2 == parent1 {
-                parent2 = &individuals[self.random.gen_range(0..individuals.len())];
+        let mut offspring_number = 0;
+        while offspring_number < number {
+            let mut attempt_number = 0;
+            loop {
+                let parent1 = &individuals[self.random.gen_range(0..individuals.len())];
+                let mut parent2 = &individuals[self.random.gen_range(0..individuals.len())];
+                while parent2 == parent1 {
+                    parent2 = &individuals[self.random.gen_range(0..individuals.len())];
+                }
+                let mut children = self.settings.crossover.cross(
+                    &parent1.expression_tree,
+                    &parent2.expression_tree,
+                    &mut self.random,
+                    &self.settings,
+                );
+                let mut mutation_indices = vec![];
+                for child in children.iter_mut() {
+                    mutation_indices.push(
+                        self.settings
+                            .mutate(&mut child.expression_tree, &mut self.random),
+                    );
+                    child.expression_tree.simplify();
+                }
+                let rejected_number = children
+                    .iter()
+                    .filter(|child| {
+                        !self
+                            .settings
+                            .bloat_limits
+                            .is_satisfied(&child.expression_tree)
+                    })
+                    .count();
+                self.rejected_offspring_number += rejected_number;
+                if let BloatPolicy::Retry {
+                    attempt_number: max_attempt_number,
+                } = self.settings.bloat_limits.policy
+                {
+                    if rejected_number > 0 && attempt_number < max_attempt_number {
+                        attempt_number += 1;
+                        continue;
+                    }
+                }
+                offspring_number += children.len();
+                for ((child, (parent, other_parent)), mutation_index) in children
+                    .into_iter()
+                    .zip([(parent1, parent2), (parent2, parent1)])
+                    .zip(mutation_indices)
+                {
+                    if self
+                        .settings
+                        .bloat_limits
+                        .is_satisfied(&child.expression_tree)
+                    {
+                        expression_trees.push(Offspring {
+                            expression_tree: child.expression_tree,
+                            provenance: Provenance {
+                                parent_ids: vec![parent.id, other_parent.id],
+                                crossover_point: child.crossover_point,
+                                mutations: mutation_index
+                                    .map(|index| self.settings.mutations[index].name.to_owned())
+                                    .into_iter()
+                                    .collect(),
+                            },
+                            origin: OffspringOrigin {
+                                parent: Rc::clone(parent),
+                                mutation_index,
+                            },
+                        });
+                    } else if let BloatPolicy::ReplaceWithParent | BloatPolicy::Retry { .. } =
+                        self.settings.bloat_limits.policy
+                    {
+                        expression_trees.push(Offspring {
+                            expression_tree: parent.expression_tree.clone(),
+                            provenance: Provenance {
+                                parent_ids: vec![parent.id],
+                                crossover_point: None,
+                                mutations: vec![],
+                            },
+                            origin: OffspringOrigin {
+                                parent: Rc::clone(parent),
+                                mutation_index: None,
+                            },
+                        });
+                    }
+                }
+                break;
             }
-            let mut expression_tree = crossing::cross(
-                &parent1.expression_tree,
-                &parent2.expression_tree,
-                &mut self.random,
-            );
-            self.settings.mutate(&mut expression_tree, &mut self.random);
-            expression_tree.simplify();
-            expression_trees.push(expression_tree);
         }
+        expression_trees.truncate(number);
         expression_trees
     }
+    /// Update the adaptive mutation state with the offspring created by crossing and set new mutation probabilities.
+    fn adapt_mutation_probabilities(
+        &mut self,
+        offspring: &[Rc<Individual>],
+        origins: &[OffspringOrigin],
+    ) {
+        let (adaptive_mutation, adaptive_mutation_state) =
+            match (&self.adaptive_mutation, &mut self.adaptive_mutation_state) {
+                (Some(adaptive_mutation), Some(adaptive_mutation_state)) => {
+                    (adaptive_mutation, adaptive_mutation_state)
+                }
+                _ => return,
+            };
+        let mut outcomes = MutationOutcomes::new(self.settings.mutations.len());
+        for (individual, origin) in offspring.iter().zip(origins) {
+            if let Some(mutation_index) = origin.mutation_index {
+                outcomes.applied_numbers[mutation_index] += 1;
+                if individual.fitness.error < origin.parent.fitness.error {
+                    outcomes.improved_numbers[mutation_index] += 1;
+                }
+            }
+        }
+        adaptive_mutation.update(adaptive_mutation_state, &outcomes);
+        adaptive_mutation.apply(adaptive_mutation_state, &mut self.settings);
+    }
     fn create_individuals(
         &mut self,
         expression_trees: Vec<ExpressionTree>,
         generation_number: u32,
+    ) -> Result<Vec<Rc<Individual>>, FitnessError> {
+        self.create_individuals_with_provenances(expression_trees, vec![], generation_number)
+    }
+    /// Create individuals from expression trees.
+    /// Individuals without the corresponding provenance get the default one.
+    fn create_individuals_with_provenances(
+        &mut self,
+        expression_trees: Vec<ExpressionTree>,
+        provenances: Vec<Provenance>,
+        generation_number: u32,
     ) -> Result<Vec<Rc<Individual>>, FitnessError> {
         let mut individuals = vec![];
+        let mut provenances = provenances.into_iter();
         for expression_tree in expression_trees {
             let fitness = expression_tree.get_fitness(&self.settings, &self.input_data)?;
+            self.evaluation_number += 1;
             let defective = fitness.error.is_nan();
-            individuals.push(Rc::new(Individual {
+            let individual = Rc::new(Individual {
                 id: self.id_generator.next().unwrap(),
                 generation_number,
                 expression_tree,
                 fitness,
                 defective,
-            }));
+                provenance: provenances.next().unwrap_or_default(),
+            });
+            if let Some(genealogy) = &mut self.genealogy {
+                genealogy.add(&individual);
+            }
+            individuals.push(individual);
         }
         Ok(individuals)
     }
@@ -216,15 +744,117 @@ impl<R: Random> Model<R> {
             true
         }
     }
-    fn execute_callback(&mut self, individuals: &[Rc<Individual>]) {
-        if let Some(callback) = &mut self.callback {
-            (callback)(individuals);
+    /// Notify observers about the run start and return true if any of them requested a stop.
+    fn notify_run_start(&mut self) -> bool {
+        let mut stop_requested = false;
+        for observer in self.observers.iter_mut() {
+            stop_requested |= observer.on_run_start() == ObserverAction::Stop;
+        }
+        stop_requested
+    }
+    /// Notify observers about the population restart.
+    fn notify_restart(&mut self, restart_number: u32, hall_of_fame: &[Rc<Individual>]) {
+        for observer in self.observers.iter_mut() {
+            observer.on_restart(restart_number, hall_of_fame);
+        }
+    }
+    /// Notify observers about the evaluated generation and return true if any of them requested a stop.
+    fn notify_generation(
+        &mut self,
+        individuals: &[Rc<Individual>],
+        stats: &GenerationStats,
+        best_error: &mut f64,
+    ) -> bool {
+        let new_best = individuals
+            .iter()
+            .filter(|individual| !individual.defective && individual.fitness.error < *best_error)
+            .min_by(|i1, i2| i1.fitness.error.partial_cmp(&i2.fitness.error).unwrap());
+        if let Some(individual) = new_best {
+            *best_error = individual.fitness.error;
+        }
+        let mut stop_requested = false;
+        for observer in self.observers.iter_mut() {
+            stop_requested |=
+                observer.on_generation_evaluated(individuals, stats) == ObserverAction::Stop;
+            if let Some(individual) = new_best {
+                stop_requested |= observer.on_new_best(individual) == ObserverAction::Stop;
+            }
+            if stats.without_improvement_generation_number > 0 {
+                stop_requested |= observer.on_stagnation(stats) == ObserverAction::Stop;
+            }
+        }
+        stop_requested
+    }
+}
+
+/// The model state between generations.
+#[derive(Debug, Clone)]
+pub struct ModelState {
+    pub generation_number: u32,
+    pub without_improvement_generation_number: u32,
+    /// The lowest error ever found.
+    pub best_error: f64,
+    /// True if one of the observers has requested a stop.
+    pub stop_requested: bool,
+    /// The time when the model was initialized.
+    pub started_at: Instant,
+    pub current_generation: Vec<Rc<Individual>>,
+    /// The number of population restarts performed.
+    pub restart_number: u32,
+    /// The best distinct individuals seen during the run ordered from the best.
+    pub hall_of_fame: Vec<Rc<Individual>>,
+    pub stats: GenerationStats,
+    pub stop_reason: Option<StopReason>,
+}
+
+/// Expression tree created by crossing before its evaluation.
+#[derive(Debug, Clone, PartialEq)]
+struct Offspring {
+    expression_tree: ExpressionTree,
+    provenance: Provenance,
+    origin: OffspringOrigin,
+}
+
+/// The parent of an offspring and the mutation applied to it.
+#[derive(Debug, Clone, PartialEq)]
+struct OffspringOrigin {
+    parent: Rc<Individual>,
+    mutation_index: Option<usize>,
+}
+
+/// Iterator over model generations.
+pub struct Generations<'a, R: Random> {
+    model: &'a mut Model<R>,
+    finished: bool,
+}
+
+impl<'a, R: Random> Iterator for Generations<'a, R> {
+    type Item = Result<ModelState, FitnessError>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        let result = match self.model.state {
+            Some(_) => self.model.step(),
+            None => self.model.initialize(),
+        };
+        match result {
+            Ok(state) => {
+                self.finished = state.stop_reason.is_some();
+                Some(Ok(state.clone()))
+            }
+            Err(err) => {
+                self.finished = true;
+                Some(Err(err))
+            }
         }
     }
 }
 
 pub struct ModelResult {
     pub individual: Individual,
+    /// The best distinct individuals seen during the run ordered from the best.
+    pub hall_of_fame: Vec<Individual>,
     pub stop_reason: StopReason,
 }
 
@@ -235,6 +865,7 @@ pub struct Individual {
     pub expression_tree: ExpressionTree,
     pub fitness: Fitness,
     pub defective: bool,
+    pub provenance: Provenance,
 }
 
 impl PartialEq for Individual {
@@ -245,35 +876,83 @@ impl PartialEq for Individual {
 
 #[cfg(test)]
 mod tests {
-    use super::super::stop_criterion::StopData;
+    use super::super::super::crossing::CrossoverMethod;
+    use super::super::super::settings::BloatLimits;
+    use super::super::diversity::DistanceMetric;
+    use super::super::observer::CallbackObserver;
+    use super::super::stop_criterion::EvaluationStopData;
     use super::*;
     use crate::expression_tree::random::MockRandom;
     use crate::expression_tree::{Node, OperationNode, ValueNode};
     use calamine::{DataType, Range, Reader, Xlsx};
     use std::cell::RefCell;
     use std::cmp::Ordering;
+    use std::collections::HashSet;
     use std::f64::NAN;
     use std::path::PathBuf;
+    use std::time::Duration;
 
     #[test]
-    #[should_panic(expected = "Panic.")]
-    fn test_execute_callback() {
+    fn test_notify_generation() {
+        let events = Rc::new(RefCell::new(vec![]));
         let individuals = create_test_individuals();
+        let stats = GenerationStats::new(1, &individuals, 1);
         let mut model = create_model::<MockRandom>(
             10,
             0,
             None,
-            Some(Box::new(|actual_individuals| {
-                assert_eq!(create_test_individuals(), actual_individuals);
-                panic!("Panic.")
-            })),
+            vec![Box::new(TestObserver {
+                events: Rc::clone(&events),
+                stop_generation_number: None,
+            })],
+        );
+        let mut best_error = f64::INFINITY;
+        assert!(!model.notify_generation(&individuals, &stats, &mut best_error));
+        assert_eq!(0.001, best_error);
+        assert!(!model.notify_generation(&individuals, &stats, &mut best_error));
+        assert_eq!(
+            vec![
+                "generation 1",
+                "best 3",
+                "stagnation",
+                "generation 1",
+                "stagnation"
+            ],
+            *events.borrow()
         );
-        model.execute_callback(&individuals);
+    }
+
+    #[test]
+    fn test_run_observer_stop() -> Result<(), FitnessError> {
+        let events = Rc::new(RefCell::new(vec![]));
+        let mut model = create_model::<MockRandom>(
+            2,
+            0,
+            None,
+            vec![Box::new(TestObserver {
+                events: Rc::clone(&events),
+                stop_generation_number: Some(0),
+            })],
+        );
+        let result = model.run()?;
+        assert_eq!(1, result.individual.id);
+        assert_eq!(
+            StopReason::ObserverRequest(StopData {
+                error: result.individual.fitness.error,
+                generation_number: 0,
+            }),
+            result.stop_reason
+        );
+        assert_eq!(
+            vec!["start", "generation 0", "best 1", "finished"],
+            *events.borrow()
+        );
+        Ok(())
     }
 
     #[test]
     fn test_is_next_generation_better() {
-        let model = create_model::<MockRandom>(10, 0, None, None);
+        let model = create_model::<MockRandom>(10, 0, None, vec![]);
         let previous_generation = create_test_individuals();
         let mut next_generation = create_test_individuals();
         assert!(!model.is_next_generation_better(&previous_generation, &next_generation));
@@ -290,7 +969,7 @@ mod tests {
 
     #[test]
     fn test_create_individuals() -> Result<(), FitnessError> {
-        let mut model = create_model::<MockRandom>(10, 0, None, None);
+        let mut model = create_model::<MockRandom>(10, 0, None, vec![]);
         let mut expression_trees = create_auxiliary_expression_trees(&model.settings);
         expression_trees.push(create_defective_expression_tree(&model.settings));
         let mut expected_individuals = vec![];
@@ -326,6 +1005,7 @@ mod tests {
                 expression_tree: expression_trees[i].clone(),
                 fitness: t.0.clone(),
                 defective: t.1,
+                provenance: Provenance::default(),
             }));
         }
         let actual_individuals = model.create_individuals(expression_trees, 0)?;
@@ -359,16 +1039,60 @@ mod tests {
 
     #[test]
     fn test_cross() {
-        let mut model = create_model(10, 0, Some(create_auxiliary_individuals_random()), None);
+        let mut model = create_model(10, 0, Some(create_auxiliary_individuals_random()), vec![]);
         let individuals = create_auxiliary_individuals(&model.settings, &model.input_data, 0);
         let expected_expression_trees = create_auxiliary_individuals_descendants(&model.settings);
-        let actual_expression_trees = model.cross(&individuals);
+        let actual_expression_trees = model
+            .cross(&individuals, individuals.len())
+            .into_iter()
+            .map(|offspring| offspring.expression_tree)
+            .collect::<Vec<ExpressionTree>>();
         assert_eq!(expected_expression_trees, actual_expression_trees);
     }
 
+    #[test]
+    fn test_cross_bloat_limits() {
+        let mut model = create_model(10, 0, Some(create_auxiliary_individuals_random()), vec![]);
+        model.settings.bloat_limits = BloatLimits {
+            max_depth: None,
+            max_node_number: Some(0),
+            policy: BloatPolicy::ReplaceWithParent,
+        };
+        let individuals = create_auxiliary_individuals(&model.settings, &model.input_data, 0);
+        let expression_trees = model.cross(&individuals, 3);
+        assert_eq!(3, expression_trees.len());
+        assert_eq!(3, model.rejected_offspring_number);
+        for offspring in &expression_trees {
+            assert!(individuals
+                .iter()
+                .any(|individual| individual.expression_tree == offspring.expression_tree));
+            assert_eq!(None, offspring.origin.mutation_index);
+        }
+        model.settings.bloat_limits.policy = BloatPolicy::Retry { attempt_number: 2 };
+        let expression_trees = model.cross(&individuals, 3);
+        assert_eq!(3, expression_trees.len());
+        assert_eq!(9, model.rejected_offspring_number);
+        for offspring in &expression_trees {
+            assert_eq!(1, offspring.provenance.parent_ids.len());
+            assert_eq!(None, offspring.origin.mutation_index);
+        }
+        model.settings.bloat_limits.policy = BloatPolicy::Reject;
+        assert_eq!(0, model.cross(&individuals, 3).len());
+        assert_eq!(3, model.rejected_offspring_number);
+    }
+
+    #[test]
+    fn test_cross_two_children() {
+        let mut model = create_seeded_model();
+        model.settings.crossover = CrossoverMethod::TwoChildSubtree.create_crossover();
+        let individuals = create_auxiliary_individuals(&model.settings, &model.input_data, 0);
+        assert_eq!(3, model.cross(&individuals, 3).len());
+        assert_eq!(4, model.cross(&individuals, 4).len());
+    }
+
     #[test]
     fn test_select_individuals_to_cross() {
-        let mut model = create_model(10, 0, Some(MockRandom::new_int(vec![3])), None);
+        let mut model = create_model(10, 0, Some(MockRandom::new_int(vec![3])), vec![]);
         let mut individuals = create_auxiliary_individuals(&model.settings, &model.input_data, 0);
         individuals.append(&mut create_auxiliary_individuals(
             &model.settings,
@@ -391,7 +1115,7 @@ mod tests {
             vec![100.0],
             vec![0.95],
         );
-        let mut model = create_model(10, 6, Some(random), None);
+        let mut model = create_model(10, 6, Some(random), vec![]);
         let mut current_generation = vec![];
         for i in 0..3 {
             current_generation.append(&mut create_auxiliary_individuals(
@@ -411,9 +1135,43 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_create_next_generation_mu_comma_lambda() -> Result<(), FitnessError> {
+        let mut model = create_replacement_model(ReplacementStrategy::MuCommaLambda, 1);
+        let current_generation = create_replacement_generation(&model);
+        let next_generation = model.create_next_generation(&current_generation, 1)?;
+        assert_eq!(10, next_generation.len());
+        let survivors = next_generation
+            .iter()
+            .filter(|individual| individual.id < 10)
+            .collect::<Vec<&Rc<Individual>>>();
+        assert_eq!(vec![&current_generation[0]], survivors);
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_next_generation_steady_state() -> Result<(), FitnessError> {
+        let mut model = create_replacement_model(ReplacementStrategy::SteadyState, 0);
+        model.replacement.immigrant_number = 2;
+        let current_generation = create_replacement_generation(&model);
+        let next_generation = model.create_next_generation(&current_generation, 1)?;
+        assert_eq!(10, next_generation.len());
+        assert_eq!(
+            6,
+            next_generation
+                .iter()
+                .filter(|individual| individual.id >= 10)
+                .count()
+        );
+        for individual in &current_generation[4..] {
+            assert!(!next_generation.contains(individual));
+        }
+        Ok(())
+    }
+
     #[test]
     fn test_create_initial_expression_trees_not_random() {
-        let mut model = create_model::<MockRandom>(2, 0, None, None);
+        let mut model = create_model::<MockRandom>(2, 0, None, vec![]);
         let expected_expression_trees = create_auxiliary_expression_trees(&model.settings);
         let actual_expression_trees = model.create_initial_expression_trees();
         assert_eq!(expected_expression_trees, actual_expression_trees);
@@ -429,7 +1187,7 @@ mod tests {
                 vec![10.0],
                 vec![0.1, 0.9, 0.4, 0.9, 0.6],
             )),
-            None,
+            vec![],
         );
         let mut expected_expression_trees = create_auxiliary_expression_trees(&model.settings);
         expected_expression_trees.push(ExpressionTree {
@@ -448,7 +1206,7 @@ mod tests {
 
     #[test]
     fn test_create_first_generation() -> Result<(), FitnessError> {
-        let mut model = create_model::<MockRandom>(2, 0, None, None);
+        let mut model = create_model::<MockRandom>(2, 0, None, vec![]);
         let expected_first_generation =
             create_auxiliary_individuals(&model.settings, &model.input_data, 0)
                 .into_iter()
@@ -463,25 +1221,13 @@ mod tests {
     fn test_run() -> Result<(), FitnessError> {
         let call_count = Rc::new(RefCell::new(0));
         let call_count_copy = Rc::clone(&call_count);
-        let mut model = create_model(
-            10,
-            6,
-            Some(MockRandom::new(
-                vec![2, 0, 2, 0, 1, 0, 2, 0, 1, 0, 0, 0, 2, 0, 1],
-                vec![100.0],
-                vec![0.95, 0.85],
-            )),
-            Some(Box::new(move |individuals| {
+        let mut model = create_model_to_run(vec![Box::new(CallbackObserver::new(
+            move |individuals, stats| {
                 assert_eq!(10, individuals.len());
+                assert_eq!(*call_count_copy.borrow(), stats.generation_number);
                 *call_count_copy.borrow_mut() += 1;
-            })),
-        );
-        model.auxiliary_expression_trees = vec![];
-        for _ in 0..5 {
-            model
-                .auxiliary_expression_trees
-                .append(&mut create_auxiliary_expression_trees(&model.settings));
-        }
+            },
+        ))]);
         let expected_expression_tree =
             &create_auxiliary_individuals_descendants(&model.settings)[0];
         let result = model.run()?;
@@ -494,6 +1240,314 @@ mod tests {
                 result.stop_reason
             ),
         }
+        assert!(model.state.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_run_budget_stop() -> Result<(), FitnessError> {
+        let mut model = create_model_to_run(vec![]);
+        model.stop_criterion.error = None;
+        model.stop_criterion.evaluation_number = Some(10);
+        let state = model.initialize()?;
+        assert_eq!(
+            Some(StopReason::EvaluationNumber(EvaluationStopData {
+                error: state.current_generation[0].fitness.error,
+                evaluation_number: 10,
+            })),
+            state.stop_reason
+        );
+        model.stop_criterion.evaluation_number = None;
+        model.stop_criterion.duration = Some(Duration::ZERO);
+        let state = model.step()?;
+        assert!(matches!(state.stop_reason, Some(StopReason::Duration(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_adaptive_mutation() -> Result<(), FitnessError> {
+        let adaptive_mutation = AdaptiveMutation {
+            min_probability: 0.02,
+            max_probability: 0.4,
+            adaptation_rate: 0.5,
+        };
+        let mut model = create_seeded_model();
+        model.adaptive_mutation = Some(adaptive_mutation.clone());
+        model.initialize()?;
+        for _ in 0..3 {
+            model.step()?;
+        }
+        let state = model.state.as_ref().unwrap();
+        assert_eq!(
+            model.settings.mutations.len(),
+            state.stats.mutation_probabilities.len()
+        );
+        let default_settings = Settings::default();
+        for (mutation_probability, mutation) in state
+            .stats
+            .mutation_probabilities
+            .iter()
+            .zip(&default_settings.mutations)
+        {
+            if mutation.probability == 0.0 {
+                assert_eq!(0.0, mutation_probability.probability);
+            } else {
+                assert!(mutation_probability.probability >= adaptive_mutation.min_probability);
+                assert!(mutation_probability.probability <= adaptive_mutation.max_probability);
+            }
+        }
+        let probability_sum = state
+            .stats
+            .mutation_probabilities
+            .iter()
+            .map(|mutation_probability| mutation_probability.probability)
+            .sum::<f64>();
+        assert!((0.9 - probability_sum).abs() < 1e-9);
+        let checkpoint = model.create_checkpoint();
+        let mut resumed_model = create_seeded_model();
+        resumed_model.adaptive_mutation = Some(adaptive_mutation);
+        let resumed_state = resumed_model.resume(&checkpoint).unwrap();
+        assert_eq!(
+            state.stats.mutation_probabilities,
+            resumed_state.stats.mutation_probabilities
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_step_diversity() -> Result<(), FitnessError> {
+        let mut model = create_seeded_model();
+        model.diversity = Some(Diversity::Crowding {
+            metric: DistanceMetric::Tree,
+        });
+        let generation_len = model.generation_size.generation_len as usize;
+        let best_error = model.initialize()?.current_generation[0].fitness.error;
+        let state = model.step()?;
+        assert_eq!(generation_len, state.current_generation.len());
+        assert!(state.current_generation[0].fitness.error <= best_error);
+        assert_eq!(
+            Some(get_mean_tree_distance(&state.current_generation)),
+            state.stats.diversity
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_step_restart() -> Result<(), FitnessError> {
+        let mut model = create_seeded_model();
+        model.hall_of_fame.len = 2;
+        model.restart = Some(Restart {
+            max_restart_number: Some(1),
+            ..Restart::new(0)
+        });
+        let generation_len = model.generation_size.generation_len as usize;
+        model.initialize()?;
+        let state = model.step()?;
+        assert_eq!(1, state.restart_number);
+        assert_eq!(1, state.stats.restart_number);
+        assert_eq!(0, state.without_improvement_generation_number);
+        assert_eq!(2, state.hall_of_fame.len());
+        assert_eq!(generation_len, state.current_generation.len());
+        for individual in &state.hall_of_fame {
+            assert!(state.current_generation.contains(individual));
+        }
+        let best_error = state.hall_of_fame[0].fitness.error;
+        let state = model.step()?;
+        assert_eq!(1, state.restart_number);
+        model.state.as_mut().unwrap().stop_reason = Some(StopReason::ObserverRequest(StopData {
+            error: 0.0,
+            generation_number: 2,
+        }));
+        assert!(model.finish().individual.fitness.error <= best_error);
+        Ok(())
+    }
+
+    #[test]
+    fn test_step_restart_large_hall_of_fame() -> Result<(), FitnessError> {
+        let mut model = create_seeded_model();
+        let generation_len = model.generation_size.generation_len as usize;
+        model.hall_of_fame = HallOfFame {
+            len: generation_len + 1,
+            per_complexity: false,
+        };
+        model.restart = Some(Restart {
+            max_restart_number: Some(1),
+            ..Restart::new(0)
+        });
+        model.initialize()?;
+        let state = model.step()?;
+        assert_eq!(1, state.restart_number);
+        assert_eq!(generation_len, state.current_generation.len());
+        // Random individuals are created last, so they get the latest identifiers.
+        let random_number = generation_len - generation_len / 2;
+        let first_random_id = model.id_generator.id - random_number as u32;
+        let state = model.state.as_ref().unwrap();
+        assert_eq!(
+            random_number,
+            state
+                .current_generation
+                .iter()
+                .filter(|individual| individual.id >= first_random_id)
+                .count()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_hall_of_fame() -> Result<(), FitnessError> {
+        let mut model = create_seeded_model();
+        model.hall_of_fame = HallOfFame {
+            len: 5,
+            per_complexity: true,
+        };
+        model.initialize()?;
+        let mut best_error = f64::INFINITY;
+        for _ in 0..5 {
+            let state = model.step()?;
+            best_error = best_error.min(state.current_generation[0].fitness.error);
+        }
+        let state = model.state.as_mut().unwrap();
+        assert!(!state.hall_of_fame.is_empty() && state.hall_of_fame.len() <= 5);
+        let complexities = state
+            .hall_of_fame
+            .iter()
+            .map(|individual| individual.fitness.complexity)
+            .collect::<HashSet<u32>>();
+        assert_eq!(state.hall_of_fame.len(), complexities.len());
+        state.stop_reason = Some(StopReason::ObserverRequest(StopData {
+            error: best_error,
+            generation_number: 5,
+        }));
+        let result = model.finish();
+        assert_eq!(complexities.len(), result.hall_of_fame.len());
+        assert!(result
+            .hall_of_fame
+            .iter()
+            .any(|individual| individual.fitness.error <= best_error));
+        Ok(())
+    }
+
+    #[test]
+    fn test_genealogy() -> Result<(), FitnessError> {
+        let mut model = create_seeded_model();
+        model.genealogy = Some(Genealogy::default());
+        model.initialize()?;
+        for _ in 0..3 {
+            model.step()?;
+        }
+        let state = model.state.as_ref().unwrap();
+        let genealogy = model.genealogy.as_ref().unwrap();
+        let crossed_individual = state
+            .current_generation
+            .iter()
+            .find(|individual| !individual.provenance.parent_ids.is_empty())
+            .unwrap();
+        assert_eq!(2, crossed_individual.provenance.parent_ids.len());
+        assert!(crossed_individual.provenance.crossover_point.is_some());
+        let best_individual = &state.current_generation[0];
+        let ancestry = genealogy.get_ancestry(best_individual.id);
+        assert!(ancestry.contains(best_individual));
+        for individual in &ancestry {
+            for parent_id in &individual.provenance.parent_ids {
+                assert!(ancestry.iter().any(|ancestor| ancestor.id == *parent_id));
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_checkpoint_and_resume() -> Result<(), FitnessError> {
+        let mut model = create_seeded_model();
+        model.initialize()?;
+        model.step()?;
+        let checkpoint = serde_json::from_str::<Checkpoint>(
+            &serde_json::to_string(&model.create_checkpoint()).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(1, checkpoint.generation_number);
+        let mut resumed_model = create_seeded_model();
+        let resumed_state = resumed_model.resume(&checkpoint).unwrap();
+        assert_eq!(1, resumed_state.generation_number);
+        assert_eq!(model.evaluation_number, resumed_model.evaluation_number);
+        assert_eq!(
+            model.auxiliary_expression_trees,
+            resumed_model.auxiliary_expression_trees
+        );
+        for _ in 0..3 {
+            let state = model.step()?.clone();
+            let resumed_state = resumed_model.step()?;
+            assert_eq!(state.generation_number, resumed_state.generation_number);
+            assert_eq!(state.stats, resumed_state.stats);
+            for (individual, resumed_individual) in state
+                .current_generation
+                .iter()
+                .zip(resumed_state.current_generation.iter())
+            {
+                assert_eq!(individual.id, resumed_individual.id);
+                assert_eq!(
+                    individual.expression_tree,
+                    resumed_individual.expression_tree
+                );
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_initialize_and_step() -> Result<(), FitnessError> {
+        let mut model = create_model_to_run(vec![]);
+        let state = model.initialize()?;
+        assert_eq!(0, state.generation_number);
+        assert_eq!(10, state.current_generation.len());
+        assert_eq!(None, state.stop_reason);
+        let state = model.step()?;
+        assert_eq!(1, state.generation_number);
+        assert_eq!(1, state.stats.generation_number);
+        assert_eq!(None, state.stats.diversity);
+        assert!(matches!(state.stop_reason, Some(StopReason::Error(_))));
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic(expected = "The model must be initialized before performing a step.")]
+    fn test_step_not_initialized() {
+        let mut model = create_model::<MockRandom>(10, 0, None, vec![]);
+        model.step().unwrap();
+    }
+
+    #[test]
+    fn test_generations() -> Result<(), FitnessError> {
+        let mut model = create_model_to_run(vec![]);
+        let states = model
+            .generations()
+            .collect::<Result<Vec<ModelState>, FitnessError>>()?;
+        assert_eq!(
+            vec![0, 1],
+            states
+                .iter()
+                .map(|state| state.generation_number)
+                .collect::<Vec<u32>>()
+        );
+        assert_eq!(None, states[0].stop_reason);
+        assert!(matches!(states[1].stop_reason, Some(StopReason::Error(_))));
+        assert_eq!(0, model.generations().count());
+        Ok(())
+    }
+
+    #[test]
+    fn test_inject() -> Result<(), FitnessError> {
+        let mut model = create_model::<MockRandom>(2, 0, None, vec![]);
+        model.initialize()?;
+        let expression_tree = create_auxiliary_individuals_descendants(&model.settings)[0].clone();
+        model.inject(vec![expression_tree.clone()])?;
+        let state = model.state.as_ref().unwrap();
+        assert_eq!(2, state.current_generation.len());
+        assert_eq!(expression_tree, state.current_generation[0].expression_tree);
+        assert_eq!(2, state.current_generation[0].id);
+        assert_eq!(
+            state.current_generation[0].fitness.error,
+            state.stats.best_error
+        );
         Ok(())
     }
 
@@ -501,7 +1555,7 @@ mod tests {
     fn test_new() {
         let settings = Settings::default();
         let auxiliary_expression_trees = create_auxiliary_expression_trees(&settings);
-        let expected_model = create_model(10, 0, Some(DefaultRandom::default()), None);
+        let expected_model = create_model(10, 0, Some(DefaultRandom::default()), vec![]);
         let actual_model = Model::new(
             settings,
             InputData::from_worksheet_range(get_worksheet("resources/input_data_sin.xlsx"))
@@ -513,7 +1567,7 @@ mod tests {
                 unadapted_percent: 0.1,
             },
             auxiliary_expression_trees,
-            None,
+            vec![],
         );
         assert_eq!(expected_model.input_data, actual_model.input_data);
         assert_eq!(expected_model.stop_criterion, actual_model.stop_criterion);
@@ -524,11 +1578,32 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_create_next_generation_mu_comma_lambda_rejected_offspring() -> Result<(), FitnessError>
+    {
+        for policy in [
+            BloatPolicy::Reject,
+            BloatPolicy::Retry { attempt_number: 2 },
+        ] {
+            let mut model = create_replacement_model(ReplacementStrategy::MuCommaLambda, 1);
+            model.settings.bloat_limits = BloatLimits {
+                max_depth: None,
+                max_node_number: Some(0),
+                policy,
+            };
+            let current_generation = create_replacement_generation(&model);
+            let next_generation = model.create_next_generation(&current_generation, 1)?;
+            assert_eq!(10, next_generation.len());
+            assert_eq!(3, model.select_individuals_to_cross(&next_generation).len());
+        }
+        Ok(())
+    }
+
     fn create_model<R: Random + Default>(
         generation_len: u32,
         id: u32,
         random: Option<R>,
-        callback: Option<Box<dyn FnMut(&[Rc<Individual>])>>,
+        observers: Vec<Box<dyn Observer>>,
     ) -> Model<R> {
         let settings = Settings::default();
         let auxiliary_expression_trees = create_auxiliary_expression_trees(&settings);
@@ -545,16 +1620,92 @@ mod tests {
                 unadapted_percent: 0.1,
             },
             auxiliary_expression_trees,
-            callback,
+            replacement: Replacement::default(),
+            adaptive_mutation: None,
+            adaptive_mutation_state: None,
+            diversity: None,
+            genealogy: None,
+            hall_of_fame: HallOfFame::default(),
+            restart: None,
+            observers,
             random: if let Some(random) = random {
                 random
             } else {
                 R::default()
             },
-            id_generator: Box::new(IdGenerator { id }),
+            id_generator: IdGenerator { id },
+            evaluation_number: 0,
+            rejected_offspring_number: 0,
+            state: None,
         }
     }
 
+    fn create_model_to_run(observers: Vec<Box<dyn Observer>>) -> Model<MockRandom> {
+        let mut model = create_model(
+            10,
+            6,
+            Some(MockRandom::new(
+                vec![2, 0, 2, 0, 1, 0, 2, 0, 1, 0, 0, 0, 2, 0, 1],
+                vec![100.0],
+                vec![0.95, 0.85],
+            )),
+            observers,
+        );
+        model.auxiliary_expression_trees = vec![];
+        for _ in 0..5 {
+            model
+                .auxiliary_expression_trees
+                .append(&mut create_auxiliary_expression_trees(&model.settings));
+        }
+        model
+    }
+
+    fn create_replacement_model(
+        strategy: ReplacementStrategy,
+        elite_number: usize,
+    ) -> Model<MockRandom> {
+        let random = MockRandom::new(
+            vec![1, 1, 0, 0, 1, 1, 0, 0, 1, 0, 0],
+            vec![100.0],
+            vec![0.95],
+        );
+        let mut model = create_model(10, 10, Some(random), vec![]);
+        model.replacement = Replacement {
+            strategy,
+            elite_number,
+            immigrant_number: 1,
+        };
+        model
+    }
+
+    fn create_replacement_generation(model: &Model<MockRandom>) -> Vec<Rc<Individual>> {
+        let mut generation = vec![];
+        for i in 0..5 {
+            generation.append(&mut create_auxiliary_individuals(
+                &model.settings,
+                &model.input_data,
+                i * 2,
+            ));
+        }
+        sort_individuals(&mut generation, model.settings.complexity_impact);
+        generation
+    }
+
+    fn create_seeded_model() -> Model<SeededRandom> {
+        let model = create_model::<MockRandom>(10, 0, None, vec![]);
+        let mut seeded_model = Model::with_random(
+            model.settings,
+            model.input_data,
+            model.stop_criterion,
+            model.generation_size,
+            model.auxiliary_expression_trees,
+            vec![],
+            SeededRandom::seeded(7),
+        );
+        seeded_model.stop_criterion.error = None;
+        seeded_model
+    }
+
     fn create_stop_criterion() -> StopCriterion {
         StopCriterion::new(
             Some(0.001),
@@ -563,6 +1714,9 @@ mod tests {
                 generation_number: 3,
             }),
             Some(100),
+            None,
+            None,
+            None,
         )
     }
 
@@ -612,6 +1766,7 @@ mod tests {
                     expression_tree,
                     fitness,
                     defective: false,
+                    provenance: Provenance::default(),
                 })
             })
             .collect()
@@ -652,6 +1807,7 @@ mod tests {
             },
             fitness,
             defective,
+            provenance: Provenance::default(),
         })
     }
 
@@ -689,6 +1845,45 @@ mod tests {
         ]
     }
 
+    struct TestObserver {
+        events: Rc<RefCell<Vec<String>>>,
+        stop_generation_number: Option<u32>,
+    }
+
+    impl Observer for TestObserver {
+        fn on_run_start(&mut self) -> ObserverAction {
+            self.events.borrow_mut().push(String::from("start"));
+            ObserverAction::Continue
+        }
+        fn on_generation_evaluated(
+            &mut self,
+            _: &[Rc<Individual>],
+            stats: &GenerationStats,
+        ) -> ObserverAction {
+            self.events
+                .borrow_mut()
+                .push(format!("generation {}", stats.generation_number));
+            if self.stop_generation_number == Some(stats.generation_number) {
+                ObserverAction::Stop
+            } else {
+                ObserverAction::Continue
+            }
+        }
+        fn on_new_best(&mut self, individual: &Rc<Individual>) -> ObserverAction {
+            self.events
+                .borrow_mut()
+                .push(format!("best {}", individual.id));
+            ObserverAction::Continue
+        }
+        fn on_stagnation(&mut self, _: &GenerationStats) -> ObserverAction {
+            self.events.borrow_mut().push(String::from("stagnation"));
+            ObserverAction::Continue
+        }
+        fn on_run_finished(&mut self, _: &ModelResult) {
+            self.events.borrow_mut().push(String::from("finished"));
+        }
+    }
+
     fn get_worksheet(path: &str) -> Range<DataType> {
         let mut path_buf = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
         path_buf.push(path);
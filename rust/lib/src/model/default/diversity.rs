@@ -0,0 +1,427 @@
+//! Module with population diversity maintenance.
+use super::super::input_data::InputData;
+use super::core::Individual;
+use super::utils::{get_individual_points, sort_individuals};
+use crate::expression_tree::{ExpressionTree, Node, Operation, ValueNode};
+use serde::Deserialize;
+use std::rc::Rc;
+
+/// The way the similarity of two individuals is measured.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DistanceMetric {
+    /// The share of nodes that differ when expression trees are overlaid from the root, between 0.0 and 1.0.
+    /// Constants are considered equal regardless of their values.
+    Tree,
+    /// The root mean square difference between values computed on the input data.
+    Output,
+}
+
+/// Diversity preserving selection of the next generation.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum Diversity {
+    /// Rank points of individuals are divided by the niche count which is the sum of
+    /// 1 - (distance / radius) ^ alpha over individuals closer than the radius.
+    /// The best individual always survives.
+    FitnessSharing {
+        metric: DistanceMetric,
+        radius: f64,
+        #[serde(default = "get_default_alpha")]
+        alpha: f64,
+    },
+    /// Each offspring replaces the most similar survivor if it is better.
+    /// Offspring are added without a competition while the generation is not full.
+    Crowding { metric: DistanceMetric },
+}
+
+impl Diversity {
+    /// Return the sorted next generation composed of survivors and offspring.
+    pub fn select(
+        &self,
+        survivors: Vec<Rc<Individual>>,
+        offspring: Vec<Rc<Individual>>,
+        generation_len: usize,
+        complexity_impact: f32,
+        input_data: &InputData,
+    ) -> Vec<Rc<Individual>> {
+        let mut individuals = match self {
+            Diversity::FitnessSharing {
+                metric,
+                radius,
+                alpha,
+            } => {
+                let mut individuals = survivors;
+                individuals.extend(offspring);
+                if individuals.len() > generation_len {
+                    sort_individuals(&mut individuals, complexity_impact);
+                    let points = get_individual_points(&individuals, complexity_impact);
+                    let distances = get_distances(&individuals, *metric, input_data);
+                    let mut shared_points = individuals
+                        .iter()
+                        .zip(&distances)
+                        .map(|(individual, individual_distances)| {
+                            let niche_count = individual_distances
+                                .iter()
+                                .filter(|&&distance| distance < *radius)
+                                .map(|distance| 1.0 - (distance / radius).powf(*alpha))
+                                .sum::<f64>();
+                            points[&individual.id] as f64 / niche_count.max(1.0)
+                        })
+                        .enumerate()
+                        .skip(1)
+                        .collect::<Vec<(usize, f64)>>();
+                    shared_points.sort_by(|(_, p1), (_, p2)| p2.partial_cmp(p1).unwrap());
+                    let mut selected = vec![Rc::clone(&individuals[0])];
+                    for (index, _) in shared_points.into_iter().take(generation_len - 1) {
+                        selected.push(Rc::clone(&individuals[index]));
+                    }
+                    individuals = selected;
+                }
+                individuals
+            }
+            Diversity::Crowding { metric } => {
+                let mut individuals = survivors;
+                let mut outputs = get_outputs(&individuals, *metric, input_data);
+                for child in offspring {
+                    let child_outputs = get_individual_outputs(&child, *metric, input_data);
+                    if individuals.len() < generation_len {
+                        individuals.push(child);
+                        outputs.push(child_outputs);
+                        continue;
+                    }
+                    let nearest_index = (0..individuals.len())
+                        .map(|index| {
+                            let distance = get_distance(
+                                &individuals[index],
+                                &child,
+                                &outputs[index],
+                                &child_outputs,
+                                *metric,
+                            );
+                            (index, distance)
+                        })
+                        .min_by(|(_, d1), (_, d2)| d1.partial_cmp(d2).unwrap())
+                        .map(|(index, _)| index);
+                    if let Some(nearest_index) = nearest_index {
+                        if is_better(&child, &individuals[nearest_index]) {
+                            individuals[nearest_index] = child;
+                            outputs[nearest_index] = child_outputs;
+                        }
+                    }
+                }
+                individuals
+            }
+        };
+        sort_individuals(&mut individuals, complexity_impact);
+        individuals.truncate(generation_len);
+        individuals
+    }
+}
+
+/// Return the share of nodes that differ when expression trees are overlaid from the root.
+pub fn get_tree_distance(
+    expression_tree1: &ExpressionTree,
+    expression_tree2: &ExpressionTree,
+) -> f64 {
+    let node_number = expression_tree1.count_nodes() + expression_tree2.count_nodes();
+    count_different_nodes(&expression_tree1.root, &expression_tree2.root) as f64
+        / node_number as f64
+}
+
+/// Return the root mean square difference between outputs, or infinity if it can't be computed.
+pub fn get_output_distance(outputs1: &[f64], outputs2: &[f64]) -> f64 {
+    if outputs1.is_empty() || outputs1.len() != outputs2.len() {
+        return f64::INFINITY;
+    }
+    let distance = (outputs1
+        .iter()
+        .zip(outputs2)
+        .map(|(output1, output2)| (output1 - output2).powf(2.0))
+        .sum::<f64>()
+        / outputs1.len() as f64)
+        .sqrt();
+    if distance.is_nan() {
+        f64::INFINITY
+    } else {
+        distance
+    }
+}
+
+/// Return the mean tree distance over all pairs of individuals, or 0.0 if there are less than two of them.
+pub fn get_mean_tree_distance(individuals: &[Rc<Individual>]) -> f64 {
+    let mut distance_sum = 0.0;
+    let mut pair_number = 0;
+    for (i, individual1) in individuals.iter().enumerate() {
+        for individual2 in &individuals[i + 1..] {
+            distance_sum +=
+                get_tree_distance(&individual1.expression_tree, &individual2.expression_tree);
+            pair_number += 1;
+        }
+    }
+    if pair_number == 0 {
+        return 0.0;
+    }
+    distance_sum / pair_number as f64
+}
+
+fn count_different_nodes(node1: &Node, node2: &Node) -> usize {
+    let arguments = match (node1, node2) {
+        (Node::Operator(operator_node1), Node::Operator(operator_node2))
+            if operator_node1.operation.get_name() == operator_node2.operation.get_name()
+                && operator_node1.arguments.len() == operator_node2.arguments.len() =>
+        {
+            operator_node1
+                .arguments
+                .iter()
+                .zip(&operator_node2.arguments)
+        }
+        (Node::Function(function_node1), Node::Function(function_node2))
+            if function_node1.operation.get_name() == function_node2.operation.get_name()
+                && function_node1.arguments.len() == function_node2.arguments.len() =>
+        {
+            function_node1
+                .arguments
+                .iter()
+                .zip(&function_node2.arguments)
+        }
+        (
+            Node::Value(ValueNode::Variable(variable1)),
+            Node::Value(ValueNode::Variable(variable2)),
+        ) if variable1 == variable2 => return 0,
+        (Node::Value(ValueNode::Constant(_)), Node::Value(ValueNode::Constant(_))) => return 0,
+        _ => return node1.count_nodes() + node2.count_nodes(),
+    };
+    arguments
+        .map(|(argument1, argument2)| count_different_nodes(argument1, argument2))
+        .sum()
+}
+
+fn get_distances(
+    individuals: &[Rc<Individual>],
+    metric: DistanceMetric,
+    input_data: &InputData,
+) -> Vec<Vec<f64>> {
+    let outputs = get_outputs(individuals, metric, input_data);
+    individuals
+        .iter()
+        .zip(&outputs)
+        .map(|(individual1, outputs1)| {
+            individuals
+                .iter()
+                .zip(&outputs)
+                .map(|(individual2, outputs2)| {
+                    get_distance(individual1, individual2, outputs1, outputs2, metric)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn get_distance(
+    individual1: &Individual,
+    individual2: &Individual,
+    outputs1: &[f64],
+    outputs2: &[f64],
+    metric: DistanceMetric,
+) -> f64 {
+    match metric {
+        DistanceMetric::Tree => {
+            get_tree_distance(&individual1.expression_tree, &individual2.expression_tree)
+        }
+        DistanceMetric::Output => get_output_distance(outputs1, outputs2),
+    }
+}
+
+/// Return outputs of individuals if the metric requires them.
+fn get_outputs(
+    individuals: &[Rc<Individual>],
+    metric: DistanceMetric,
+    input_data: &InputData,
+) -> Vec<Vec<f64>> {
+    individuals
+        .iter()
+        .map(|individual| get_individual_outputs(individual, metric, input_data))
+        .collect()
+}
+
+fn get_individual_outputs(
+    individual: &Individual,
+    metric: DistanceMetric,
+    input_data: &InputData,
+) -> Vec<f64> {
+    match metric {
+        DistanceMetric::Tree => vec![],
+        DistanceMetric::Output => individual
+            .expression_tree
+            .get_outputs(input_data)
+            .unwrap_or_default(),
+    }
+}
+
+/// Return true if the first individual has a lower error, or the same error and a lower complexity.
+fn is_better(individual1: &Individual, individual2: &Individual) -> bool {
+    if individual1.defective {
+        return false;
+    }
+    if individual2.defective {
+        return true;
+    }
+    individual1.fitness.error < individual2.fitness.error
+        || individual1.fitness.error == individual2.fitness.error
+            && individual1.fitness.complexity < individual2.fitness.complexity
+}
+
+fn get_default_alpha() -> f64 {
+    1.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::fitness::Fitness;
+    use super::super::super::settings::Settings;
+    use super::super::genealogy::Provenance;
+    use super::*;
+    use crate::expression_tree::OperationNode;
+
+    #[test]
+    fn test_get_tree_distance() {
+        let settings = Settings::default();
+        let sin_x =
+            create_function_expression_tree(&settings, ValueNode::Variable(String::from("x")));
+        let sin_2 = create_function_expression_tree(&settings, ValueNode::Constant(2.0));
+        let sin_3 = create_function_expression_tree(&settings, ValueNode::Constant(3.0));
+        let x = ExpressionTree {
+            root: Node::Value(ValueNode::Variable(String::from("x"))),
+            variables: vec![String::from("x")],
+        };
+        assert_eq!(0.0, get_tree_distance(&sin_2, &sin_3));
+        assert_eq!(0.5, get_tree_distance(&sin_x, &sin_2));
+        assert_eq!(1.0, get_tree_distance(&sin_x, &x));
+    }
+
+    #[test]
+    fn test_get_output_distance() {
+        assert_eq!(2.0, get_output_distance(&[1.0, 2.0], &[3.0, 0.0]));
+        assert_eq!(f64::INFINITY, get_output_distance(&[1.0, 2.0], &[1.0]));
+        assert_eq!(f64::INFINITY, get_output_distance(&[f64::NAN], &[1.0]));
+    }
+
+    #[test]
+    fn test_select_crowding() {
+        let settings = Settings::default();
+        let input_data = create_input_data();
+        let survivors = vec![
+            create_individual(
+                0,
+                create_function_expression_tree(&settings, ValueNode::Constant(1.0)),
+                1.0,
+            ),
+            create_individual(1, create_variable_expression_tree(), 2.0),
+        ];
+        let offspring = vec![
+            create_individual(2, create_variable_expression_tree(), 1.5),
+            create_individual(
+                3,
+                create_function_expression_tree(&settings, ValueNode::Constant(2.0)),
+                3.0,
+            ),
+        ];
+        let individuals = Diversity::Crowding {
+            metric: DistanceMetric::Tree,
+        }
+        .select(survivors, offspring, 2, 0.5, &input_data);
+        assert_eq!(
+            vec![0, 2],
+            individuals
+                .iter()
+                .map(|individual| individual.id)
+                .collect::<Vec<u32>>()
+        );
+    }
+
+    #[test]
+    fn test_select_fitness_sharing() {
+        let settings = Settings::default();
+        let input_data = create_input_data();
+        let survivors = vec![
+            create_individual(
+                0,
+                create_function_expression_tree(&settings, ValueNode::Constant(1.0)),
+                1.0,
+            ),
+            create_individual(
+                1,
+                create_function_expression_tree(&settings, ValueNode::Constant(2.0)),
+                2.0,
+            ),
+            create_individual(2, create_variable_expression_tree(), 3.0),
+        ];
+        let individuals = Diversity::FitnessSharing {
+            metric: DistanceMetric::Output,
+            radius: 0.5,
+            alpha: 1.0,
+        }
+        .select(survivors, vec![], 2, 0.5, &input_data);
+        assert_eq!(
+            vec![0, 2],
+            individuals
+                .iter()
+                .map(|individual| individual.id)
+                .collect::<Vec<u32>>()
+        );
+    }
+
+    #[test]
+    fn test_deserialize() -> Result<(), serde_json::Error> {
+        let expected_diversity = Diversity::FitnessSharing {
+            metric: DistanceMetric::Output,
+            radius: 0.1,
+            alpha: 1.0,
+        };
+        let actual_diversity = serde_json::from_str::<Diversity>(
+            r#"{"method": "fitness_sharing", "metric": "output", "radius": 0.1}"#,
+        )?;
+        assert_eq!(expected_diversity, actual_diversity);
+        Ok(())
+    }
+
+    fn create_function_expression_tree(settings: &Settings, value: ValueNode) -> ExpressionTree {
+        ExpressionTree {
+            root: Node::Function(OperationNode {
+                operation: settings.find_function_by_name("sin").unwrap(),
+                arguments: vec![Node::Value(value)],
+            }),
+            variables: vec![String::from("x")],
+        }
+    }
+
+    fn create_variable_expression_tree() -> ExpressionTree {
+        ExpressionTree {
+            root: Node::Value(ValueNode::Variable(String::from("x"))),
+            variables: vec![String::from("x")],
+        }
+    }
+
+    fn create_input_data() -> InputData {
+        InputData::build(
+            vec![String::from("x"), String::from("y")],
+            vec![vec![1.0, 1.0], vec![2.0, 2.0]],
+        )
+        .unwrap()
+    }
+
+    fn create_individual(id: u32, expression_tree: ExpressionTree, error: f64) -> Rc<Individual> {
+        Rc::new(Individual {
+            id,
+            generation_number: 0,
+            expression_tree,
+            fitness: Fitness {
+                error,
+                complexity: 1,
+            },
+            defective: false,
+            provenance: Provenance::default(),
+        })
+    }
+}
@@ -0,0 +1,136 @@
+//! Module with individual genealogy.
+use super::core::Individual;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+/// The way an individual was produced.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Provenance {
+    /// The ids of the parents, empty for initial, auxiliary, immigrant and injected individuals.
+    /// The first parent is the one whose subtree was replaced by the crossover.
+    pub parent_ids: Vec<u32>,
+    /// The pre-order index of the node of the first parent replaced by the crossover.
+    pub crossover_point: Option<usize>,
+    /// The names of the mutations applied after the crossover.
+    pub mutations: Vec<String>,
+}
+
+/// Archive of individuals used to reconstruct ancestries.
+/// Only ancestors of the current generation are kept.
+#[derive(Debug, Clone, Default)]
+pub struct Genealogy {
+    individuals: HashMap<u32, Rc<Individual>>,
+}
+
+impl Genealogy {
+    pub fn add(&mut self, individual: &Rc<Individual>) {
+        self.individuals
+            .insert(individual.id, Rc::clone(individual));
+    }
+    pub fn get(&self, id: u32) -> Option<&Rc<Individual>> {
+        self.individuals.get(&id)
+    }
+    /// Return the number of archived individuals.
+    pub fn len(&self) -> usize {
+        self.individuals.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.individuals.is_empty()
+    }
+    /// Return the individual with the id and all its archived ancestors ordered by id.
+    /// Parent ids missing from the archive, e.g. after resuming from a checkpoint, are skipped.
+    pub fn get_ancestry(&self, id: u32) -> Vec<Rc<Individual>> {
+        let mut ancestry = self
+            .collect_ancestor_ids(&[id])
+            .into_iter()
+            .filter_map(|id| self.individuals.get(&id).cloned())
+            .collect::<Vec<Rc<Individual>>>();
+        ancestry.sort_by_key(|individual| individual.id);
+        ancestry
+    }
+    /// Remove individuals that are not ancestors of the individuals.
+    pub fn retain_ancestors(&mut self, individuals: &[Rc<Individual>]) {
+        let ids = individuals
+            .iter()
+            .map(|individual| individual.id)
+            .collect::<Vec<u32>>();
+        let ancestor_ids = self.collect_ancestor_ids(&ids);
+        self.individuals.retain(|id, _| ancestor_ids.contains(id));
+    }
+    fn collect_ancestor_ids(&self, ids: &[u32]) -> HashSet<u32> {
+        let mut ancestor_ids = HashSet::new();
+        let mut stack = ids.to_vec();
+        while let Some(id) = stack.pop() {
+            if !ancestor_ids.insert(id) {
+                continue;
+            }
+            if let Some(individual) = self.individuals.get(&id) {
+                stack.extend(individual.provenance.parent_ids.iter().cloned());
+            }
+        }
+        ancestor_ids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::fitness::Fitness;
+    use super::*;
+    use crate::expression_tree::{ExpressionTree, Node, ValueNode};
+
+    #[test]
+    fn test_get_ancestry() {
+        let genealogy = create_genealogy();
+        assert_eq!(
+            vec![0, 1, 2, 4],
+            genealogy
+                .get_ancestry(4)
+                .iter()
+                .map(|individual| individual.id)
+                .collect::<Vec<u32>>()
+        );
+        assert!(genealogy.get_ancestry(10).is_empty());
+    }
+
+    #[test]
+    fn test_retain_ancestors() {
+        let mut genealogy = create_genealogy();
+        let individuals = vec![Rc::clone(genealogy.get(3).unwrap())];
+        genealogy.retain_ancestors(&individuals);
+        assert_eq!(2, genealogy.len());
+        assert!(genealogy.get(3).is_some());
+        assert!(genealogy.get(1).is_some());
+    }
+
+    fn create_genealogy() -> Genealogy {
+        let mut genealogy = Genealogy::default();
+        for (id, parent_ids) in [
+            (0, vec![]),
+            (1, vec![]),
+            (2, vec![0, 1]),
+            (3, vec![1]),
+            (4, vec![2, 0]),
+        ] {
+            genealogy.add(&Rc::new(Individual {
+                id,
+                generation_number: id / 2,
+                expression_tree: ExpressionTree {
+                    root: Node::Value(ValueNode::Constant(id as f64)),
+                    variables: vec![],
+                },
+                fitness: Fitness {
+                    error: 0.0,
+                    complexity: 1,
+                },
+                defective: false,
+                provenance: Provenance {
+                    parent_ids,
+                    crossover_point: None,
+                    mutations: vec![],
+                },
+            }));
+        }
+        genealogy
+    }
+}
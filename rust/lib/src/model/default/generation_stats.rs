@@ -0,0 +1,265 @@
+//! Module with generation statistics.
+use super::adaptive_mutation::MutationProbability;
+use super::core::Individual;
+use crate::expression_tree::{ExpressionTree, Node, Operation, ValueNode};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::rc::Rc;
+
+/// Summary of a generation computed by the model after each generation.
+/// Error statistics are computed only for non-defective individuals and are NaN if there are none.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GenerationStats {
+    pub generation_number: u32,
+    pub best_error: f64,
+    pub mean_error: f64,
+    pub median_error: f64,
+    pub error_quantiles: ErrorQuantiles,
+    pub mean_complexity: f64,
+    pub min_complexity: u32,
+    pub max_complexity: u32,
+    /// The share of defective individuals between 0.0 and 1.0.
+    pub defective_share: f64,
+    /// The number of expression trees that differ in something other than constant values.
+    pub unique_structure_number: usize,
+    /// The mean tree distance between pairs of individuals between 0.0 and 1.0,
+    /// computed by the model only if the diversity is maintained.
+    pub diversity: Option<f64>,
+    pub without_improvement_generation_number: u32,
+    /// The number of offspring exceeding the bloat limits.
+    pub rejected_offspring_number: usize,
+    /// The number of population restarts performed.
+    pub restart_number: u32,
+    /// The mutation probabilities used to create the next generation.
+    pub mutation_probabilities: Vec<MutationProbability>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ErrorQuantiles {
+    pub q10: f64,
+    pub q25: f64,
+    pub q75: f64,
+    pub q90: f64,
+}
+
+impl GenerationStats {
+    pub fn new(
+        generation_number: u32,
+        individuals: &[Rc<Individual>],
+        without_improvement_generation_number: u32,
+    ) -> GenerationStats {
+        let mut errors = individuals
+            .iter()
+            .filter(|individual| !individual.defective)
+            .map(|individual| individual.fitness.error)
+            .collect::<Vec<f64>>();
+        errors.sort_by(|e1, e2| e1.partial_cmp(e2).unwrap());
+        let complexities = individuals
+            .iter()
+            .map(|individual| individual.fitness.complexity)
+            .collect::<Vec<u32>>();
+        let structures = individuals
+            .iter()
+            .map(|individual| get_structure(&individual.expression_tree))
+            .collect::<HashSet<String>>();
+        GenerationStats {
+            generation_number,
+            best_error: get_quantile(&errors, 0.0),
+            mean_error: get_mean(errors.iter().cloned()),
+            median_error: get_quantile(&errors, 0.5),
+            error_quantiles: ErrorQuantiles {
+                q10: get_quantile(&errors, 0.1),
+                q25: get_quantile(&errors, 0.25),
+                q75: get_quantile(&errors, 0.75),
+                q90: get_quantile(&errors, 0.9),
+            },
+            mean_complexity: get_mean(complexities.iter().map(|&complexity| complexity as f64)),
+            min_complexity: complexities.iter().cloned().min().unwrap_or(0),
+            max_complexity: complexities.iter().cloned().max().unwrap_or(0),
+            defective_share: if individuals.is_empty() {
+                0.0
+            } else {
+                (individuals.len() - errors.len()) as f64 / individuals.len() as f64
+            },
+            unique_structure_number: structures.len(),
+            diversity: None,
+            without_improvement_generation_number,
+            rejected_offspring_number: 0,
+            restart_number: 0,
+            mutation_probabilities: vec![],
+        }
+    }
+}
+
+/// Return the quantile of sorted values using linear interpolation between the closest ranks.
+fn get_quantile(sorted_values: &[f64], quantile: f64) -> f64 {
+    if sorted_values.is_empty() {
+        return f64::NAN;
+    }
+    let position = quantile * (sorted_values.len() - 1) as f64;
+    let lower = position.floor() as usize;
+    let upper = position.ceil() as usize;
+    if lower == upper {
+        return sorted_values[lower];
+    }
+    sorted_values[lower] + (sorted_values[upper] - sorted_values[lower]) * (position - lower as f64)
+}
+
+fn get_mean<I>(values: I) -> f64
+where
+    I: Iterator<Item = f64>,
+{
+    let mut count = 0;
+    let mut sum = 0.0;
+    for value in values {
+        count += 1;
+        sum += value;
+    }
+    if count == 0 {
+        return f64::NAN;
+    }
+    sum / count as f64
+}
+
+/// Return a string describing the expression tree structure where all constants are replaced with the same symbol.
+fn get_structure(expression_tree: &ExpressionTree) -> String {
+    let mut structure = vec![];
+    expression_tree.walk_pre_order(&mut |node| match node {
+        Node::Operator(operator_node) => structure.push(format!(
+            "{}/{}",
+            operator_node.operation.get_name(),
+            operator_node.arguments.len()
+        )),
+        Node::Function(function_node) => structure.push(format!(
+            "{}/{}",
+            function_node.operation.get_name(),
+            function_node.arguments.len()
+        )),
+        Node::Value(ValueNode::Variable(variable)) => structure.push(variable.to_owned()),
+        Node::Value(ValueNode::Constant(_)) => structure.push(String::from("#")),
+    });
+    structure.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::fitness::Fitness;
+    use super::super::super::settings::Settings;
+    use super::super::genealogy::Provenance;
+    use super::*;
+    use crate::expression_tree::OperationNode;
+
+    #[test]
+    fn test_new() {
+        let settings = Settings::default();
+        let individuals = create_test_individuals(&settings);
+        let stats = GenerationStats::new(5, &individuals, 2);
+        assert_eq!(5, stats.generation_number);
+        assert_eq!(1.0, stats.best_error);
+        assert_eq!(2.5, stats.mean_error);
+        assert_eq!(2.5, stats.median_error);
+        assert!((1.3 - stats.error_quantiles.q10).abs() < 1e-9);
+        assert!((1.75 - stats.error_quantiles.q25).abs() < 1e-9);
+        assert!((3.25 - stats.error_quantiles.q75).abs() < 1e-9);
+        assert!((3.7 - stats.error_quantiles.q90).abs() < 1e-9);
+        assert!((2.6 - stats.mean_complexity).abs() < 1e-9);
+        assert_eq!(1, stats.min_complexity);
+        assert_eq!(5, stats.max_complexity);
+        assert_eq!(0.2, stats.defective_share);
+        assert_eq!(3, stats.unique_structure_number);
+        assert_eq!(None, stats.diversity);
+        assert_eq!(2, stats.without_improvement_generation_number);
+    }
+
+    #[test]
+    fn test_new_all_defective() {
+        let individuals = vec![create_individual(
+            0,
+            ExpressionTree {
+                root: Node::Value(ValueNode::Constant(1.0)),
+                variables: vec![],
+            },
+            f64::NAN,
+            1,
+        )];
+        let stats = GenerationStats::new(0, &individuals, 0);
+        assert!(stats.best_error.is_nan());
+        assert!(stats.mean_error.is_nan());
+        assert!(stats.median_error.is_nan());
+        assert_eq!(1.0, stats.defective_share);
+    }
+
+    #[test]
+    fn test_get_quantile() {
+        let values = vec![1.0, 2.0, 3.0, 4.0, 5.0];
+        assert_eq!(1.0, get_quantile(&values, 0.0));
+        assert_eq!(3.0, get_quantile(&values, 0.5));
+        assert_eq!(3.5, get_quantile(&values, 0.625));
+        assert_eq!(5.0, get_quantile(&values, 1.0));
+        assert!(get_quantile(&[], 0.5).is_nan());
+    }
+
+    #[test]
+    fn test_get_structure() {
+        let settings = Settings::default();
+        let expression_tree = create_sum_expression_tree(&settings, 2.0);
+        assert_eq!("+/2 x #", get_structure(&expression_tree));
+    }
+
+    fn create_test_individuals(settings: &Settings) -> Vec<Rc<Individual>> {
+        vec![
+            create_individual(0, create_sum_expression_tree(settings, 1.0), 1.0, 3),
+            create_individual(1, create_sum_expression_tree(settings, 2.0), 2.0, 3),
+            create_individual(2, create_variable_expression_tree(), 3.0, 1),
+            create_individual(3, create_variable_expression_tree(), 4.0, 1),
+            create_individual(
+                4,
+                ExpressionTree {
+                    root: Node::Function(OperationNode {
+                        operation: settings.find_function_by_name("sqrt").unwrap(),
+                        arguments: vec![Node::Value(ValueNode::Constant(-1.0))],
+                    }),
+                    variables: vec![String::from("x")],
+                },
+                f64::NAN,
+                5,
+            ),
+        ]
+    }
+
+    fn create_sum_expression_tree(settings: &Settings, constant: f64) -> ExpressionTree {
+        ExpressionTree {
+            root: Node::Operator(OperationNode {
+                operation: settings.find_binary_operator_by_name("+").unwrap(),
+                arguments: vec![
+                    Node::Value(ValueNode::Variable(String::from("x"))),
+                    Node::Value(ValueNode::Constant(constant)),
+                ],
+            }),
+            variables: vec![String::from("x")],
+        }
+    }
+
+    fn create_variable_expression_tree() -> ExpressionTree {
+        ExpressionTree {
+            root: Node::Value(ValueNode::Variable(String::from("x"))),
+            variables: vec![String::from("x")],
+        }
+    }
+
+    fn create_individual(
+        id: u32,
+        expression_tree: ExpressionTree,
+        error: f64,
+        complexity: u32,
+    ) -> Rc<Individual> {
+        Rc::new(Individual {
+            id,
+            generation_number: 0,
+            expression_tree,
+            fitness: Fitness { error, complexity },
+            defective: error.is_nan(),
+            provenance: Provenance::default(),
+        })
+    }
+}
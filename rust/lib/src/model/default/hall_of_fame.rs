@@ -0,0 +1,144 @@
+//! Module with the hall of fame of the best individuals seen during the run.
+use super::core::Individual;
+use super::utils::sort_individuals;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Bounded archive of the best distinct individuals updated after each generation.
+/// Individuals are distinct if their expression trees differ.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct HallOfFame {
+    /// The maximum number of individuals.
+    #[serde(default = "get_default_len")]
+    pub len: usize,
+    /// Keep only the individual with the lowest error for each complexity.
+    #[serde(default)]
+    pub per_complexity: bool,
+}
+
+impl Default for HallOfFame {
+    fn default() -> HallOfFame {
+        HallOfFame {
+            len: get_default_len(),
+            per_complexity: false,
+        }
+    }
+}
+
+impl HallOfFame {
+    /// Return the best individuals of the hall of fame and the generation ordered from the best.
+    /// Defective individuals are never added.
+    pub fn update(
+        &self,
+        hall_of_fame: &[Rc<Individual>],
+        generation: &[Rc<Individual>],
+        complexity_impact: f32,
+    ) -> Vec<Rc<Individual>> {
+        let mut individuals = hall_of_fame.to_vec();
+        for individual in generation {
+            if !individual.defective
+                && !individuals
+                    .iter()
+                    .any(|other| other.expression_tree == individual.expression_tree)
+            {
+                individuals.push(Rc::clone(individual));
+            }
+        }
+        if self.per_complexity {
+            individuals = get_best_per_complexity(individuals);
+        }
+        sort_individuals(&mut individuals, complexity_impact);
+        individuals.truncate(self.len);
+        individuals
+    }
+}
+
+fn get_best_per_complexity(individuals: Vec<Rc<Individual>>) -> Vec<Rc<Individual>> {
+    let mut best_individuals = HashMap::<u32, Rc<Individual>>::new();
+    for individual in individuals {
+        match best_individuals.get(&individual.fitness.complexity) {
+            Some(best_individual) if best_individual.fitness.error <= individual.fitness.error => {}
+            _ => {
+                best_individuals.insert(individual.fitness.complexity, individual);
+            }
+        }
+    }
+    best_individuals.into_values().collect()
+}
+
+fn get_default_len() -> usize {
+    1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::fitness::Fitness;
+    use super::super::genealogy::Provenance;
+    use super::*;
+    use crate::expression_tree::{ExpressionTree, Node, ValueNode};
+
+    #[test]
+    fn test_update() {
+        let hall_of_fame = HallOfFame {
+            len: 2,
+            per_complexity: false,
+        };
+        let individuals = hall_of_fame.update(
+            &[create_individual(0, 0.0, 0.5, 1)],
+            &create_generation(),
+            0.0,
+        );
+        assert_eq!(vec![3, 0], get_ids(&individuals));
+    }
+
+    #[test]
+    fn test_update_per_complexity() {
+        let hall_of_fame = HallOfFame {
+            len: 5,
+            per_complexity: true,
+        };
+        let individuals = hall_of_fame.update(&[], &create_generation(), 0.0);
+        assert_eq!(vec![3, 4], get_ids(&individuals));
+    }
+
+    #[test]
+    fn test_deserialize() -> Result<(), serde_json::Error> {
+        let expected_hall_of_fame = HallOfFame {
+            len: 1,
+            per_complexity: true,
+        };
+        let actual_hall_of_fame =
+            serde_json::from_str::<HallOfFame>(r#"{"per_complexity": true}"#)?;
+        assert_eq!(expected_hall_of_fame, actual_hall_of_fame);
+        Ok(())
+    }
+
+    fn create_generation() -> Vec<Rc<Individual>> {
+        vec![
+            create_individual(5, 0.0, 0.5, 1),
+            create_individual(1, 1.0, f64::NAN, 1),
+            create_individual(2, 2.0, 0.7, 1),
+            create_individual(3, 3.0, 0.2, 1),
+            create_individual(4, 4.0, 0.9, 3),
+        ]
+    }
+
+    fn create_individual(id: u32, constant: f64, error: f64, complexity: u32) -> Rc<Individual> {
+        Rc::new(Individual {
+            id,
+            generation_number: 0,
+            expression_tree: ExpressionTree {
+                root: Node::Value(ValueNode::Constant(constant)),
+                variables: vec![],
+            },
+            fitness: Fitness { error, complexity },
+            defective: error.is_nan(),
+            provenance: Provenance::default(),
+        })
+    }
+
+    fn get_ids(individuals: &[Rc<Individual>]) -> Vec<u32> {
+        individuals.iter().map(|individual| individual.id).collect()
+    }
+}
@@ -0,0 +1,97 @@
+//! Module with model observers.
+use super::core::{Individual, ModelResult};
+use super::generation_stats::GenerationStats;
+use std::rc::Rc;
+
+/// The observer action returned from lifecycle events.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ObserverAction {
+    Continue,
+    /// Request a graceful stop of the model after the current generation.
+    Stop,
+}
+
+/// Model lifecycle events observer.
+/// All events have empty default implementations, so an observer only implements the events it needs.
+pub trait Observer {
+    /// Called before the first generation is created.
+    fn on_run_start(&mut self) -> ObserverAction {
+        ObserverAction::Continue
+    }
+    /// Called after each generation has been created, evaluated and sorted.
+    fn on_generation_evaluated(
+        &mut self,
+        _individuals: &[Rc<Individual>],
+        _stats: &GenerationStats,
+    ) -> ObserverAction {
+        ObserverAction::Continue
+    }
+    /// Called when a generation contains an individual with an error lower than ever before.
+    fn on_new_best(&mut self, _individual: &Rc<Individual>) -> ObserverAction {
+        ObserverAction::Continue
+    }
+    /// Called after each generation without improvement.
+    fn on_stagnation(&mut self, _stats: &GenerationStats) -> ObserverAction {
+        ObserverAction::Continue
+    }
+    /// Called after the population has been reinitialized with the hall of fame kept.
+    fn on_restart(&mut self, _restart_number: u32, _hall_of_fame: &[Rc<Individual>]) {}
+    /// Called once the model has stopped.
+    fn on_run_finished(&mut self, _result: &ModelResult) {}
+}
+
+/// Observer calling the callback after each generation.
+pub struct CallbackObserver<F>
+where
+    F: FnMut(&[Rc<Individual>], &GenerationStats),
+{
+    pub callback: F,
+}
+
+impl<F> CallbackObserver<F>
+where
+    F: FnMut(&[Rc<Individual>], &GenerationStats),
+{
+    pub fn new(callback: F) -> CallbackObserver<F> {
+        CallbackObserver { callback }
+    }
+}
+
+impl<F> Observer for CallbackObserver<F>
+where
+    F: FnMut(&[Rc<Individual>], &GenerationStats),
+{
+    fn on_generation_evaluated(
+        &mut self,
+        individuals: &[Rc<Individual>],
+        stats: &GenerationStats,
+    ) -> ObserverAction {
+        (self.callback)(individuals, stats);
+        ObserverAction::Continue
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_callback_observer() {
+        let mut call_count = 0;
+        let stats = GenerationStats::new(3, &[], 0);
+        {
+            let mut observer = CallbackObserver::new(|individuals, stats| {
+                assert_eq!(0, individuals.len());
+                assert_eq!(3, stats.generation_number);
+                call_count += 1;
+            });
+            assert_eq!(ObserverAction::Continue, observer.on_run_start());
+            assert_eq!(
+                ObserverAction::Continue,
+                observer.on_generation_evaluated(&[], &stats)
+            );
+            assert_eq!(ObserverAction::Continue, observer.on_stagnation(&stats));
+        }
+        assert_eq!(1, call_count);
+    }
+}
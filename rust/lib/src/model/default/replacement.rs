@@ -0,0 +1,58 @@
+//! Module with generation replacement.
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplacementStrategy {
+    /// The previous generation competes with offspring for a place in the next generation.
+    MuPlusLambda,
+    /// Offspring replace the whole previous generation except elites.
+    MuCommaLambda,
+    /// Offspring replace the worst individuals of the previous generation except elites.
+    SteadyState,
+}
+
+/// The way the next generation is composed of the previous generation and offspring.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Replacement {
+    pub strategy: ReplacementStrategy,
+    /// The number of the best individuals carried to the next generation unchanged.
+    #[serde(default)]
+    pub elite_number: usize,
+    /// The number of random expression trees added to each generation.
+    #[serde(default = "get_default_immigrant_number")]
+    pub immigrant_number: usize,
+}
+
+impl Default for Replacement {
+    fn default() -> Replacement {
+        Replacement {
+            strategy: ReplacementStrategy::MuPlusLambda,
+            elite_number: 0,
+            immigrant_number: get_default_immigrant_number(),
+        }
+    }
+}
+
+fn get_default_immigrant_number() -> usize {
+    1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize() -> Result<(), serde_json::Error> {
+        let expected_replacement = Replacement {
+            strategy: ReplacementStrategy::SteadyState,
+            elite_number: 2,
+            immigrant_number: 1,
+        };
+        let actual_replacement = serde_json::from_str::<Replacement>(
+            r#"{"strategy": "steady_state", "elite_number": 2}"#,
+        )?;
+        assert_eq!(expected_replacement, actual_replacement);
+        Ok(())
+    }
+}
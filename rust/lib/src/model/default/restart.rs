@@ -0,0 +1,72 @@
+//! Module with population restarts on stagnation.
+use super::super::settings::Initialization;
+use serde::Deserialize;
+
+/// The policy of reinitializing the population after a number of generations without improvement.
+/// The hall of fame of the model is carried to the new population, taking at most half of it.
+/// The run continues until one of the stop criteria is met, so the stop criterion without improvement
+/// should allow more generations than the restart or be omitted.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Restart {
+    /// The number of generations without improvement after which the population is reinitialized.
+    pub stagnation_generation_number: u32,
+    /// The maximum number of restarts, or None to restart until the run stops.
+    #[serde(default)]
+    pub max_restart_number: Option<u32>,
+    /// The initialization used to create random expression trees after a restart,
+    /// or None to use the initialization of the settings.
+    #[serde(default)]
+    pub initialization: Option<Initialization>,
+}
+
+impl Restart {
+    pub fn new(stagnation_generation_number: u32) -> Restart {
+        Restart {
+            stagnation_generation_number,
+            max_restart_number: None,
+            initialization: None,
+        }
+    }
+    /// Check if the population must be reinitialized.
+    pub fn must_restart(
+        &self,
+        without_improvement_generation_number: u32,
+        restart_number: u32,
+    ) -> bool {
+        without_improvement_generation_number >= self.stagnation_generation_number
+            && match self.max_restart_number {
+                Some(max_restart_number) => restart_number < max_restart_number,
+                None => true,
+            }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_must_restart() {
+        let restart = Restart {
+            max_restart_number: Some(2),
+            ..Restart::new(5)
+        };
+        assert!(!restart.must_restart(4, 0));
+        assert!(restart.must_restart(5, 1));
+        assert!(!restart.must_restart(5, 2));
+        assert!(Restart::new(5).must_restart(6, 100));
+    }
+
+    #[test]
+    fn test_deserialize() -> Result<(), serde_json::Error> {
+        let expected_restart = Restart {
+            initialization: Some(Initialization::Full { max_depth: 6 }),
+            ..Restart::new(20)
+        };
+        let actual_restart = serde_json::from_str::<Restart>(
+            r#"{"stagnation_generation_number": 20, "initialization": {"method": "full", "max_depth": 6}}"#,
+        )?;
+        assert_eq!(expected_restart, actual_restart);
+        Ok(())
+    }
+}
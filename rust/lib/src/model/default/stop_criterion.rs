@@ -1,24 +1,58 @@
 //! Module with model stop criterion.
-use serde::Deserialize;
+use super::core::Individual;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer};
+use std::rc::Rc;
+use std::time::Duration;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum StopReason {
     Error(f64),
     WithoutImprovement(StopData),
     GenerationNumber(StopData),
+    /// Stop requested by one of the model observers.
+    ObserverRequest(StopData),
+    Duration(DurationStopData),
+    EvaluationNumber(EvaluationStopData),
+    ComplexityError(ComplexityStopData),
 }
 
-#[derive(Debug, PartialEq, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
 pub struct StopData {
     pub error: f64,
     pub generation_number: u32,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct DurationStopData {
+    pub error: f64,
+    pub duration: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvaluationStopData {
+    pub error: f64,
+    pub evaluation_number: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ComplexityStopData {
+    pub error: f64,
+    pub complexity: u32,
+}
+
 #[derive(Debug, PartialEq, Deserialize)]
 pub struct StopCriterion {
     pub error: Option<f64>,
     pub without_improvement: Option<StopData>,
     pub generation_number: Option<u32>,
+    /// The maximum wall-clock duration of the run, deserialized from seconds.
+    #[serde(default, deserialize_with = "deserialize_duration")]
+    pub duration: Option<Duration>,
+    /// The maximum number of fitness evaluations.
+    pub evaluation_number: Option<u64>,
+    /// Stop when an individual has both an error and a complexity less than or equal to these values.
+    pub complexity_error: Option<ComplexityStopData>,
 }
 
 impl StopCriterion {
@@ -26,15 +60,26 @@ impl StopCriterion {
         error: Option<f64>,
         without_improvement: Option<StopData>,
         generation_number: Option<u32>,
+        duration: Option<Duration>,
+        evaluation_number: Option<u64>,
+        complexity_error: Option<ComplexityStopData>,
     ) -> StopCriterion {
         assert!(
-            generation_number.is_some() || without_improvement.is_some() || error.is_some(),
+            generation_number.is_some()
+                || without_improvement.is_some()
+                || error.is_some()
+                || duration.is_some()
+                || evaluation_number.is_some()
+                || complexity_error.is_some(),
             "At least one stop criterion must be set."
         );
         StopCriterion {
             generation_number,
             without_improvement,
             error,
+            duration,
+            evaluation_number,
+            complexity_error,
         }
     }
     pub fn must_stop(
@@ -66,16 +111,75 @@ impl StopCriterion {
         }
         return None;
     }
+    /// Check the complexity and error combination for all individuals.
+    pub fn must_stop_by_complexity(&self, individuals: &[Rc<Individual>]) -> Option<StopReason> {
+        if let Some(complexity_error) = &self.complexity_error {
+            for individual in individuals {
+                if !individual.defective
+                    && individual.fitness.error <= complexity_error.error
+                    && individual.fitness.complexity <= complexity_error.complexity
+                {
+                    return Some(StopReason::ComplexityError(ComplexityStopData {
+                        error: individual.fitness.error,
+                        complexity: individual.fitness.complexity,
+                    }));
+                }
+            }
+        }
+        None
+    }
+    /// Check the wall-clock duration and the fitness evaluation budget.
+    pub fn must_stop_by_budget(
+        &self,
+        error: f64,
+        duration: Duration,
+        evaluation_number: u64,
+    ) -> Option<StopReason> {
+        if let Some(max_duration) = self.duration {
+            if duration >= max_duration {
+                return Some(StopReason::Duration(DurationStopData { error, duration }));
+            }
+        }
+        if let Some(max_evaluation_number) = self.evaluation_number {
+            if evaluation_number >= max_evaluation_number {
+                return Some(StopReason::EvaluationNumber(EvaluationStopData {
+                    error,
+                    evaluation_number,
+                }));
+            }
+        }
+        None
+    }
+}
+
+fn deserialize_duration<'de, D>(deserializer: D) -> Result<Option<Duration>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<f64>::deserialize(deserializer)? {
+        Some(seconds) => match Duration::try_from_secs_f64(seconds) {
+            Ok(duration) => Ok(Some(duration)),
+            Err(err) => Err(D::Error::custom(format!(
+                r#"Invalid duration {}: "{}"."#,
+                seconds, err
+            ))),
+        },
+        None => Ok(None),
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::super::super::fitness::Fitness;
+    use super::super::genealogy::Provenance;
+    use super::super::utils::IdGenerator;
     use super::*;
+    use crate::expression_tree::{ExpressionTree, Node, ValueNode};
 
     #[test]
     #[should_panic(expected = "At least one stop criterion must be set.")]
     fn test_new_panic() {
-        StopCriterion::new(None, None, None);
+        StopCriterion::new(None, None, None, None, None, None);
     }
 
     #[test]
@@ -87,6 +191,12 @@ mod tests {
                 generation_number: 3,
             }),
             error: Some(0.001),
+            duration: Some(Duration::from_secs(60)),
+            evaluation_number: Some(10000),
+            complexity_error: Some(ComplexityStopData {
+                error: 0.01,
+                complexity: 5,
+            }),
         };
         let actual_stop_criterion = create_test_stop_criterion();
         assert_eq!(expected_stop_criterion, actual_stop_criterion);
@@ -139,6 +249,105 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_must_stop_by_complexity() {
+        let stop_criterion = create_test_stop_criterion();
+        let mut id_generator = IdGenerator { id: 0 };
+        let individuals = vec![
+            create_individual(f64::NAN, 1, &mut id_generator),
+            create_individual(0.005, 6, &mut id_generator),
+            create_individual(0.02, 4, &mut id_generator),
+        ];
+        assert_eq!(None, stop_criterion.must_stop_by_complexity(&individuals));
+        let individuals = vec![
+            create_individual(0.005, 6, &mut id_generator),
+            create_individual(0.01, 5, &mut id_generator),
+        ];
+        assert_eq!(
+            Some(StopReason::ComplexityError(ComplexityStopData {
+                error: 0.01,
+                complexity: 5
+            })),
+            stop_criterion.must_stop_by_complexity(&individuals)
+        );
+    }
+
+    #[test]
+    fn test_must_stop_by_budget_none() {
+        let stop_criterion = create_test_stop_criterion();
+        assert_eq!(
+            None,
+            stop_criterion.must_stop_by_budget(0.01, Duration::from_secs(59), 9999)
+        );
+    }
+
+    #[test]
+    fn test_must_stop_by_budget_duration() {
+        let stop_criterion = create_test_stop_criterion();
+        let expected_stop_reason = Some(StopReason::Duration(DurationStopData {
+            error: 0.01,
+            duration: Duration::from_secs(61),
+        }));
+        assert_eq!(
+            expected_stop_reason,
+            stop_criterion.must_stop_by_budget(0.01, Duration::from_secs(61), 10000)
+        );
+    }
+
+    #[test]
+    fn test_must_stop_by_budget_evaluation_number() {
+        let stop_criterion = create_test_stop_criterion();
+        let expected_stop_reason = Some(StopReason::EvaluationNumber(EvaluationStopData {
+            error: 0.01,
+            evaluation_number: 10000,
+        }));
+        assert_eq!(
+            expected_stop_reason,
+            stop_criterion.must_stop_by_budget(0.01, Duration::from_secs(1), 10000)
+        );
+    }
+
+    #[test]
+    fn test_deserialize() -> Result<(), serde_json::Error> {
+        let expected_stop_criterion = create_test_stop_criterion();
+        let actual_stop_criterion = serde_json::from_str::<StopCriterion>(
+            r#"{
+                "error": 0.001,
+                "without_improvement": {"error": 0.001, "generation_number": 3},
+                "generation_number": 100,
+                "duration": 60,
+                "evaluation_number": 10000,
+                "complexity_error": {"error": 0.01, "complexity": 5}
+            }"#,
+        )?;
+        assert_eq!(expected_stop_criterion, actual_stop_criterion);
+        let actual_stop_criterion = serde_json::from_str::<StopCriterion>(r#"{"duration": 1.5}"#)?;
+        assert_eq!(
+            Some(Duration::from_millis(1500)),
+            actual_stop_criterion.duration
+        );
+        assert!(serde_json::from_str::<StopCriterion>(r#"{"duration": -1}"#).is_err());
+        Ok(())
+    }
+
+    fn create_individual(
+        error: f64,
+        complexity: u32,
+        id_generator: &mut IdGenerator,
+    ) -> Rc<Individual> {
+        Rc::new(Individual {
+            id: id_generator.next().unwrap(),
+            generation_number: 0,
+            expression_tree: ExpressionTree {
+                root: Node::Value(ValueNode::Constant(1.0)),
+                variables: vec![],
+            },
+            fitness: Fitness { error, complexity },
+            defective: error.is_nan(),
+            provenance: Provenance::default(),
+        })
+    }
+
     fn create_test_stop_criterion() -> StopCriterion {
         StopCriterion::new(
             Some(0.001),
@@ -147,6 +356,12 @@ mod tests {
                 generation_number: 3,
             }),
             Some(100),
+            Some(Duration::from_secs(60)),
+            Some(10000),
+            Some(ComplexityStopData {
+                error: 0.01,
+                complexity: 5,
+            }),
         )
     }
 }
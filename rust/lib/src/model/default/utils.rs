@@ -29,13 +29,22 @@ pub fn get_individuals_fitness(individuals: &[Rc<Individual>]) -> f64 {
 }
 
 pub fn sort_individuals(individuals: &mut Vec<Rc<Individual>>, complexity_impact: f32) {
+    let points = get_individual_points(individuals, complexity_impact);
+    individuals.sort_by(|i1, i2| points[&i2.id].partial_cmp(&points[&i1.id]).unwrap())
+}
+
+/// Return rank points of individuals by id. The better an individual, the more points it has.
+pub fn get_individual_points(
+    individuals: &[Rc<Individual>],
+    complexity_impact: f32,
+) -> HashMap<u32, f32> {
     let mut points = HashMap::new();
     for individual in individuals.iter() {
         points.insert(individual.id, 0.0);
     }
     add_individual_error_points(individuals, &mut points);
     add_individual_complexity_points(individuals, &mut points, complexity_impact);
-    individuals.sort_by(|i1, i2| points[&i2.id].partial_cmp(&points[&i1.id]).unwrap())
+    points
 }
 
 fn add_individual_error_points(individuals: &[Rc<Individual>], points: &mut HashMap<u32, f32>) {
@@ -91,6 +100,7 @@ fn add_individual_points<F>(
 #[cfg(test)]
 mod tests {
     use super::super::super::fitness::Fitness;
+    use super::super::genealogy::Provenance;
     use super::*;
     use crate::expression_tree::{ExpressionTree, Node, ValueNode};
     use std::f64::NAN;
@@ -155,6 +165,7 @@ mod tests {
             },
             fitness,
             defective,
+            provenance: Provenance::default(),
         })
     }
 
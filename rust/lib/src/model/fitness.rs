@@ -29,6 +29,14 @@ impl ExpressionTree {
     }
     pub fn get_error(&self, input_data: &InputData) -> Result<f64, FitnessError> {
         let mut error = 0.0;
+        for (output, row) in self.get_outputs(input_data)?.iter().zip(&input_data.rows) {
+            error += (output - row[row.len() - 1]).powf(2.0);
+        }
+        Ok(error)
+    }
+    /// Return computed values for each input data row.
+    pub fn get_outputs(&self, input_data: &InputData) -> Result<Vec<f64>, FitnessError> {
+        let mut outputs = vec![];
         for row in &input_data.rows {
             let mut variables = HashMap::new();
             for (variable, value) in input_data
@@ -44,13 +52,12 @@ impl ExpressionTree {
                 Ok(subs_tree) => subs_tree,
                 Err(err) => return Err(FitnessError::SubsError(err)),
             };
-            let compute_result = match subs_tree.compute() {
-                Ok(compute_result) => compute_result,
+            match subs_tree.compute() {
+                Ok(compute_result) => outputs.push(compute_result),
                 Err(err) => return Err(FitnessError::ComputeError(err)),
             };
-            error += (compute_result - row[row.len() - 1]).powf(2.0);
         }
-        Ok(error)
+        Ok(outputs)
     }
     pub fn get_complexity(&self, settings: &Settings) -> u32 {
         self.root.get_complexity(settings)
@@ -170,6 +177,18 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_expression_tree_get_outputs() -> Result<(), FitnessError> {
+        let settings = create_settings();
+        let expression_tree = create_expression_tree_to_get_fitness(&settings);
+        let input_data = create_input_data_to_get_fitness();
+        assert_eq!(
+            vec![3.5, 0.5, 6.5],
+            expression_tree.get_outputs(&input_data)?
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_expression_tree_get_error_subs_error() {
         let settings = create_settings();
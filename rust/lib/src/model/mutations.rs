@@ -3,6 +3,8 @@ use super::settings::Settings;
 use crate::expression_tree::random::Random;
 use crate::expression_tree::{ExpressionTree, Node, OperationNode, ValueNode};
 use std::cmp::max;
+use std::f64::consts::PI;
+use std::mem;
 use std::rc::Rc;
 
 pub fn replace_subtree_mutation<R>(
@@ -21,7 +23,7 @@ where
         tree_complexity as i32 - node.get_complexity(settings) as i32 + complexity_random,
         0,
     ) as u32;
-    *node = Node::create_random(random, settings, &variables, complexity).node;
+    *node = Node::create_random_initial(random, settings, &variables, complexity);
     true
 }
 
@@ -109,12 +111,16 @@ where
         })
     } else {
         let function = Rc::clone(&settings.functions[index - settings.operators.len()]);
+        let arguments_range = function.get_arguments_range();
+        let arguments_number = arguments
+            .len()
+            .clamp(*arguments_range.start(), *arguments_range.end());
         prepare_arguments(
             random,
             settings,
             &variables,
             &mut arguments,
-            function.arguments_number,
+            arguments_number,
             complexity + function.complexity,
         );
         Node::Function(OperationNode {
@@ -146,6 +152,191 @@ where
     true
 }
 
+/// Replace the tree with one of its own subtrees.
+pub fn hoist_mutation<R>(expression_tree: &mut ExpressionTree, random: &mut R, _: &Settings) -> bool
+where
+    R: Random + ?Sized,
+{
+    let count = expression_tree.count_nodes();
+    if count < 2 {
+        return false;
+    }
+    expression_tree.root = expression_tree
+        .get_node_at(random.gen_range(1..count))
+        .clone();
+    true
+}
+
+/// Wrap a random subtree in a random operation whose other arguments are random leaves.
+pub fn insert_mutation<R>(
+    expression_tree: &mut ExpressionTree,
+    random: &mut R,
+    settings: &Settings,
+) -> bool
+where
+    R: Random + ?Sized,
+{
+    let operation_number = settings.operators.len() + settings.functions.len();
+    if operation_number == 0 {
+        return false;
+    }
+    let variables = expression_tree.variables.clone();
+    let node = expression_tree.get_random_node_mut(random);
+    let index = random.gen_range(0..operation_number);
+    let arguments_number = if index < settings.operators.len() {
+        settings.operators[index].arguments_number
+    } else {
+        settings.functions[index - settings.operators.len()].choose_arguments_number(random)
+    };
+    if arguments_number == 0 {
+        return false;
+    }
+    let subtree_index = random.gen_range(0..arguments_number);
+    let mut arguments = vec![];
+    for i in 0..arguments_number {
+        if i == subtree_index {
+            arguments.push(Node::Value(ValueNode::Constant(0.0)));
+        } else {
+            arguments.push(Node::create_random_value(random, settings, &variables).node);
+        }
+    }
+    arguments[subtree_index] = mem::replace(node, Node::Value(ValueNode::Constant(0.0)));
+    *node = if index < settings.operators.len() {
+        Node::Operator(OperationNode {
+            operation: Rc::clone(&settings.operators[index]),
+            arguments,
+        })
+    } else {
+        Node::Function(OperationNode {
+            operation: Rc::clone(&settings.functions[index - settings.operators.len()]),
+            arguments,
+        })
+    };
+    true
+}
+
+/// Swap two arguments of a random non-commutative operator or function with several arguments.
+pub fn swap_arguments_mutation<R>(
+    expression_tree: &mut ExpressionTree,
+    random: &mut R,
+    _: &Settings,
+) -> bool
+where
+    R: Random + ?Sized,
+{
+    let mut indices = vec![];
+    let mut counter = 0;
+    expression_tree.walk_pre_order(&mut |node| {
+        let swappable = match node {
+            Node::Operator(operator_node) => {
+                !operator_node.operation.commutative && operator_node.arguments.len() > 1
+            }
+            Node::Function(function_node) => {
+                !function_node.operation.commutative && function_node.arguments.len() > 1
+            }
+            Node::Value(_) => false,
+        };
+        if swappable {
+            indices.push(counter);
+        }
+        counter += 1;
+    });
+    if indices.is_empty() {
+        return false;
+    }
+    let node = expression_tree.get_node_at_mut(indices[random.gen_range(0..indices.len())]);
+    let arguments = match node {
+        Node::Operator(operator_node) => &mut operator_node.arguments,
+        Node::Function(function_node) => &mut function_node.arguments,
+        Node::Value(_) => unreachable!(),
+    };
+    let first = random.gen_range(0..arguments.len());
+    let mut second = random.gen_range(0..arguments.len() - 1);
+    if second >= first {
+        second += 1;
+    }
+    arguments.swap(first, second);
+    true
+}
+
+/// Add a random leaf to a random variadic function or remove one of its arguments
+/// within the allowed number of arguments.
+pub fn arity_mutation<R>(
+    expression_tree: &mut ExpressionTree,
+    random: &mut R,
+    settings: &Settings,
+) -> bool
+where
+    R: Random + ?Sized,
+{
+    let variables = expression_tree.variables.clone();
+    let function_node = match expression_tree.find_random_variadic_function_node_mut(random) {
+        Some(Node::Function(function_node)) => function_node,
+        _ => return false,
+    };
+    let arguments_range = function_node.operation.get_arguments_range();
+    let arguments = &mut function_node.arguments;
+    let add = if arguments.len() <= *arguments_range.start() {
+        true
+    } else if arguments.len() >= *arguments_range.end() {
+        false
+    } else {
+        random.gen_range(0..2) == 0
+    };
+    if add {
+        let argument = Node::create_random_value(random, settings, &variables).node;
+        arguments.insert(random.gen_range(0..arguments.len() + 1), argument);
+    } else {
+        arguments.remove(random.gen_range(0..arguments.len()));
+    }
+    true
+}
+
+/// The standard deviation of the Gaussian constant perturbation relative to the constant magnitude.
+pub const GAUSSIAN_CONSTANT_SCALE: f64 = 0.1;
+
+/// Add normally distributed noise to a random constant.
+/// The standard deviation is proportional to the constant magnitude, so small constants are tuned finely.
+pub fn gaussian_constant_mutation<R>(
+    expression_tree: &mut ExpressionTree,
+    random: &mut R,
+    _: &Settings,
+) -> bool
+where
+    R: Random + ?Sized,
+{
+    let mut indices = vec![];
+    let mut counter = 0;
+    expression_tree.walk_pre_order(&mut |node| {
+        if let Node::Value(ValueNode::Constant(_)) = node {
+            indices.push(counter);
+        }
+        counter += 1;
+    });
+    if indices.is_empty() {
+        return false;
+    }
+    let node = expression_tree.get_node_at_mut(indices[random.gen_range(0..indices.len())]);
+    if let Node::Value(ValueNode::Constant(constant)) = node {
+        let standard_deviation = if *constant == 0.0 {
+            GAUSSIAN_CONSTANT_SCALE
+        } else {
+            GAUSSIAN_CONSTANT_SCALE * constant.abs()
+        };
+        *constant += standard_deviation * gen_standard_normal(random);
+    }
+    true
+}
+
+/// Return a standard normal random value using the Box-Muller transform.
+fn gen_standard_normal<R>(random: &mut R) -> f64
+where
+    R: Random + ?Sized,
+{
+    let radius = (-2.0 * (1.0 - random.gen_float_standard()).ln()).sqrt();
+    radius * (2.0 * PI * random.gen_float_standard()).cos()
+}
+
 fn prepare_arguments<R>(
     random: &mut R,
     settings: &Settings,
@@ -395,6 +586,260 @@ mod tests {
         assert!(!performed);
     }
 
+    #[test]
+    fn test_hoist_mutation() {
+        let settings = Settings::default();
+        let mut expression_tree = create_expression_tree(&settings);
+        let performed = hoist_mutation(
+            &mut expression_tree,
+            &mut MockRandom::new_int(vec![3]),
+            &settings,
+        );
+        let expected_expression_tree = ExpressionTree {
+            root: Node::Operator(OperationNode {
+                operation: settings.find_binary_operator_by_name("+").unwrap(),
+                arguments: vec![
+                    Node::Value(ValueNode::Variable(String::from("x"))),
+                    Node::Value(ValueNode::Constant(10.0)),
+                ],
+            }),
+            variables: vec![String::from("x")],
+        };
+        assert!(performed);
+        assert_eq!(expected_expression_tree, expression_tree);
+    }
+
+    #[test]
+    fn test_hoist_mutation_not_performed() {
+        let settings = Settings::default();
+        let mut expression_tree = create_value_expression_tree();
+        let performed = hoist_mutation(
+            &mut expression_tree,
+            &mut MockRandom::new_int(vec![]),
+            &settings,
+        );
+        assert!(!performed);
+    }
+
+    #[test]
+    fn test_insert_mutation() {
+        let settings = Settings::default();
+        let mut expression_tree = create_expression_tree(&settings);
+        let performed = insert_mutation(
+            &mut expression_tree,
+            &mut MockRandom::new(vec![5, 3, 1], vec![2.0], vec![0.6]),
+            &settings,
+        );
+        let expected_expression_tree = ExpressionTree {
+            root: Node::Function(OperationNode {
+                operation: settings.find_function_by_name("sin").unwrap(),
+                arguments: vec![Node::Function(OperationNode {
+                    operation: settings.find_function_by_name("log").unwrap(),
+                    arguments: vec![
+                        Node::Value(ValueNode::Constant(5.0)),
+                        Node::Operator(OperationNode {
+                            operation: settings.find_binary_operator_by_name("+").unwrap(),
+                            arguments: vec![
+                                Node::Value(ValueNode::Variable(String::from("x"))),
+                                Node::Operator(OperationNode {
+                                    operation: settings.find_binary_operator_by_name("/").unwrap(),
+                                    arguments: vec![
+                                        Node::Value(ValueNode::Constant(2.0)),
+                                        Node::Value(ValueNode::Constant(10.0)),
+                                    ],
+                                }),
+                            ],
+                        }),
+                    ],
+                })],
+            }),
+            variables: vec![String::from("x")],
+        };
+        assert!(performed);
+        assert_eq!(expected_expression_tree, expression_tree);
+    }
+
+    #[test]
+    fn test_swap_arguments_mutation() {
+        let settings = Settings::default();
+        let mut expression_tree = create_expression_tree(&settings);
+        let performed = swap_arguments_mutation(
+            &mut expression_tree,
+            &mut MockRandom::new_int(vec![0, 1, 0]),
+            &settings,
+        );
+        let expected_expression_tree = ExpressionTree {
+            root: Node::Function(OperationNode {
+                operation: settings.find_function_by_name("sin").unwrap(),
+                arguments: vec![Node::Function(OperationNode {
+                    operation: settings.find_function_by_name("log").unwrap(),
+                    arguments: vec![
+                        Node::Operator(OperationNode {
+                            operation: settings.find_binary_operator_by_name("+").unwrap(),
+                            arguments: vec![
+                                Node::Value(ValueNode::Variable(String::from("x"))),
+                                Node::Value(ValueNode::Constant(10.0)),
+                            ],
+                        }),
+                        Node::Value(ValueNode::Constant(5.0)),
+                    ],
+                })],
+            }),
+            variables: vec![String::from("x")],
+        };
+        assert!(performed);
+        assert_eq!(expected_expression_tree, expression_tree);
+    }
+
+    #[test]
+    fn test_swap_arguments_mutation_commutative() {
+        let settings = Settings::default();
+        let mut expression_tree = ExpressionTree {
+            root: Node::Operator(OperationNode {
+                operation: settings.find_binary_operator_by_name("*").unwrap(),
+                arguments: vec![
+                    Node::Value(ValueNode::Variable(String::from("x"))),
+                    Node::Value(ValueNode::Constant(10.0)),
+                ],
+            }),
+            variables: vec![String::from("x")],
+        };
+        let performed = swap_arguments_mutation(
+            &mut expression_tree,
+            &mut MockRandom::new_int(vec![]),
+            &settings,
+        );
+        assert!(!performed);
+    }
+
+    #[test]
+    fn test_swap_arguments_mutation_commutative_function() {
+        let settings = create_variadic_settings();
+        let mut expression_tree = create_variadic_expression_tree(&settings);
+        let performed = swap_arguments_mutation(
+            &mut expression_tree,
+            &mut MockRandom::new_int(vec![]),
+            &settings,
+        );
+        assert!(!performed);
+    }
+
+    #[test]
+    fn test_gaussian_constant_mutation() {
+        let settings = Settings::default();
+        let mut expression_tree = create_expression_tree(&settings);
+        let performed = gaussian_constant_mutation(
+            &mut expression_tree,
+            &mut MockRandom::new(vec![1], vec![], vec![1.0 - (-0.5f64).exp(), 0.0]),
+            &settings,
+        );
+        assert!(performed);
+        match expression_tree.get_node_at(5) {
+            Node::Value(ValueNode::Constant(constant)) => assert!((11.0 - constant).abs() < 1e-9),
+            node => panic!("Expected a constant, but {:?} was received.", node),
+        }
+    }
+
+    #[test]
+    fn test_gaussian_constant_mutation_not_performed() {
+        let settings = Settings::default();
+        let mut expression_tree = ExpressionTree {
+            root: Node::Value(ValueNode::Variable(String::from("x"))),
+            variables: vec![String::from("x")],
+        };
+        let performed = gaussian_constant_mutation(
+            &mut expression_tree,
+            &mut MockRandom::new_int(vec![]),
+            &settings,
+        );
+        assert!(!performed);
+    }
+
+    #[test]
+    fn test_arity_mutation_add() {
+        let settings = create_variadic_settings();
+        let mut expression_tree = create_variadic_expression_tree(&settings);
+        let performed = arity_mutation(
+            &mut expression_tree,
+            &mut MockRandom::new(vec![0, 1], vec![3.0], vec![0.6]),
+            &settings,
+        );
+        let expected_arguments = vec![
+            Node::Value(ValueNode::Variable(String::from("x"))),
+            Node::Value(ValueNode::Constant(3.0)),
+            Node::Value(ValueNode::Constant(5.0)),
+        ];
+        assert!(performed);
+        assert_eq!(
+            Node::Function(OperationNode {
+                operation: settings.find_function_by_name("max").unwrap(),
+                arguments: expected_arguments,
+            }),
+            expression_tree.root
+        );
+    }
+
+    #[test]
+    fn test_arity_mutation_remove() {
+        let settings = create_variadic_settings();
+        let mut expression_tree = create_variadic_expression_tree(&settings);
+        arity_mutation(
+            &mut expression_tree,
+            &mut MockRandom::new(vec![0, 0], vec![3.0], vec![0.6]),
+            &settings,
+        );
+        let performed = arity_mutation(
+            &mut expression_tree,
+            &mut MockRandom::new_int(vec![0, 1, 2]),
+            &settings,
+        );
+        let expected_arguments = vec![
+            Node::Value(ValueNode::Constant(3.0)),
+            Node::Value(ValueNode::Variable(String::from("x"))),
+        ];
+        assert!(performed);
+        assert_eq!(
+            Node::Function(OperationNode {
+                operation: settings.find_function_by_name("max").unwrap(),
+                arguments: expected_arguments,
+            }),
+            expression_tree.root
+        );
+    }
+
+    #[test]
+    fn test_arity_mutation_not_performed() {
+        let settings = Settings::default();
+        let mut expression_tree = create_expression_tree(&settings);
+        let performed = arity_mutation(
+            &mut expression_tree,
+            &mut MockRandom::new_int(vec![]),
+            &settings,
+        );
+        assert!(!performed);
+    }
+
+    fn create_variadic_settings() -> Settings {
+        let mut settings = Settings::default();
+        settings
+            .functions
+            .append(&mut Settings::get_variadic_functions());
+        settings
+    }
+
+    fn create_variadic_expression_tree(settings: &Settings) -> ExpressionTree {
+        ExpressionTree {
+            root: Node::Function(OperationNode {
+                operation: settings.find_function_by_name("max").unwrap(),
+                arguments: vec![
+                    Node::Value(ValueNode::Variable(String::from("x"))),
+                    Node::Value(ValueNode::Constant(5.0)),
+                ],
+            }),
+            variables: vec![String::from("x")],
+        }
+    }
+
     fn create_expression_tree(settings: &Settings) -> ExpressionTree {
         ExpressionTree {
             root: Node::Function(OperationNode {
@@ -1,8 +1,12 @@
 //! Module with model settings.
 pub mod core;
 pub mod default;
+#[cfg(feature = "special-functions")]
+pub mod special;
 pub mod types;
 
 pub use self::core::*;
 pub use default::*;
+#[cfg(feature = "special-functions")]
+pub use special::*;
 pub use types::*;
@@ -1,9 +1,18 @@
 //! Settings core functionality module.
-use super::types::{ConvertOutputData, Converter, ConverterOperation};
+use super::super::crossing::Crossover;
+use super::types::{
+    BloatLimits, ConvertOutputData, Converter, ConverterOperation, FunctionDeclaration,
+    Initialization, NamedConstant,
+};
 use crate::expression_tree::random::Random;
-use crate::expression_tree::{ExpressionTree, Function, Node, Operation, Operator};
+use crate::expression_tree::{
+    DeclarationError, ExpressionTree, Fixity, Function, Node, Operation, Operator,
+};
 use std::rc::Rc;
 
+/// The probability of the arity mutation once the variadic functions are added.
+const ARITY_MUTATION_PROBABILITY: f64 = 0.1;
+
 #[derive(Debug, PartialEq)]
 pub struct NodeProbability {
     pub operator_node: f64,
@@ -12,6 +21,7 @@ pub struct NodeProbability {
 }
 
 pub struct Mutation {
+    pub name: String,
     pub mutation_fn: Box<dyn Fn(&mut ExpressionTree, &mut dyn Random, &Settings) -> bool>,
     pub probability: f64,
 }
@@ -34,10 +44,15 @@ pub struct Settings {
     pub operators: Vec<Rc<Operator>>,
     pub functions: Vec<Rc<Function>>,
     pub converters: Vec<Converter>,
+    /// Constants the parser resolves by name.
+    pub constants: Vec<NamedConstant>,
     pub variable_complexity: u32,
     pub constant_complexity: u32,
     pub complexity_impact: f32,
     pub get_node_probability_fn: Box<dyn Fn(u32) -> NodeProbability>,
+    pub initialization: Initialization,
+    pub bloat_limits: BloatLimits,
+    pub crossover: Box<dyn Crossover>,
     pub mutations: Vec<Mutation>,
 }
 
@@ -53,6 +68,57 @@ impl Settings {
         }
         None
     }
+    /// Add the function declared by an expression, which may use the functions declared before.
+    pub fn declare_function(
+        &mut self,
+        declaration: &FunctionDeclaration,
+    ) -> Result<(), DeclarationError> {
+        let function =
+            Function::parse_declaration(&declaration.expression, declaration.complexity, self)?;
+        self.functions.push(Rc::new(function));
+        Ok(())
+    }
+    /// Add the functions whose names aren't used by the functions of the settings yet.
+    pub fn add_functions(&mut self, functions: Vec<Rc<Function>>) {
+        for function in functions {
+            if self.find_function_by_name(&function.name).is_none() {
+                self.functions.push(function);
+            }
+        }
+    }
+    /// Add the variadic functions and enable the arity mutation, which adds or removes their arguments,
+    /// unless its probability is set already.
+    pub fn add_variadic_functions(&mut self) {
+        self.add_functions(Settings::get_variadic_functions());
+        if let Some(mutation) = self.find_mutation_by_name_mut("arity") {
+            if mutation.probability == 0.0 {
+                mutation.probability = ARITY_MUTATION_PROBABILITY;
+            }
+        }
+    }
+    /// Add the comparison operators and the conditional and piecewise functions.
+    pub fn add_piecewise_operations(&mut self) {
+        for operator in Settings::get_comparison_operators() {
+            if self
+                .find_operator_by_name(&operator.name, operator.fixity.clone())
+                .is_none()
+            {
+                self.operators.push(operator);
+            }
+        }
+        self.add_functions(Settings::get_piecewise_functions());
+    }
+    pub fn find_constant_by_name(&self, name: &str) -> Option<f64> {
+        self.constants
+            .iter()
+            .find(|constant| constant.name == name)
+            .map(|constant| constant.value)
+    }
+    pub fn find_mutation_by_name_mut(&mut self, name: &str) -> Option<&mut Mutation> {
+        self.mutations
+            .iter_mut()
+            .find(|mutation| mutation.name == name)
+    }
     pub fn find_unary_operator_by_name(&self, name: &str) -> Option<Rc<Operator>> {
         for operator in &self.operators {
             if operator.get_name() == name && operator.arguments_number == 1 {
@@ -69,6 +135,12 @@ impl Settings {
         }
         None
     }
+    pub fn find_operator_by_name(&self, name: &str, fixity: Fixity) -> Option<Rc<Operator>> {
+        self.operators
+            .iter()
+            .find(|operator| operator.get_name() == name && operator.fixity == fixity)
+            .map(Rc::clone)
+    }
     pub fn find_converters(&self, operation: &ConverterOperation) -> Vec<&Converter> {
         let mut converters = vec![];
         for converter in &self.converters {
@@ -93,25 +165,29 @@ impl Settings {
             arguments,
         }
     }
-    pub fn mutate<R>(&self, expression_tree: &mut ExpressionTree, random: &mut R)
+    /// Apply a random mutation and return the index of the performed one, or None if no mutation was performed.
+    pub fn mutate<R>(&self, expression_tree: &mut ExpressionTree, random: &mut R) -> Option<usize>
     where
         R: Random,
     {
         let random_probability = random.gen_float_standard();
         let mut probability = 0.0;
         let mut executed = true;
-        let mut performed = false;
-        while executed && !performed {
+        let mut performed_index = None;
+        while executed && performed_index.is_none() {
             executed = false;
-            for mutation in &self.mutations {
+            for (index, mutation) in self.mutations.iter().enumerate() {
                 probability += mutation.probability;
                 if random_probability < probability {
                     executed = true;
-                    performed = mutation.mutate(expression_tree, random, self);
+                    if mutation.mutate(expression_tree, random, self) {
+                        performed_index = Some(index);
+                    }
                     break;
                 }
             }
         }
+        performed_index
     }
 }
 
@@ -152,6 +228,91 @@ mod tests {
         assert_eq!(None, settings.find_function_by_name("fn"));
     }
 
+    #[test]
+    fn test_declare_function() -> Result<(), DeclarationError> {
+        let mut settings = Settings::default();
+        settings.declare_function(&FunctionDeclaration {
+            expression: String::from("sigmoid(a) = 1 / (1 + exp(-a))"),
+            complexity: 4,
+        })?;
+        settings.declare_function(&FunctionDeclaration {
+            expression: String::from("swish(a) = a * sigmoid(a)"),
+            complexity: 5,
+        })?;
+        let swish = settings.find_function_by_name("swish").unwrap();
+        assert_eq!(
+            Rc::clone(&swish),
+            settings.functions[settings.functions.len() - 1]
+        );
+        assert_eq!(1.0 / (1.0 + (-2.0_f64).exp()) * 2.0, swish.compute(&[2.0]));
+        let expression_tree = ExpressionTree::parse("2 * swish(x)", &settings).unwrap();
+        assert_eq!("2 * swish(x)", expression_tree.to_string());
+        Ok(())
+    }
+
+    #[test]
+    fn test_add_piecewise_operations() {
+        let mut settings = Settings::default();
+        settings.add_functions(Settings::get_variadic_functions());
+        let functions_number = settings.functions.len();
+        settings.add_piecewise_operations();
+        assert_eq!(functions_number + 3, settings.functions.len());
+        assert_eq!(
+            Settings::default().operators.len() + 6,
+            settings.operators.len()
+        );
+        for expression in [
+            "if(x + 1 < 2 * x, x, max(x, 2, 3)) - heaviside(x - 1)",
+            "(x >= 1) * clamp(x, 0, 1 <= x)",
+            "(x == 1) - (2 * x != x + 1)",
+            "x == 1 != (x != 2)",
+        ] {
+            let expression_tree = ExpressionTree::parse(expression, &settings).unwrap();
+            assert_eq!(expression, expression_tree.to_string());
+        }
+    }
+
+    #[test]
+    fn test_add_variadic_functions() {
+        let mut settings = Settings::default();
+        settings.add_variadic_functions();
+        assert_eq!(
+            Settings::default().functions.len() + Settings::get_variadic_functions().len(),
+            settings.functions.len()
+        );
+        assert_eq!(
+            ARITY_MUTATION_PROBABILITY,
+            settings
+                .find_mutation_by_name_mut("arity")
+                .unwrap()
+                .probability
+        );
+        let mut settings = Settings::default();
+        settings
+            .find_mutation_by_name_mut("arity")
+            .unwrap()
+            .probability = 0.3;
+        settings.add_variadic_functions();
+        assert_eq!(
+            0.3,
+            settings
+                .find_mutation_by_name_mut("arity")
+                .unwrap()
+                .probability
+        );
+    }
+
+    #[test]
+    fn test_find_mutation_by_name_mut() {
+        let mut settings = Settings::default();
+        settings
+            .find_mutation_by_name_mut("hoist")
+            .unwrap()
+            .probability = 0.1;
+        assert_eq!(0.1, settings.mutations[5].probability);
+        assert!(settings.find_mutation_by_name_mut("mutation").is_none());
+    }
+
     #[test]
     fn test_find_converters() {
         let settings = Settings::default();
@@ -239,10 +400,12 @@ mod tests {
         let mut random = MockRandom::new(vec![], vec![], vec![0.45]);
         settings.mutations = vec![
             Mutation {
+                name: String::from("first"),
                 mutation_fn: Box::new(|_, _, _| panic!("Mutation number 1.")),
                 probability: 0.2,
             },
             Mutation {
+                name: String::from("second"),
                 mutation_fn: Box::new(move |actual_expression_tree, _, _| {
                     assert_eq!(expression_tree_clone, *actual_expression_tree);
                     panic!("Mutation number 2.");
@@ -250,10 +413,48 @@ mod tests {
                 probability: 0.3,
             },
             Mutation {
+                name: String::from("third"),
                 mutation_fn: Box::new(|_, _, _| panic!("Mutation number 3.")),
                 probability: 0.3,
             },
         ];
         settings.mutate(&mut expression_tree, &mut random);
     }
+
+    #[test]
+    fn test_mutate_performed_index() {
+        let settings = Settings {
+            mutations: vec![
+                Mutation {
+                    name: String::from("first"),
+                    mutation_fn: Box::new(|_, _, _| true),
+                    probability: 0.2,
+                },
+                Mutation {
+                    name: String::from("second"),
+                    mutation_fn: Box::new(|_, _, _| false),
+                    probability: 0.3,
+                },
+            ],
+            ..Settings::default()
+        };
+        let mut expression_tree = ExpressionTree {
+            root: Node::Value(ValueNode::Constant(5.0)),
+            variables: vec![],
+        };
+        assert_eq!(
+            Some(0),
+            settings.mutate(
+                &mut expression_tree,
+                &mut MockRandom::new(vec![], vec![], vec![0.45])
+            )
+        );
+        assert_eq!(
+            None,
+            settings.mutate(
+                &mut expression_tree,
+                &mut MockRandom::new(vec![], vec![], vec![0.95])
+            )
+        );
+    }
 }
@@ -1,20 +1,33 @@
 //! Module for getting default settings.
+use super::super::crossing::SubtreeCrossover;
 use super::super::mutations::{
+    arity_mutation, gaussian_constant_mutation, hoist_mutation, insert_mutation,
     remove_operation_mutation, replace_leaf_mutation, replace_operation_mutation,
-    replace_subtree_mutation, shift_leaf_mutation,
+    replace_subtree_mutation, shift_leaf_mutation, swap_arguments_mutation,
 };
 use super::core::{Mutation, NodeProbability, Settings};
-use super::types::{Converter, ConverterOperation};
-use crate::expression_tree::{Associativity, Function, Node, Operator, ValueNode};
-use std::f64::{consts::E, consts::PI, NAN};
+use super::types::{BloatLimits, Converter, ConverterOperation, Initialization, NamedConstant};
+use crate::expression_tree::{Associativity, Fixity, Function, Node, Operator, ValueNode};
+use std::f64::consts::{E, PI};
 use std::rc::Rc;
 
+thread_local! {
+    // The built-in operations are created once per thread, so they are equal in all settings.
+    static DEFAULT_OPERATORS: Vec<Rc<Operator>> = Settings::create_default_operators();
+    static DEFAULT_FUNCTIONS: Vec<Rc<Function>> = Settings::create_default_functions();
+    static VARIADIC_FUNCTIONS: Vec<Rc<Function>> = Settings::create_variadic_functions();
+    static COMPARISON_OPERATORS: Vec<Rc<Operator>> = Settings::create_comparison_operators();
+    static IF_FUNCTION: Rc<Function> = Settings::create_if_function();
+    static PIECEWISE_FUNCTIONS: Vec<Rc<Function>> = Settings::create_piecewise_functions();
+}
+
 impl Default for Settings {
     fn default() -> Settings {
         let mut settings = Settings {
             operators: Settings::get_default_operators(),
             functions: Settings::get_default_functions(),
             converters: vec![],
+            constants: Settings::get_default_constants(),
             variable_complexity: 1,
             constant_complexity: 1,
             complexity_impact: 0.5,
@@ -26,37 +39,80 @@ impl Default for Settings {
                     value_node: 1.0 - operation_node_probability * 2.0,
                 }
             }),
+            initialization: Initialization::Complexity,
+            bloat_limits: BloatLimits::default(),
+            crossover: Box::new(SubtreeCrossover),
             mutations: vec![
                 Mutation {
+                    name: String::from("replace_subtree"),
                     mutation_fn: Box::new(|expression_tree, random, settings| {
                         replace_subtree_mutation(expression_tree, random, settings)
                     }),
                     probability: 0.15,
                 },
                 Mutation {
+                    name: String::from("replace_leaf"),
                     mutation_fn: Box::new(|expression_tree, random, settings| {
                         replace_leaf_mutation(expression_tree, random, settings)
                     }),
                     probability: 0.15,
                 },
                 Mutation {
+                    name: String::from("shift_leaf"),
                     mutation_fn: Box::new(|expression_tree, random, settings| {
                         shift_leaf_mutation(expression_tree, random, settings)
                     }),
                     probability: 0.2,
                 },
                 Mutation {
+                    name: String::from("replace_operation"),
                     mutation_fn: Box::new(|expression_tree, random, settings| {
                         replace_operation_mutation(expression_tree, random, settings)
                     }),
                     probability: 0.2,
                 },
                 Mutation {
+                    name: String::from("remove_operation"),
                     mutation_fn: Box::new(|expression_tree, random, settings| {
                         remove_operation_mutation(expression_tree, random, settings)
                     }),
                     probability: 0.2,
                 },
+                Mutation {
+                    name: String::from("hoist"),
+                    mutation_fn: Box::new(|expression_tree, random, settings| {
+                        hoist_mutation(expression_tree, random, settings)
+                    }),
+                    probability: 0.0,
+                },
+                Mutation {
+                    name: String::from("insert"),
+                    mutation_fn: Box::new(|expression_tree, random, settings| {
+                        insert_mutation(expression_tree, random, settings)
+                    }),
+                    probability: 0.0,
+                },
+                Mutation {
+                    name: String::from("swap_arguments"),
+                    mutation_fn: Box::new(|expression_tree, random, settings| {
+                        swap_arguments_mutation(expression_tree, random, settings)
+                    }),
+                    probability: 0.0,
+                },
+                Mutation {
+                    name: String::from("gaussian_constant"),
+                    mutation_fn: Box::new(|expression_tree, random, settings| {
+                        gaussian_constant_mutation(expression_tree, random, settings)
+                    }),
+                    probability: 0.0,
+                },
+                Mutation {
+                    name: String::from("arity"),
+                    mutation_fn: Box::new(|expression_tree, random, settings| {
+                        arity_mutation(expression_tree, random, settings)
+                    }),
+                    probability: 0.0,
+                },
             ],
         };
         settings.converters = settings.get_default_converters();
@@ -65,202 +121,472 @@ impl Default for Settings {
 }
 
 impl Settings {
+    pub fn get_default_constants() -> Vec<NamedConstant> {
+        vec![
+            NamedConstant {
+                name: String::from("pi"),
+                value: PI,
+            },
+            NamedConstant {
+                name: String::from("e"),
+                value: E,
+            },
+        ]
+    }
     pub fn get_default_operators() -> Vec<Rc<Operator>> {
+        DEFAULT_OPERATORS.with(Vec::clone)
+    }
+    fn create_default_operators() -> Vec<Rc<Operator>> {
         vec![
             Rc::new(Operator {
                 name: String::from("+"),
                 arguments_number: 2,
                 precedence: 1,
                 associativity: Associativity::Left,
+                fixity: Fixity::Infix,
                 complexity: 1,
-                compute_fn: |arguments| arguments[0] + arguments[1],
+                commutative: true,
+                computation: Rc::new(|arguments: &[f64]| arguments[0] + arguments[1]),
             }),
             Rc::new(Operator {
                 name: String::from("-"),
                 arguments_number: 2,
                 precedence: 1,
                 associativity: Associativity::Left,
+                fixity: Fixity::Infix,
                 complexity: 1,
-                compute_fn: |arguments| arguments[0] - arguments[1],
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| arguments[0] - arguments[1]),
             }),
             Rc::new(Operator {
                 name: String::from("*"),
                 arguments_number: 2,
                 precedence: 2,
                 associativity: Associativity::Left,
+                fixity: Fixity::Infix,
                 complexity: 2,
-                compute_fn: |arguments| arguments[0] * arguments[1],
+                commutative: true,
+                computation: Rc::new(|arguments: &[f64]| arguments[0] * arguments[1]),
             }),
             Rc::new(Operator {
                 name: String::from("/"),
                 arguments_number: 2,
                 precedence: 2,
                 associativity: Associativity::Left,
+                fixity: Fixity::Infix,
                 complexity: 2,
-                compute_fn: |arguments| arguments[0] / arguments[1],
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| arguments[0] / arguments[1]),
             }),
             Rc::new(Operator {
                 name: String::from("^"),
                 arguments_number: 2,
                 precedence: 3,
                 associativity: Associativity::Right,
+                fixity: Fixity::Infix,
                 complexity: 3,
-                compute_fn: |arguments| arguments[0].powf(arguments[1]),
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| arguments[0].powf(arguments[1])),
             }),
             Rc::new(Operator {
                 name: String::from("+"),
                 arguments_number: 1,
                 precedence: 4,
                 associativity: Associativity::Right,
+                fixity: Fixity::Prefix,
                 complexity: 1,
-                compute_fn: |arguments| arguments[0],
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| arguments[0]),
             }),
             Rc::new(Operator {
                 name: String::from("-"),
                 arguments_number: 1,
                 precedence: 4,
                 associativity: Associativity::Right,
+                fixity: Fixity::Prefix,
                 complexity: 1,
-                compute_fn: |arguments| -arguments[0],
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| -arguments[0]),
             }),
         ]
     }
     pub fn get_default_functions() -> Vec<Rc<Function>> {
+        DEFAULT_FUNCTIONS.with(Vec::clone)
+    }
+    fn create_default_functions() -> Vec<Rc<Function>> {
         vec![
             Rc::new(Function {
                 name: String::from("abs"),
                 arguments_number: 1,
+                max_arguments_number: None,
                 complexity: 3,
-                compute_fn: |arguments| arguments[0].abs(),
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| arguments[0].abs()),
             }),
             Rc::new(Function {
                 name: String::from("log"),
                 arguments_number: 2,
+                max_arguments_number: None,
                 complexity: 4,
-                compute_fn: |arguments| arguments[0].log(arguments[1]),
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| arguments[0].log(arguments[1])),
             }),
             Rc::new(Function {
                 name: String::from("sin"),
                 arguments_number: 1,
+                max_arguments_number: None,
                 complexity: 4,
-                compute_fn: |arguments| arguments[0].sin(),
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| arguments[0].sin()),
             }),
             Rc::new(Function {
                 name: String::from("arcsin"),
                 arguments_number: 1,
+                max_arguments_number: None,
                 complexity: 5,
-                compute_fn: |arguments| arguments[0].asin(),
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| arguments[0].asin()),
             }),
             Rc::new(Function {
                 name: String::from("cos"),
                 arguments_number: 1,
+                max_arguments_number: None,
                 complexity: 4,
-                compute_fn: |arguments| arguments[0].cos(),
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| arguments[0].cos()),
             }),
             Rc::new(Function {
                 name: String::from("arccos"),
                 arguments_number: 1,
+                max_arguments_number: None,
                 complexity: 5,
-                compute_fn: |arguments| arguments[0].acos(),
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| arguments[0].acos()),
             }),
             Rc::new(Function {
                 name: String::from("tan"),
                 arguments_number: 1,
+                max_arguments_number: None,
                 complexity: 5,
-                compute_fn: |arguments| arguments[0].tan(),
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| arguments[0].tan()),
             }),
             Rc::new(Function {
                 name: String::from("arctan"),
                 arguments_number: 1,
+                max_arguments_number: None,
                 complexity: 6,
-                compute_fn: |arguments| arguments[0].atan(),
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| arguments[0].atan()),
             }),
             Rc::new(Function {
                 name: String::from("cot"),
                 arguments_number: 1,
+                max_arguments_number: None,
                 complexity: 5,
-                compute_fn: |arguments| 1.0 / arguments[0].tan(),
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| 1.0 / arguments[0].tan()),
             }),
             Rc::new(Function {
                 name: String::from("arccot"),
                 arguments_number: 1,
+                max_arguments_number: None,
                 complexity: 6,
-                compute_fn: |arguments| PI / 2.0 - arguments[0].atan(),
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| PI / 2.0 - arguments[0].atan()),
             }),
             Rc::new(Function {
                 name: String::from("sinh"),
                 arguments_number: 1,
+                max_arguments_number: None,
                 complexity: 5,
-                compute_fn: |arguments| arguments[0].sinh(),
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| arguments[0].sinh()),
             }),
             Rc::new(Function {
                 name: String::from("arsinh"),
                 arguments_number: 1,
+                max_arguments_number: None,
                 complexity: 6,
-                compute_fn: |arguments| arguments[0].asinh(),
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| arguments[0].asinh()),
             }),
             Rc::new(Function {
                 name: String::from("cosh"),
                 arguments_number: 1,
+                max_arguments_number: None,
                 complexity: 5,
-                compute_fn: |arguments| arguments[0].cosh(),
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| arguments[0].cosh()),
             }),
             Rc::new(Function {
                 name: String::from("arcosh"),
                 arguments_number: 1,
+                max_arguments_number: None,
                 complexity: 6,
-                compute_fn: |arguments| arguments[0].acosh(),
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| arguments[0].acosh()),
             }),
             Rc::new(Function {
                 name: String::from("tanh"),
                 arguments_number: 1,
+                max_arguments_number: None,
                 complexity: 6,
-                compute_fn: |arguments| arguments[0].tanh(),
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| arguments[0].tanh()),
             }),
             Rc::new(Function {
                 name: String::from("artanh"),
                 arguments_number: 1,
+                max_arguments_number: None,
                 complexity: 7,
-                compute_fn: |arguments| arguments[0].atanh(),
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| arguments[0].atanh()),
             }),
             Rc::new(Function {
                 name: String::from("coth"),
                 arguments_number: 1,
+                max_arguments_number: None,
                 complexity: 6,
-                compute_fn: |arguments| 1.0 / arguments[0].tanh(),
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| 1.0 / arguments[0].tanh()),
             }),
             Rc::new(Function {
                 name: String::from("arcoth"),
                 arguments_number: 1,
+                max_arguments_number: None,
                 complexity: 7,
-                compute_fn: |arguments| {
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| {
                     if arguments[0] < -1.0 || arguments[0] > 1.0 {
                         ((arguments[0] + 1.0) / (arguments[0] - 1.0)).ln() * 0.5
                     } else {
-                        NAN
+                        f64::NAN
                     }
-                },
+                }),
             }),
             Rc::new(Function {
                 name: String::from("ln"),
                 arguments_number: 1,
+                max_arguments_number: None,
                 complexity: 4,
-                compute_fn: |arguments| arguments[0].ln(),
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| arguments[0].ln()),
             }),
             Rc::new(Function {
                 name: String::from("exp"),
                 arguments_number: 1,
+                max_arguments_number: None,
                 complexity: 3,
-                compute_fn: |arguments| arguments[0].exp(),
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| arguments[0].exp()),
             }),
             Rc::new(Function {
                 name: String::from("sqrt"),
                 arguments_number: 1,
+                max_arguments_number: None,
                 complexity: 3,
-                compute_fn: |arguments| arguments[0].sqrt(),
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| arguments[0].sqrt()),
             }),
         ]
     }
+    /// Return the functions that accept from 2 to 4 arguments.
+    /// They aren't included in the default settings, see `Settings::add_variadic_functions`
+    /// which also enables the arity mutation changing their arguments number.
+    pub fn get_variadic_functions() -> Vec<Rc<Function>> {
+        VARIADIC_FUNCTIONS.with(Vec::clone)
+    }
+    fn create_variadic_functions() -> Vec<Rc<Function>> {
+        vec![
+            Rc::new(Function {
+                name: String::from("min"),
+                arguments_number: 2,
+                max_arguments_number: Some(4),
+                complexity: 3,
+                commutative: true,
+                computation: Rc::new(|arguments: &[f64]| {
+                    arguments.iter().copied().fold(f64::INFINITY, f64::min)
+                }),
+            }),
+            Rc::new(Function {
+                name: String::from("max"),
+                arguments_number: 2,
+                max_arguments_number: Some(4),
+                complexity: 3,
+                commutative: true,
+                computation: Rc::new(|arguments: &[f64]| {
+                    arguments.iter().copied().fold(f64::NEG_INFINITY, f64::max)
+                }),
+            }),
+            Rc::new(Function {
+                name: String::from("hypot"),
+                arguments_number: 2,
+                max_arguments_number: Some(4),
+                complexity: 4,
+                commutative: true,
+                computation: Rc::new(|arguments: &[f64]| {
+                    arguments
+                        .iter()
+                        .map(|argument| argument * argument)
+                        .sum::<f64>()
+                        .sqrt()
+                }),
+            }),
+            Rc::new(Function {
+                name: String::from("sum"),
+                arguments_number: 2,
+                max_arguments_number: Some(4),
+                complexity: 2,
+                commutative: true,
+                computation: Rc::new(|arguments: &[f64]| arguments.iter().sum()),
+            }),
+            Rc::new(Function {
+                name: String::from("mean"),
+                arguments_number: 2,
+                max_arguments_number: Some(4),
+                complexity: 3,
+                commutative: true,
+                computation: Rc::new(|arguments: &[f64]| {
+                    arguments.iter().sum::<f64>() / arguments.len() as f64
+                }),
+            }),
+        ]
+    }
+    /// Return the comparison operators, which compute 1 if the comparison holds and 0 otherwise.
+    /// They bind weaker than the arithmetic operators and aren't included in the default settings.
+    pub fn get_comparison_operators() -> Vec<Rc<Operator>> {
+        COMPARISON_OPERATORS.with(Vec::clone)
+    }
+    fn create_comparison_operators() -> Vec<Rc<Operator>> {
+        vec![
+            Rc::new(Operator {
+                name: String::from("<"),
+                arguments_number: 2,
+                precedence: 0,
+                associativity: Associativity::Left,
+                fixity: Fixity::Infix,
+                complexity: 2,
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| compare(arguments, |a, b| a < b)),
+            }),
+            Rc::new(Operator {
+                name: String::from("<="),
+                arguments_number: 2,
+                precedence: 0,
+                associativity: Associativity::Left,
+                fixity: Fixity::Infix,
+                complexity: 2,
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| compare(arguments, |a, b| a <= b)),
+            }),
+            Rc::new(Operator {
+                name: String::from(">"),
+                arguments_number: 2,
+                precedence: 0,
+                associativity: Associativity::Left,
+                fixity: Fixity::Infix,
+                complexity: 2,
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| compare(arguments, |a, b| a > b)),
+            }),
+            Rc::new(Operator {
+                name: String::from(">="),
+                arguments_number: 2,
+                precedence: 0,
+                associativity: Associativity::Left,
+                fixity: Fixity::Infix,
+                complexity: 2,
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| compare(arguments, |a, b| a >= b)),
+            }),
+            Rc::new(Operator {
+                name: String::from("=="),
+                arguments_number: 2,
+                precedence: 0,
+                associativity: Associativity::Left,
+                fixity: Fixity::Infix,
+                complexity: 2,
+                commutative: true,
+                computation: Rc::new(|arguments: &[f64]| compare(arguments, |a, b| a == b)),
+            }),
+            Rc::new(Operator {
+                name: String::from("!="),
+                arguments_number: 2,
+                precedence: 0,
+                associativity: Associativity::Left,
+                fixity: Fixity::Infix,
+                complexity: 2,
+                commutative: true,
+                computation: Rc::new(|arguments: &[f64]| compare(arguments, |a, b| a != b)),
+            }),
+        ]
+    }
+    /// Return the conditional function `if(condition, a, b)`, which computes `a` if the condition is not 0
+    /// and `b` otherwise. Simplification takes the branch of a constant condition only for this function.
+    pub fn get_if_function() -> Rc<Function> {
+        IF_FUNCTION.with(Rc::clone)
+    }
+    fn create_if_function() -> Rc<Function> {
+        Rc::new(Function {
+            name: String::from("if"),
+            arguments_number: 3,
+            max_arguments_number: None,
+            complexity: 4,
+            commutative: false,
+            computation: Rc::new(|arguments: &[f64]| {
+                if arguments[0].is_nan() {
+                    f64::NAN
+                } else if arguments[0] != 0.0 {
+                    arguments[1]
+                } else {
+                    arguments[2]
+                }
+            }),
+        })
+    }
+    /// Return the conditional and piecewise functions including the variadic `min` and `max`.
+    /// They aren't included in the default settings.
+    pub fn get_piecewise_functions() -> Vec<Rc<Function>> {
+        PIECEWISE_FUNCTIONS.with(Vec::clone)
+    }
+    fn create_piecewise_functions() -> Vec<Rc<Function>> {
+        let mut functions = vec![
+            Settings::get_if_function(),
+            Rc::new(Function {
+                name: String::from("heaviside"),
+                arguments_number: 1,
+                max_arguments_number: None,
+                complexity: 3,
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| {
+                    if arguments[0].is_nan() {
+                        f64::NAN
+                    } else if arguments[0] < 0.0 {
+                        0.0
+                    } else {
+                        1.0
+                    }
+                }),
+            }),
+            Rc::new(Function {
+                name: String::from("clamp"),
+                arguments_number: 3,
+                max_arguments_number: None,
+                complexity: 4,
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| {
+                    arguments[0].max(arguments[1]).min(arguments[2])
+                }),
+            }),
+        ];
+        functions.extend(
+            Settings::get_variadic_functions()
+                .into_iter()
+                .filter(|function| function.name == "min" || function.name == "max"),
+        );
+        functions
+    }
     pub fn get_default_converters(&self) -> Vec<Converter> {
         let circumflex = self.find_binary_operator_by_name("^").unwrap();
         let log = self.find_function_by_name("log").unwrap();
@@ -344,9 +670,21 @@ impl Settings {
     }
 }
 
+/// Compare two arguments, the result is NaN if any of them is NaN.
+fn compare(arguments: &[f64], predicate: fn(f64, f64) -> bool) -> f64 {
+    if arguments[0].is_nan() || arguments[1].is_nan() {
+        f64::NAN
+    } else if predicate(arguments[0], arguments[1]) {
+        1.0
+    } else {
+        0.0
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::expression_tree::Operation;
 
     #[test]
     fn test_get_node_probability_fn() {
@@ -359,4 +697,62 @@ mod tests {
         let actual_node_probability = settings.get_node_probability(11);
         assert_eq!(expected_node_probability, actual_node_probability);
     }
+
+    #[test]
+    fn test_get_variadic_functions() {
+        let functions = Settings::get_variadic_functions();
+        let compute = |name: &str, arguments: &[f64]| {
+            functions
+                .iter()
+                .find(|function| function.name == name)
+                .unwrap()
+                .compute(arguments)
+        };
+        assert_eq!(-1.0, compute("min", &[2.0, -1.0, 3.0]));
+        assert_eq!(3.0, compute("max", &[2.0, -1.0, 3.0]));
+        assert_eq!(5.0, compute("hypot", &[3.0, 4.0]));
+        assert_eq!(10.0, compute("sum", &[1.0, 2.0, 3.0, 4.0]));
+        assert_eq!(2.5, compute("mean", &[1.0, 2.0, 3.0, 4.0]));
+        assert_eq!(-0.5, compute("mean", &[1.0, -2.0]));
+    }
+
+    #[test]
+    fn test_get_comparison_operators() {
+        let operators = Settings::get_comparison_operators();
+        let compute = |name: &str, arguments: &[f64]| {
+            operators
+                .iter()
+                .find(|operator| operator.name == name)
+                .unwrap()
+                .compute(arguments)
+        };
+        assert_eq!(1.0, compute("<", &[1.0, 2.0]));
+        assert_eq!(0.0, compute("<=", &[3.0, 2.0]));
+        assert_eq!(1.0, compute(">=", &[2.0, 2.0]));
+        assert!(compute(">", &[f64::NAN, 2.0]).is_nan());
+        assert_eq!(1.0, compute("==", &[2.0, 2.0]));
+        assert_eq!(0.0, compute("!=", &[2.0, 2.0]));
+        assert_eq!(1.0, compute("!=", &[1.0, 2.0]));
+        assert!(compute("==", &[f64::NAN, f64::NAN]).is_nan());
+        assert!(compute("!=", &[2.0, f64::NAN]).is_nan());
+    }
+
+    #[test]
+    fn test_get_piecewise_functions() {
+        let functions = Settings::get_piecewise_functions();
+        let compute = |name: &str, arguments: &[f64]| {
+            functions
+                .iter()
+                .find(|function| function.name == name)
+                .unwrap()
+                .compute(arguments)
+        };
+        assert_eq!(2.0, compute("if", &[1.0, 2.0, 3.0]));
+        assert_eq!(3.0, compute("if", &[0.0, 2.0, 3.0]));
+        assert_eq!(0.0, compute("heaviside", &[-0.5]));
+        assert_eq!(1.0, compute("heaviside", &[0.0]));
+        assert_eq!(1.0, compute("clamp", &[-2.0, 1.0, 4.0]));
+        assert_eq!(4.0, compute("clamp", &[7.0, 1.0, 4.0]));
+        assert_eq!(1.0, compute("min", &[1.0, 4.0]));
+    }
 }
@@ -0,0 +1,547 @@
+//! Module with the special function pack enabled by the `special-functions` feature.
+use super::core::Settings;
+use super::types::{Converter, ConverterOperation};
+use crate::expression_tree::{Function, Node, Operation, ValueNode};
+use std::f64::consts::{E, FRAC_2_PI, FRAC_2_SQRT_PI, PI};
+use std::rc::Rc;
+
+/// The argument magnitude below which the error function is computed by its series.
+const ERF_SERIES_LIMIT: f64 = 0.5;
+/// The number of series terms, enough for the double precision below the limit.
+const ERF_SERIES_TERMS: u32 = 16;
+
+/// The coefficients of the Lanczos approximation of the gamma function with g = 7.
+const LANCZOS_COEFFICIENTS: [f64; 9] = [
+    0.999_999_999_999_809_9,
+    676.520_368_121_885_1,
+    -1_259.139_216_722_402_8,
+    771.323_428_777_653_1,
+    -176.615_029_162_140_6,
+    12.507_343_278_686_905,
+    -0.138_571_095_265_720_12,
+    9.984_369_578_019_572e-6,
+    1.505_632_735_149_311_6e-7,
+];
+
+thread_local! {
+    // Shared like the default operations.
+    static SPECIAL_FUNCTIONS: Vec<Rc<Function>> = Settings::create_special_functions();
+}
+
+impl Settings {
+    /// Return the special and rounding functions.
+    /// They aren't included in the default settings.
+    pub fn get_special_functions() -> Vec<Rc<Function>> {
+        SPECIAL_FUNCTIONS.with(Vec::clone)
+    }
+    fn create_special_functions() -> Vec<Rc<Function>> {
+        vec![
+            Rc::new(Function {
+                name: String::from("erf"),
+                arguments_number: 1,
+                max_arguments_number: None,
+                complexity: 5,
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| erf(arguments[0])),
+            }),
+            Rc::new(Function {
+                name: String::from("erfc"),
+                arguments_number: 1,
+                max_arguments_number: None,
+                complexity: 5,
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| erfc(arguments[0])),
+            }),
+            Rc::new(Function {
+                name: String::from("gamma"),
+                arguments_number: 1,
+                max_arguments_number: None,
+                complexity: 6,
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| gamma(arguments[0])),
+            }),
+            Rc::new(Function {
+                name: String::from("lgamma"),
+                arguments_number: 1,
+                max_arguments_number: None,
+                complexity: 6,
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| lgamma(arguments[0])),
+            }),
+            Rc::new(Function {
+                name: String::from("sigmoid"),
+                arguments_number: 1,
+                max_arguments_number: None,
+                complexity: 4,
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| 1.0 / (1.0 + (-arguments[0]).exp())),
+            }),
+            Rc::new(Function {
+                name: String::from("softplus"),
+                arguments_number: 1,
+                max_arguments_number: None,
+                complexity: 4,
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| {
+                    arguments[0].max(0.0) + (-arguments[0].abs()).exp().ln_1p()
+                }),
+            }),
+            Rc::new(Function {
+                name: String::from("floor"),
+                arguments_number: 1,
+                max_arguments_number: None,
+                complexity: 3,
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| arguments[0].floor()),
+            }),
+            Rc::new(Function {
+                name: String::from("ceil"),
+                arguments_number: 1,
+                max_arguments_number: None,
+                complexity: 3,
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| arguments[0].ceil()),
+            }),
+            Rc::new(Function {
+                name: String::from("round"),
+                arguments_number: 1,
+                max_arguments_number: None,
+                complexity: 3,
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| arguments[0].round()),
+            }),
+            Rc::new(Function {
+                name: String::from("sign"),
+                arguments_number: 1,
+                max_arguments_number: None,
+                complexity: 3,
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| {
+                    if arguments[0] == 0.0 {
+                        0.0
+                    } else {
+                        arguments[0].signum()
+                    }
+                }),
+            }),
+            Rc::new(Function {
+                name: String::from("mod"),
+                arguments_number: 2,
+                max_arguments_number: None,
+                complexity: 3,
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| {
+                    arguments[0] - arguments[1] * (arguments[0] / arguments[1]).floor()
+                }),
+            }),
+            Rc::new(Function {
+                name: String::from("j0"),
+                arguments_number: 1,
+                max_arguments_number: None,
+                complexity: 6,
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| bessel_j0(arguments[0])),
+            }),
+            Rc::new(Function {
+                name: String::from("j1"),
+                arguments_number: 1,
+                max_arguments_number: None,
+                complexity: 6,
+                commutative: false,
+                computation: Rc::new(|arguments: &[f64]| bessel_j1(arguments[0])),
+            }),
+        ]
+    }
+    /// Return the converters of the expressions equal to `sigmoid` and `softplus`
+    /// for which the settings have all the operations involved.
+    fn get_special_converters(&self) -> Vec<Converter> {
+        let mut converters = vec![];
+        if let (Some(slash), Some(sigmoid)) = (
+            self.find_binary_operator_by_name("/"),
+            self.find_function_by_name("sigmoid"),
+        ) {
+            converters.push(Converter {
+                from: ConverterOperation::Operator(slash),
+                to: ConverterOperation::Function(sigmoid),
+                is_conversion_possible_fn: |arguments| get_sigmoid_argument(arguments).is_some(),
+                convert_fn: |arguments| vec![get_sigmoid_argument(&arguments).unwrap().clone()],
+            });
+        }
+        let softplus = match self.find_function_by_name("softplus") {
+            Some(softplus) => softplus,
+            None => return converters,
+        };
+        if let Some(ln) = self.find_function_by_name("ln") {
+            converters.push(Converter {
+                from: ConverterOperation::Function(ln),
+                to: ConverterOperation::Function(Rc::clone(&softplus)),
+                is_conversion_possible_fn: |arguments| {
+                    get_one_plus_exp_argument(&arguments[0]).is_some()
+                },
+                convert_fn: |arguments| {
+                    vec![get_one_plus_exp_argument(&arguments[0]).unwrap().clone()]
+                },
+            });
+        }
+        if let Some(log) = self.find_function_by_name("log") {
+            converters.push(Converter {
+                from: ConverterOperation::Function(log),
+                to: ConverterOperation::Function(softplus),
+                is_conversion_possible_fn: |arguments| {
+                    is_constant(&arguments[0], E, 0.001)
+                        && get_one_plus_exp_argument(&arguments[1]).is_some()
+                },
+                convert_fn: |arguments| {
+                    vec![get_one_plus_exp_argument(&arguments[1]).unwrap().clone()]
+                },
+            });
+        }
+        converters
+    }
+    /// Add the special functions whose names aren't used yet and their converters,
+    /// which take precedence over the converters of the settings.
+    pub fn add_special_functions(&mut self) {
+        self.add_functions(Settings::get_special_functions());
+        let mut converters = self.get_special_converters();
+        converters.append(&mut self.converters);
+        self.converters = converters;
+    }
+}
+
+/// Return `a` if the arguments of a division are `1` and `1 + exp(-a)`.
+fn get_sigmoid_argument(arguments: &[Node]) -> Option<&Node> {
+    if !is_constant(&arguments[0], 1.0, 0.0) {
+        return None;
+    }
+    match get_one_plus_exp_argument(&arguments[1]) {
+        Some(Node::Operator(operator_node))
+            if operator_node.operation.get_name() == "-" && operator_node.arguments.len() == 1 =>
+        {
+            Some(&operator_node.arguments[0])
+        }
+        _ => None,
+    }
+}
+
+/// Return `a` if the node is `1 + exp(a)` or `exp(a) + 1`.
+fn get_one_plus_exp_argument(node: &Node) -> Option<&Node> {
+    match node {
+        Node::Operator(operator_node)
+            if operator_node.operation.get_name() == "+" && operator_node.arguments.len() == 2 =>
+        {
+            let arguments = &operator_node.arguments;
+            if is_constant(&arguments[0], 1.0, 0.0) {
+                get_exp_argument(&arguments[1])
+            } else if is_constant(&arguments[1], 1.0, 0.0) {
+                get_exp_argument(&arguments[0])
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Return `a` if the node is `exp(a)` or `e ^ a`.
+fn get_exp_argument(node: &Node) -> Option<&Node> {
+    match node {
+        Node::Function(function_node) if function_node.operation.get_name() == "exp" => {
+            Some(&function_node.arguments[0])
+        }
+        Node::Operator(operator_node)
+            if operator_node.operation.get_name() == "^"
+                && is_constant(&operator_node.arguments[0], E, 0.001) =>
+        {
+            Some(&operator_node.arguments[1])
+        }
+        _ => None,
+    }
+}
+
+fn is_constant(node: &Node, value: f64, tolerance: f64) -> bool {
+    match node {
+        Node::Value(ValueNode::Constant(constant)) => (constant - value).abs() <= tolerance,
+        _ => false,
+    }
+}
+
+/// Return the error function using its Maclaurin series for small arguments,
+/// where `1 - erfc(x)` would lose the accuracy, e.g. `erf(0)` is exactly 0.
+fn erf(x: f64) -> f64 {
+    if x.abs() >= ERF_SERIES_LIMIT {
+        return 1.0 - erfc(x);
+    }
+    let square = x * x;
+    let mut term = x;
+    let mut sum = x;
+    for n in 1..=ERF_SERIES_TERMS {
+        term *= -square / n as f64;
+        sum += term / (2 * n + 1) as f64;
+    }
+    FRAC_2_SQRT_PI * sum
+}
+
+/// Return the complementary error function with the fractional error less than 1.2e-7
+/// using the Chebyshev fitting from Numerical Recipes.
+fn erfc(x: f64) -> f64 {
+    if x.abs() < ERF_SERIES_LIMIT {
+        return 1.0 - erf(x);
+    }
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let result = t
+        * (-z * z - 1.265_512_23
+            + t * (1.000_023_68
+                + t * (0.374_091_96
+                    + t * (0.096_784_18
+                        + t * (-0.186_288_06
+                            + t * (0.278_868_07
+                                + t * (-1.135_203_98
+                                    + t * (1.488_515_87
+                                        + t * (-0.822_152_23 + t * 0.170_872_77)))))))))
+            .exp();
+    if x >= 0.0 {
+        result
+    } else {
+        2.0 - result
+    }
+}
+
+/// Return the gamma function using the Lanczos approximation and the reflection formula.
+/// The result is NaN at the poles.
+fn gamma(x: f64) -> f64 {
+    if x <= 0.0 && x == x.floor() {
+        f64::NAN
+    } else if x < 0.5 {
+        PI / ((PI * x).sin() * gamma(1.0 - x))
+    } else {
+        let (t, sum) = get_lanczos_sum(x - 1.0);
+        // The power is split, since it overflows before the product does, e.g. for gamma(150).
+        let half_power = t.powf((x - 0.5) / 2.0);
+        (2.0 * PI).sqrt() * (half_power * (-t).exp()) * half_power * sum
+    }
+}
+
+/// Return the natural logarithm of the absolute value of the gamma function.
+/// The result is infinity at the poles.
+fn lgamma(x: f64) -> f64 {
+    if x <= 0.0 && x == x.floor() {
+        f64::INFINITY
+    } else if x < 0.5 {
+        (PI / (PI * x).sin().abs()).ln() - lgamma(1.0 - x)
+    } else {
+        let (t, sum) = get_lanczos_sum(x - 1.0);
+        0.5 * (2.0 * PI).ln() + (x - 0.5) * t.ln() - t + sum.ln()
+    }
+}
+
+fn get_lanczos_sum(x: f64) -> (f64, f64) {
+    let sum = LANCZOS_COEFFICIENTS
+        .iter()
+        .enumerate()
+        .skip(1)
+        .fold(LANCZOS_COEFFICIENTS[0], |sum, (i, coefficient)| {
+            sum + coefficient / (x + i as f64)
+        });
+    (x + 7.5, sum)
+}
+
+/// Return the Bessel function of the first kind of order 0
+/// using the rational approximations from Numerical Recipes.
+fn bessel_j0(x: f64) -> f64 {
+    if x.abs() < 8.0 {
+        let y = x * x;
+        let numerator = 57_568_490_574.0
+            + y * (-13_362_590_354.0
+                + y * (651_619_640.7
+                    + y * (-11_214_424.18 + y * (77_392.330_17 + y * (-184.905_245_6)))));
+        let denominator = 57_568_490_411.0
+            + y * (1_029_532_985.0
+                + y * (9_494_680.718 + y * (59_272.648_53 + y * (267.853_271_2 + y))));
+        numerator / denominator
+    } else {
+        let ax = x.abs();
+        let z = 8.0 / ax;
+        let y = z * z;
+        let xx = ax - 0.785_398_164;
+        let p = 1.0
+            + y * (-0.109_862_862_7e-2
+                + y * (0.273_451_040_7e-4 + y * (-0.207_337_063_9e-5 + y * 0.209_388_721_1e-6)));
+        let q = -0.156_249_999_5e-1
+            + y * (0.143_048_876_5e-3
+                + y * (-0.691_114_765_1e-5 + y * (0.762_109_516_1e-6 - y * 0.934_935_152e-7)));
+        (FRAC_2_PI / ax).sqrt() * (xx.cos() * p - z * xx.sin() * q)
+    }
+}
+
+/// Return the Bessel function of the first kind of order 1
+/// using the rational approximations from Numerical Recipes.
+fn bessel_j1(x: f64) -> f64 {
+    if x.abs() < 8.0 {
+        let y = x * x;
+        let numerator = x
+            * (72_362_614_232.0
+                + y * (-7_895_059_235.0
+                    + y * (242_396_853.1
+                        + y * (-2_972_611.439 + y * (15_704.482_60 + y * (-30.160_366_06))))));
+        let denominator = 144_725_228_442.0
+            + y * (2_300_535_178.0
+                + y * (18_583_304.74 + y * (99_447.433_94 + y * (376.999_139_7 + y))));
+        numerator / denominator
+    } else {
+        let ax = x.abs();
+        let z = 8.0 / ax;
+        let y = z * z;
+        let xx = ax - 2.356_194_491;
+        let p = 1.0
+            + y * (0.183_105e-2
+                + y * (-0.351_639_649_6e-4 + y * (0.245_752_017_4e-5 + y * (-0.240_337_019e-6))));
+        let q = 0.046_874_999_95
+            + y * (-0.200_269_087_3e-3
+                + y * (0.844_919_909_6e-5 + y * (-0.882_289_87e-6 + y * 0.105_787_412e-6)));
+        let result = (FRAC_2_PI / ax).sqrt() * (xx.cos() * p - z * xx.sin() * q);
+        if x < 0.0 {
+            -result
+        } else {
+            result
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expression_tree::{ExpressionTree, OperationNode};
+
+    #[test]
+    fn test_get_special_functions() {
+        let functions = Settings::get_special_functions();
+        let compute = |name: &str, arguments: &[f64]| {
+            functions
+                .iter()
+                .find(|function| function.name == name)
+                .unwrap()
+                .compute(arguments)
+        };
+        for (name, argument, expected) in [
+            ("erf", 0.5, 0.520_499_877_813_046_5),
+            ("erfc", -1.0, 1.842_700_792_949_715),
+            ("gamma", 5.0, 24.0),
+            ("gamma", -0.5, -3.544_907_701_811_032),
+            ("lgamma", 100.0, 359.134_205_369_575_4),
+            ("sigmoid", 0.0, 0.5),
+            ("softplus", -800.0, 0.0),
+            ("j0", 2.5, -0.048_383_776_468_197_99),
+            ("j1", -10.0, -0.043_472_746_168_861_44),
+        ] {
+            let actual = compute(name, &[argument]);
+            assert!(
+                (expected - actual).abs() <= 1e-6 * expected.abs().max(1.0),
+                "{}({}) = {}, but expected {}",
+                name,
+                argument,
+                actual,
+                expected
+            );
+        }
+        assert!(compute("gamma", &[-2.0]).is_nan());
+        assert_eq!(0.0, compute("erf", &[0.0]));
+        assert_eq!(1.0, compute("erfc", &[0.0]));
+        let erf = compute("erf", &[1e-8]);
+        assert!((erf - 1e-8 * FRAC_2_SQRT_PI).abs() <= 1e-15 * erf);
+        assert!((compute("erf", &[0.3]) + compute("erf", &[-0.3])).abs() <= f64::EPSILON);
+        let gamma = compute("gamma", &[150.0]);
+        assert!((gamma - 3.808_922_637_630_570_3e260).abs() <= 1e-9 * gamma);
+        assert_eq!(-3.0, compute("floor", &[-2.5]));
+        assert_eq!(-3.0, compute("round", &[-2.5]));
+        assert_eq!(0.0, compute("sign", &[0.0]));
+        assert_eq!(1.0, compute("mod", &[-5.0, 3.0]));
+    }
+
+    #[test]
+    fn test_special_functions_to_latex() {
+        let mut settings = Settings::default();
+        settings.add_special_functions();
+        let expression_tree = ExpressionTree::parse(
+            "gamma(x) * erf(x) - lgamma(x) + j0(x) / floor(x) - sign(x) * erfc(j1(x))",
+            &settings,
+        )
+        .unwrap();
+        assert_eq!(
+            concat!(
+                r"\Gamma\left(x\right) \cdot \operatorname{erf}\left(x\right) - \ln\Gamma\left(x\right)",
+                r" + \frac{J_0\left(x\right)}{\left\lfloor x\right\rfloor} - \operatorname{sgn}\left(x\right)",
+                r" \cdot \operatorname{erfc}\left(J_1\left(x\right)\right)",
+            ),
+            expression_tree.to_latex()
+        );
+    }
+
+    #[test]
+    fn test_add_special_functions() {
+        let mut settings = Settings::default();
+        settings.add_special_functions();
+        for (expression, expected_expression) in [
+            ("2 * (1 / (1 + exp(-x)))", "2 * sigmoid(x)"),
+            ("ln(1 + exp(2 * x)) - erf(x)", "softplus(2 * x) - erf(x)"),
+            ("1 / (exp(x) + 1)", "1 / (exp(x) + 1)"),
+        ] {
+            let expression_tree = ExpressionTree::parse(expression, &settings).unwrap();
+            assert_eq!(
+                expected_expression,
+                expression_tree.create_output(&settings).to_string()
+            );
+        }
+    }
+
+    #[test]
+    fn test_add_special_functions_without_operations() {
+        let mut settings = Settings::default();
+        settings.operators.retain(|operator| operator.name != "/");
+        settings.functions.retain(|function| function.name != "ln");
+        let converters_number = settings.converters.len();
+        settings.add_special_functions();
+        assert_eq!(converters_number + 1, settings.converters.len());
+        let expression_tree = ExpressionTree::parse("log(e, 1 + exp(x))", &settings).unwrap();
+        assert_eq!("softplus(x)", expression_tree.to_string());
+    }
+
+    #[test]
+    fn test_create_output_sigmoid() {
+        let mut settings = Settings::default();
+        settings.add_special_functions();
+        let expression_tree = ExpressionTree {
+            root: Node::Operator(OperationNode {
+                operation: settings.find_binary_operator_by_name("/").unwrap(),
+                arguments: vec![
+                    Node::Value(ValueNode::Constant(1.0)),
+                    Node::Operator(OperationNode {
+                        operation: settings.find_binary_operator_by_name("+").unwrap(),
+                        arguments: vec![
+                            Node::Operator(OperationNode {
+                                operation: settings.find_binary_operator_by_name("^").unwrap(),
+                                arguments: vec![
+                                    Node::Value(ValueNode::Constant(E)),
+                                    Node::Operator(OperationNode {
+                                        operation: settings
+                                            .find_unary_operator_by_name("-")
+                                            .unwrap(),
+                                        arguments: vec![Node::Value(ValueNode::Variable(
+                                            String::from("x"),
+                                        ))],
+                                    }),
+                                ],
+                            }),
+                            Node::Value(ValueNode::Constant(1.0)),
+                        ],
+                    }),
+                ],
+            }),
+            variables: vec![String::from("x")],
+        };
+        assert_eq!(
+            "sigmoid(x)",
+            expression_tree.create_output(&settings).to_string()
+        );
+    }
+}
@@ -1,5 +1,6 @@
 //! Module with settings types.
-use crate::expression_tree::types::{Function, Node, OperationNode, Operator};
+use crate::expression_tree::types::{ExpressionTree, Function, Node, OperationNode, Operator};
+use serde::Deserialize;
 use std::cmp::PartialEq;
 use std::fmt;
 use std::rc::Rc;
@@ -69,10 +70,221 @@ impl ConvertOutputData {
     }
 }
 
+/// Constant referred to by name in parsed expressions, e.g. `pi`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct NamedConstant {
+    pub name: String,
+    pub value: f64,
+}
+
+/// Function declared by an expression of its parameters, e.g. `sigmoid(a) = 1 / (1 + exp(-a))`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct FunctionDeclaration {
+    pub expression: String,
+    pub complexity: u32,
+}
+
+/// The method of creating random expression trees for the initial generation and subtree mutations.
+/// The depth of a tree consisting of a single leaf is 0.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(try_from = "InitializationData")]
+pub enum Initialization {
+    /// Grow trees according to the node probability which depends on the tree complexity.
+    Complexity,
+    /// Choose each node between operations and leaves while the depth is within the bounds.
+    Grow { min_depth: usize, max_depth: usize },
+    /// Make all leaves have the maximum depth.
+    Full { max_depth: usize },
+    /// Use grow and full methods equally with the maximum depth ramped between the bounds.
+    RampedHalfAndHalf { min_depth: usize, max_depth: usize },
+}
+
+/// Deserialized initialization whose depth bounds aren't checked yet.
+#[derive(Deserialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+enum InitializationData {
+    Complexity,
+    Grow { min_depth: usize, max_depth: usize },
+    Full { max_depth: usize },
+    RampedHalfAndHalf { min_depth: usize, max_depth: usize },
+}
+
+impl TryFrom<InitializationData> for Initialization {
+    type Error = String;
+
+    fn try_from(data: InitializationData) -> Result<Self, Self::Error> {
+        let initialization = match data {
+            InitializationData::Complexity => Initialization::Complexity,
+            InitializationData::Grow {
+                min_depth,
+                max_depth,
+            } => Initialization::Grow {
+                min_depth,
+                max_depth,
+            },
+            InitializationData::Full { max_depth } => Initialization::Full { max_depth },
+            InitializationData::RampedHalfAndHalf {
+                min_depth,
+                max_depth,
+            } => Initialization::RampedHalfAndHalf {
+                min_depth,
+                max_depth,
+            },
+        };
+        initialization.validate()?;
+        Ok(initialization)
+    }
+}
+
+impl Initialization {
+    /// Check that the minimum depth doesn't exceed the maximum one.
+    fn validate(&self) -> Result<(), String> {
+        match *self {
+            Initialization::Grow {
+                min_depth,
+                max_depth,
+            }
+            | Initialization::RampedHalfAndHalf {
+                min_depth,
+                max_depth,
+            } if min_depth > max_depth => Err(format!(
+                "The minimum depth {} exceeds the maximum depth {}.",
+                min_depth, max_depth
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Hard limits on the size of offspring created by crossover and mutations.
+#[derive(Debug, Clone, PartialEq, Default, Deserialize)]
+pub struct BloatLimits {
+    pub max_depth: Option<usize>,
+    pub max_node_number: Option<usize>,
+    #[serde(default)]
+    pub policy: BloatPolicy,
+}
+
+/// What to do with offspring exceeding the bloat limits.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BloatPolicy {
+    /// Drop the offspring, so the generation gets fewer offspring.
+    Reject,
+    /// Cross other parents until the offspring is within the limits,
+    /// and use a copy of the parent as `ReplaceWithParent` does once the attempts run out.
+    Retry { attempt_number: usize },
+    /// Use a copy of the parent that the offspring was created from instead of the offspring.
+    #[default]
+    ReplaceWithParent,
+}
+
+impl BloatLimits {
+    pub fn is_satisfied(&self, expression_tree: &ExpressionTree) -> bool {
+        if let Some(max_depth) = self.max_depth {
+            if expression_tree.get_depth() > max_depth {
+                return false;
+            }
+        }
+        if let Some(max_node_number) = self.max_node_number {
+            if expression_tree.count_nodes() > max_node_number {
+                return false;
+            }
+        }
+        true
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::super::core::Settings;
     use super::*;
+    use crate::expression_tree::types::ValueNode;
+
+    #[test]
+    fn test_deserialize_initialization() -> Result<(), serde_json::Error> {
+        assert_eq!(
+            Initialization::RampedHalfAndHalf {
+                min_depth: 2,
+                max_depth: 6
+            },
+            serde_json::from_str::<Initialization>(
+                r#"{"method": "ramped_half_and_half", "min_depth": 2, "max_depth": 6}"#
+            )?
+        );
+        assert_eq!(
+            Initialization::Complexity,
+            serde_json::from_str::<Initialization>(r#"{"method": "complexity"}"#)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_initialization_invalid_depths() {
+        for method in ["grow", "ramped_half_and_half"] {
+            let error = serde_json::from_str::<Initialization>(&format!(
+                r#"{{"method": "{}", "min_depth": 4, "max_depth": 3}}"#,
+                method
+            ))
+            .unwrap_err();
+            assert!(error
+                .to_string()
+                .starts_with("The minimum depth 4 exceeds the maximum depth 3."));
+        }
+        assert!(serde_json::from_str::<Initialization>(
+            r#"{"method": "grow", "min_depth": 3, "max_depth": 3}"#
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_bloat_limits_is_satisfied() {
+        let settings = Settings::default();
+        let expression_tree = ExpressionTree {
+            root: Node::Function(OperationNode {
+                operation: settings.find_function_by_name("sin").unwrap(),
+                arguments: vec![Node::Operator(OperationNode {
+                    operation: settings.find_binary_operator_by_name("+").unwrap(),
+                    arguments: vec![
+                        Node::Value(ValueNode::Variable(String::from("x"))),
+                        Node::Value(ValueNode::Constant(1.0)),
+                    ],
+                })],
+            }),
+            variables: vec![String::from("x")],
+        };
+        assert!(BloatLimits::default().is_satisfied(&expression_tree));
+        let mut bloat_limits = BloatLimits {
+            max_depth: Some(2),
+            max_node_number: Some(4),
+            policy: BloatPolicy::Reject,
+        };
+        assert!(bloat_limits.is_satisfied(&expression_tree));
+        bloat_limits.max_depth = Some(1);
+        assert!(!bloat_limits.is_satisfied(&expression_tree));
+        bloat_limits.max_depth = None;
+        bloat_limits.max_node_number = Some(3);
+        assert!(!bloat_limits.is_satisfied(&expression_tree));
+    }
+
+    #[test]
+    fn test_deserialize_bloat_limits() -> Result<(), serde_json::Error> {
+        assert_eq!(
+            BloatLimits {
+                max_depth: Some(8),
+                max_node_number: None,
+                policy: BloatPolicy::Retry { attempt_number: 3 }
+            },
+            serde_json::from_str::<BloatLimits>(
+                r#"{"max_depth": 8, "policy": {"type": "retry", "attempt_number": 3}}"#
+            )?
+        );
+        assert_eq!(
+            BloatPolicy::ReplaceWithParent,
+            serde_json::from_str::<BloatLimits>(r#"{"max_node_number": 30}"#)?.policy
+        );
+        Ok(())
+    }
 
     mod converter_tests {
         use super::*;
@@ -85,8 +297,8 @@ mod tests {
             assert_eq!(
                 format!(
                     "Converter {{ from: {:?}, to: {:?} }}",
-                    ConverterOperation::Function(Rc::new(create_log_function())),
-                    ConverterOperation::Function(Rc::new(create_ln_function())),
+                    ConverterOperation::Function(create_log_function()),
+                    ConverterOperation::Function(create_ln_function()),
                 ),
                 format!("{:?}", test_converter)
             );
@@ -96,14 +308,14 @@ mod tests {
             let test_converter1 = create_log_to_ln_converter();
             let mut test_converter2 = create_log_to_ln_converter();
             assert!(test_converter1 == test_converter2);
-            test_converter2.from = ConverterOperation::Function(Rc::new(create_ln_function()));
+            test_converter2.from = ConverterOperation::Function(create_ln_function());
             assert!(test_converter1 != test_converter2);
         }
 
         #[test]
         fn test_is_conversion_possible() {
-            let log_function = Rc::new(create_log_function());
-            let ln_function = Rc::new(create_ln_function());
+            let log_function = create_log_function();
+            let ln_function = create_ln_function();
             let converter = create_log_to_ln_converter();
             let possible_arguments = vec![
                 Node::Value(ValueNode::Constant(E + 0.0001)),
@@ -125,7 +337,7 @@ mod tests {
 
         #[test]
         fn test_convert() {
-            let ln_function = Rc::new(create_ln_function());
+            let ln_function = create_ln_function();
             let converter = create_log_to_ln_converter();
             let arguments = vec![
                 Node::Value(ValueNode::Constant(E + 0.0001)),
@@ -165,14 +377,14 @@ mod tests {
         #[test]
         fn test_convert_output_data_to_node_function() {
             let function_output_data = ConvertOutputData {
-                operation: ConverterOperation::Function(Rc::new(create_log_function())),
+                operation: ConverterOperation::Function(create_log_function()),
                 arguments: vec![
                     Node::Value(ValueNode::Variable(String::from("x"))),
                     Node::Value(ValueNode::Constant(1.0)),
                 ],
             };
             let expected_node = Node::Function(OperationNode {
-                operation: Rc::new(create_log_function()),
+                operation: create_log_function(),
                 arguments: vec![
                     Node::Value(ValueNode::Variable(String::from("x"))),
                     Node::Value(ValueNode::Constant(1.0)),
@@ -181,28 +393,18 @@ mod tests {
             assert_eq!(expected_node, function_output_data.to_node());
         }
 
-        fn create_log_function() -> Function {
-            Function {
-                name: String::from("log"),
-                arguments_number: 2,
-                complexity: 1,
-                compute_fn: |arguments| arguments[0].log(arguments[1]),
-            }
+        fn create_log_function() -> Rc<Function> {
+            Settings::default().find_function_by_name("log").unwrap()
         }
 
-        fn create_ln_function() -> Function {
-            Function {
-                name: String::from("ln"),
-                arguments_number: 1,
-                complexity: 1,
-                compute_fn: |arguments| arguments[0].ln(),
-            }
+        fn create_ln_function() -> Rc<Function> {
+            Settings::default().find_function_by_name("ln").unwrap()
         }
 
         fn create_log_to_ln_converter() -> Converter {
             Converter {
-                from: ConverterOperation::Function(Rc::new(create_log_function())),
-                to: ConverterOperation::Function(Rc::new(create_ln_function())),
+                from: ConverterOperation::Function(create_log_function()),
+                to: ConverterOperation::Function(create_ln_function()),
                 is_conversion_possible_fn: |arguments| {
                     if let Node::Value(ValueNode::Constant(constant)) = arguments[0] {
                         (constant - E).abs() <= 0.001